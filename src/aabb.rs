@@ -13,20 +13,28 @@ impl AABB {
         return AABB { minimum, maximum };
     }
     pub fn does_hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
-        for a in 0..2 {
+        return self.hit_interval(ray, t_min, t_max).is_some();
+    }
+
+    /// The range of t for which the ray is inside the box, clipped to
+    /// `t_min..t_max`.
+    pub fn hit_interval(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for a in 0..3 {
             let inv_d = 1.0 / ray.direction.get_by_index(a);
             let mut t0 = (self.minimum.get_by_index(a) - ray.origin.get_by_index(a)) * inv_d;
-            let mut t1 = (self.minimum.get_by_index(a) - ray.origin.get_by_index(a)) * inv_d;
+            let mut t1 = (self.maximum.get_by_index(a) - ray.origin.get_by_index(a)) * inv_d;
             if inv_d < 0.0 {
                 std::mem::swap(&mut t0, &mut t1);
             }
-            let t_min = if t0 > t_min { t0 } else { t_min };
-            let t_max = if t1 < t_max { t1 } else { t_max };
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
             if t_max <= t_min {
-                return false;
+                return None;
             }
         }
-        return true;
+        return Some((t_min, t_max));
     }
 }
 
@@ -47,3 +55,25 @@ impl Add for AABB {
         return AABB::new(new_minimum, new_maximum);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interval_is_clipped_by_every_axis() {
+        let b = AABB::new(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 2.0, 3.0));
+        let ray = Ray::new(V3::new(0.5, 1.0, -5.0), V3::new(0.0, 0.0, 1.0), 0.0);
+        let (t_0, t_1) = b.hit_interval(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!(f64::abs(t_0 - 5.0) < 1e-9);
+        assert!(f64::abs(t_1 - 8.0) < 1e-9);
+    }
+
+    #[test]
+    fn ray_passing_beside_the_box_in_z_misses() {
+        // only the z axis separates the ray from the box
+        let b = AABB::new(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(V3::new(-5.0, 0.5, 2.0), V3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(!b.does_hit(&ray, 0.0, f64::INFINITY));
+    }
+}
@@ -0,0 +1,319 @@
+use std::sync::Arc;
+
+use crate::{
+    colour::Colour,
+    hittable::HitRecord,
+    material::{BsdfSample, Material},
+    ray::Ray,
+    texture::Texture,
+    v3::{unit_vector, V3},
+};
+
+/// A material whose shading normal is tilted by a tangent space normal map:
+/// a texture whose red, green and blue give the normal along u, along v, and
+/// out of the surface, each mapped from -1..1 to 0..1. Only works on shapes
+/// which give their uv derivatives.
+pub struct NormalMap {
+    material: Arc<dyn Material + Send + Sync>,
+    normals: Arc<dyn Texture + Send + Sync>,
+    /// How much of the map's tilt to use: 0 for none, 1 for all of it
+    strength: f64,
+}
+
+impl NormalMap {
+    pub fn new<T: Texture + Send + Sync + 'static>(
+        material: Arc<dyn Material + Send + Sync>,
+        normals: T,
+        strength: f64,
+    ) -> Self {
+        return NormalMap {
+            material,
+            normals: Arc::new(normals),
+            strength,
+        };
+    }
+
+    /// The hit with its shading normal tilted by the map.
+    fn mapped(&self, ray_in: &Ray, hit_record: &HitRecord) -> HitRecord {
+        let normal = hit_record.outward_shading_normal();
+        let tangent = hit_record.dpdu - V3::dot(hit_record.dpdu, normal) * normal;
+        if tangent.near_zero() {
+            return hit_record.clone();
+        }
+        let tangent = unit_vector(tangent);
+        let mut bitangent = V3::cross(normal, tangent);
+        if V3::dot(bitangent, hit_record.dpdv) < 0.0 {
+            bitangent = -bitangent;
+        }
+
+        let (u, v, p) = (hit_record.u, hit_record.v, hit_record.point);
+        let encoded = match hit_record.footprint(ray_in) {
+            Some(footprint) => self.normals.colour_filtered(u, v, p, &footprint),
+            None => self.normals.colour(u, v, p),
+        };
+        let x = self.strength * (2.0 * encoded.red - 1.0);
+        let y = self.strength * (2.0 * encoded.green - 1.0);
+        let z = 2.0 * encoded.blue - 1.0;
+        let mapped = x * tangent + y * bitangent + f64::max(z, 0.0) * normal;
+
+        let mut mapped_hit = hit_record.clone();
+        mapped_hit.set_shading_normal(mapped);
+        return mapped_hit;
+    }
+}
+
+impl Material for NormalMap {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        return self
+            .material
+            .scatter(ray_in, &self.mapped(ray_in, hit_record));
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        return self
+            .material
+            .sample(ray_in, &self.mapped(ray_in, hit_record));
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> Colour {
+        return self
+            .material
+            .eval(ray_in, &self.mapped(ray_in, hit_record), wi);
+    }
+
+    fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> f64 {
+        return self
+            .material
+            .pdf(ray_in, &self.mapped(ray_in, hit_record), wi);
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: V3) -> Colour {
+        return self.material.emitted(u, v, p);
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: V3) -> f64 {
+        return self.material.alpha(u, v, p);
+    }
+}
+
+/// A material whose shading normal is tilted as if the surface were raised
+/// by `scale` times the brightness of a texture. Only works on shapes which
+/// give their uv derivatives.
+pub struct BumpMap {
+    material: Arc<dyn Material + Send + Sync>,
+    heights: Arc<dyn Texture + Send + Sync>,
+    scale: f64,
+}
+
+/// The step in u or v to measure the slope of a bump map over, when the
+/// pixel's footprint isn't known.
+const BUMP_STEP: f64 = 0.0005;
+
+impl BumpMap {
+    pub fn new<T: Texture + Send + Sync + 'static>(
+        material: Arc<dyn Material + Send + Sync>,
+        heights: T,
+        scale: f64,
+    ) -> Self {
+        return BumpMap {
+            material,
+            heights: Arc::new(heights),
+            scale,
+        };
+    }
+
+    fn height(&self, u: f64, v: f64, p: V3) -> f64 {
+        let colour = self.heights.colour(u, v, p);
+        return self.scale * (colour.red + colour.green + colour.blue) / 3.0;
+    }
+
+    /// The hit with its shading normal tilted by the bumps.
+    fn mapped(&self, ray_in: &Ray, hit_record: &HitRecord) -> HitRecord {
+        let (dpdu, dpdv) = (hit_record.dpdu, hit_record.dpdv);
+        if V3::cross(dpdu, dpdv).near_zero() {
+            return hit_record.clone();
+        }
+        // measure the slope over about a pixel, so the bumps don't alias
+        let (du, dv) = match hit_record.footprint(ray_in) {
+            Some(f) => (
+                0.5 * (f64::abs(f.dudx) + f64::abs(f.dudy)),
+                0.5 * (f64::abs(f.dvdx) + f64::abs(f.dvdy)),
+            ),
+            None => (0.0, 0.0),
+        };
+        let du = if du > 0.0 { du } else { BUMP_STEP };
+        let dv = if dv > 0.0 { dv } else { BUMP_STEP };
+
+        let (u, v, p) = (hit_record.u, hit_record.v, hit_record.point);
+        let height = self.height(u, v, p);
+        let slope_u = (self.height(u + du, v, p + du * dpdu) - height) / du;
+        let slope_v = (self.height(u, v + dv, p + dv * dpdv) - height) / dv;
+
+        // the bumped surface moves out along the normal as well as along u
+        // and v
+        let normal = hit_record.outward_shading_normal();
+        let bumped_dpdu = dpdu + slope_u * normal;
+        let bumped_dpdv = dpdv + slope_v * normal;
+        let mut bumped = V3::cross(bumped_dpdu, bumped_dpdv);
+        if V3::dot(V3::cross(dpdu, dpdv), normal) < 0.0 {
+            bumped = -bumped;
+        }
+
+        let mut bumped_hit = hit_record.clone();
+        bumped_hit.set_shading_normal(bumped);
+        return bumped_hit;
+    }
+}
+
+impl Material for BumpMap {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        return self
+            .material
+            .scatter(ray_in, &self.mapped(ray_in, hit_record));
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        return self
+            .material
+            .sample(ray_in, &self.mapped(ray_in, hit_record));
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> Colour {
+        return self
+            .material
+            .eval(ray_in, &self.mapped(ray_in, hit_record), wi);
+    }
+
+    fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> f64 {
+        return self
+            .material
+            .pdf(ray_in, &self.mapped(ray_in, hit_record), wi);
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: V3) -> Colour {
+        return self.material.emitted(u, v, p);
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: V3) -> f64 {
+        return self.material.alpha(u, v, p);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hittable::{Hittable, XZRect},
+        material::{tests::check_sampling_at, Lambertian},
+        texture::SolidColour,
+    };
+
+    /// Scatters straight out along the shading normal, to show it.
+    struct ShowNormal;
+
+    impl Material for ShowNormal {
+        fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+            let ray = Ray::new(hit_record.point, hit_record.shading_normal, ray_in.time);
+            return Some((ray, Colour::new(1.0, 1.0, 1.0)));
+        }
+    }
+
+    /// Shows u as a grey level.
+    struct RisingWithU;
+
+    impl Texture for RisingWithU {
+        fn colour(&self, u: f64, _v: f64, _p: V3) -> Colour {
+            return Colour::new(u, u, u);
+        }
+    }
+
+    fn assert_close_v3(a: V3, b: V3) {
+        assert!(
+            (a - b).length() < 1e-6,
+            "expected ({}, {}, {}), got ({}, {}, {})",
+            b.x,
+            b.y,
+            b.z,
+            a.x,
+            a.y,
+            a.z
+        );
+    }
+
+    /// The shading normal `material` gives the middle of a flat square,
+    /// seen from above, with u along x and v along z.
+    fn shading_normal(material: Arc<dyn Material + Send + Sync>) -> V3 {
+        let square = XZRect::new(0.0, 1.0, 0.0, 1.0, 0.0, material.clone());
+        let ray = Ray::new(V3::new(0.5, 1.0, 0.5), V3::new(0.0, -1.0, 0.0), 0.0);
+        let hit_record = square.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_close_v3(hit_record.normal, V3::new(0.0, 1.0, 0.0));
+        let (scattered, _) = material.scatter(&ray, &hit_record).unwrap();
+        return scattered.direction;
+    }
+
+    #[test]
+    fn flat_normal_map_leaves_the_normal() {
+        let flat = SolidColour::new(Colour::new(0.5, 0.5, 1.0));
+        let material = NormalMap::new(Arc::new(ShowNormal), flat, 1.0);
+        assert_close_v3(shading_normal(Arc::new(material)), V3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn normal_map_tilts_along_the_tangent() {
+        // 0.6 along u and 0.8 out of the surface
+        let tilted = || SolidColour::new(Colour::new(0.8, 0.5, 0.9));
+        let material = NormalMap::new(Arc::new(ShowNormal), tilted(), 1.0);
+        assert_close_v3(shading_normal(Arc::new(material)), V3::new(0.6, 0.8, 0.0));
+        let material = NormalMap::new(Arc::new(ShowNormal), tilted(), 0.0);
+        assert_close_v3(shading_normal(Arc::new(material)), V3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn bump_map_tilts_away_from_rising_ground() {
+        let flat = SolidColour::new(Colour::new(0.3, 0.3, 0.3));
+        let material = BumpMap::new(Arc::new(ShowNormal), flat, 1.0);
+        assert_close_v3(shading_normal(Arc::new(material)), V3::new(0.0, 1.0, 0.0));
+        // rising by one over the square in x
+        let material = BumpMap::new(Arc::new(ShowNormal), RisingWithU, 1.0);
+        let expected = unit_vector(V3::new(-1.0, 1.0, 0.0));
+        assert_close_v3(shading_normal(Arc::new(material)), expected);
+    }
+
+    #[test]
+    fn tilted_normals_do_not_scatter_into_the_surface() {
+        // almost flat against the surface
+        let steep = SolidColour::new(Colour::new(1.0, 0.5, 0.5));
+        let lambertian = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+        let material: Arc<dyn Material + Send + Sync> =
+            Arc::new(NormalMap::new(lambertian, steep, 1.0));
+        let square = XZRect::new(0.0, 1.0, 0.0, 1.0, 0.0, material.clone());
+        let ray = Ray::new(V3::new(0.5, 1.0, 0.5), V3::new(0.0, -1.0, 0.0), 0.0);
+        let hit_record = square.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        for _ in 0..200 {
+            let (scattered, _) = material.scatter(&ray, &hit_record).unwrap();
+            assert!(scattered.direction.y >= 0.0);
+        }
+    }
+
+    #[test]
+    fn sampling_sees_the_tilted_normal() {
+        let lambertian = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+        let tilted = SolidColour::new(Colour::new(0.8, 0.5, 0.9));
+        let normal_map: Arc<dyn Material + Send + Sync> =
+            Arc::new(NormalMap::new(lambertian.clone(), tilted, 1.0));
+        let bump_map: Arc<dyn Material + Send + Sync> =
+            Arc::new(BumpMap::new(lambertian, RisingWithU, 1.0));
+        let ray = Ray::new(V3::new(0.0, 1.0, 0.5), V3::new(0.5, -1.0, 0.0), 0.0);
+        for (material, normal) in [
+            (normal_map, V3::new(0.6, 0.8, 0.0)),
+            (bump_map, unit_vector(V3::new(-1.0, 1.0, 0.0))),
+        ] {
+            let square = XZRect::new(0.0, 1.0, 0.0, 1.0, 0.0, material.clone());
+            let hit_record = square.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            let up = V3::new(0.0, 1.0, 0.0);
+            let along_normal = material.eval(&ray, &hit_record, normal);
+            assert!(along_normal.red > material.eval(&ray, &hit_record, up).red);
+            check_sampling_at(&ray, &hit_record);
+        }
+    }
+}
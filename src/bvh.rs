@@ -2,20 +2,40 @@ use std::{cmp::Ordering, sync::Arc};
 
 use crate::{
     aabb::AABB,
-    hittable::{HitRecord, Hittable},
+    hittable::{HitRecord, Hittable, HittableList},
     ray::Ray,
+    v3::V3,
 };
 
 pub struct BVHNode {
     left: Arc<dyn Hittable + Sync + Send>,
     right: Arc<dyn Hittable + Sync + Send>,
     bounding_box: AABB,
+    /// Hittables with no bounding box (such as infinite planes), which can't
+    /// be placed in the tree and so are tested against every ray.
+    unbounded: Vec<Arc<dyn Hittable + Sync + Send>>,
 }
 
 impl BVHNode {
     pub fn new(hittable_list: Vec<Arc<dyn Hittable + Send + Sync>>, t_0: f64, t_1: f64) -> BVHNode {
-        let length = hittable_list.len();
-        return make_bvh_node(&mut hittable_list.clone(), 0, length, t_0, t_1);
+        let (mut bounded, unbounded): (Vec<_>, Vec<_>) = hittable_list
+            .into_iter()
+            .partition(|hittable| hittable.bounding_box(t_0, t_1).is_some());
+        let length = bounded.len();
+        let mut node = if length == 0 {
+            let empty = Arc::new(HittableList::new());
+            let origin = V3::new(0.0, 0.0, 0.0);
+            BVHNode {
+                left: empty.clone(),
+                right: empty,
+                bounding_box: AABB::new(origin, origin),
+                unbounded: vec![],
+            }
+        } else {
+            make_bvh_node(&mut bounded, 0, length, t_0, t_1)
+        };
+        node.unbounded = unbounded;
+        return node;
     }
 }
 
@@ -82,7 +102,7 @@ fn make_bvh_node(
 
             let mid = start + object_span / 2;
             let left = make_bvh_node(objects, start, mid, t_0, t_1);
-            let right = make_bvh_node(objects, mid, end, t_1, t_1);
+            let right = make_bvh_node(objects, mid, end, t_0, t_1);
             (Arc::new(left), Arc::new(right))
         }
     };
@@ -94,11 +114,36 @@ fn make_bvh_node(
         left,
         right,
         bounding_box,
+        unbounded: vec![],
     };
 }
 
 impl Hittable for BVHNode {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        if !self.unbounded.is_empty() {
+            let mut record: Option<HitRecord> = None;
+            let mut closest_t = t_max;
+            for hittable in &self.unbounded {
+                if let Some(hittable_record) = hittable.hit(ray, t_min, closest_t) {
+                    closest_t = hittable_record.time;
+                    record = Some(hittable_record);
+                }
+            }
+            return self.hit_tree(ray, t_min, closest_t).or(record);
+        }
+        return self.hit_tree(ray, t_min, t_max);
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        if !self.unbounded.is_empty() {
+            return None;
+        }
+        return Some(self.bounding_box);
+    }
+}
+
+impl BVHNode {
+    fn hit_tree(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
         if !self.bounding_box.does_hit(ray, t_min, t_max) {
             return None;
         }
@@ -113,8 +158,32 @@ impl Hittable for BVHNode {
             return self.right.hit(ray, t_min, t_max);
         }
     }
+}
 
-    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
-        return Some(self.bounding_box);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{colour::Colour, hittable::MovingSphere, material::Lambertian};
+
+    #[test]
+    fn moving_hittables_are_found_over_the_whole_shutter() {
+        let material = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+        let hittables: Vec<Arc<dyn Hittable + Send + Sync>> = (0..4)
+            .map(|i| {
+                let centre = V3::new(10.0 * i as f64, 0.0, 0.0);
+                let end = centre + V3::new(0.0, 5.0, 0.0);
+                let sphere = MovingSphere::new(centre, end, 0.0, 1.0, 1.0, material.clone());
+                Arc::new(sphere) as Arc<dyn Hittable + Send + Sync>
+            })
+            .collect();
+        let bvh = BVHNode::new(hittables, 0.0, 1.0);
+        for i in 0..4 {
+            for (time, y) in [(0.0, 0.0), (1.0, 5.0)] {
+                let origin = V3::new(10.0 * i as f64, y, 10.0);
+                let ray = Ray::new(origin, V3::new(0.0, 0.0, -1.0), time);
+                let hit = bvh.hit(&ray, 0.001, f64::INFINITY).unwrap();
+                assert!(f64::abs(hit.time - 9.0) < 1e-6);
+            }
+        }
     }
 }
@@ -1,5 +1,6 @@
 use crate::{
-    ray::Ray,
+    ray::{Ray, RayDifferentials},
+    utils::scale,
     v3::{random_in_unit_disk, unit_vector, V3},
 };
 
@@ -11,9 +12,13 @@ pub struct Camera {
     u: V3,
     v: V3,
     lens_radius: f64,
+    /// shutter open/close times
+    time_0: f64,
+    time_1: f64,
 }
 
 impl Camera {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         look_from: V3,
         look_at: V3,
@@ -22,6 +27,8 @@ impl Camera {
         aspect_ratio: f64,
         aperture: f64,
         focus_dist: f64,
+        time_0: f64,
+        time_1: f64,
     ) -> Camera {
         let theta = vertical_field_of_view.to_radians();
         let h = f64::tan(theta / 2.0);
@@ -47,14 +54,28 @@ impl Camera {
             u,
             v,
             lens_radius,
+            time_0,
+            time_1,
         };
     }
 
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
+    /// The ray through `s`, `t` on the viewport (each from 0 to 1), with
+    /// differentials for neighbouring pixels `pixel_width` and `pixel_height`
+    /// away.
+    pub fn get_ray(&self, s: f64, t: f64, pixel_width: f64, pixel_height: f64) -> Ray {
         let random_disk = self.lens_radius * random_in_unit_disk();
         let offset = random_disk.x * self.u + random_disk.y * self.v;
-        let ray_direction =
-            self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset;
-        return Ray::new(self.origin + offset, ray_direction);
+        let origin = self.origin + offset;
+        let direction_at = |s: f64, t: f64| {
+            return self.lower_left_corner + s * self.horizontal + t * self.vertical - origin;
+        };
+        let time = scale(rand::random(), self.time_0, self.time_1);
+        let differentials = RayDifferentials {
+            x_origin: origin,
+            x_direction: direction_at(s + pixel_width, t),
+            y_origin: origin,
+            y_direction: direction_at(s, t + pixel_height),
+        };
+        return Ray::new_with_differentials(origin, direction_at(s, t), time, differentials);
     }
 }
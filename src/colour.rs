@@ -14,6 +14,16 @@ impl Colour {
         return Colour { red, green, blue };
     }
 
+    /// The colour with any negative parts raised to zero. Spectral rendering
+    /// gives these for wavelengths whose colour is outside sRGB.
+    pub fn clamp_non_negative(self) -> Colour {
+        return Colour {
+            red: f64::max(self.red, 0.0),
+            green: f64::max(self.green, 0.0),
+            blue: f64::max(self.blue, 0.0),
+        };
+    }
+
     pub fn gamma_correct(self, scale: f64) -> Colour {
         return Colour {
             red: f64::sqrt(self.red * scale),
@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    hittable::{HitRecord, Hittable, Interval},
+    ray::Ray,
+    v3::V3,
+};
+
+#[derive(Clone, Copy)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    /// The left hittable with the right one cut away
+    Difference,
+}
+
+impl CsgOperation {
+    fn is_inside(&self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+            CsgOperation::Difference => inside_left && !inside_right,
+        }
+    }
+}
+
+/// Constructive solid geometry: combines two closed hittables into one, by
+/// working out where along a ray it is inside each of them. Each surface
+/// keeps the material of the hittable it came from.
+pub struct Csg {
+    operation: CsgOperation,
+    left: Arc<dyn Hittable + Send + Sync>,
+    right: Arc<dyn Hittable + Send + Sync>,
+}
+
+impl Csg {
+    pub fn new(
+        operation: CsgOperation,
+        left: Arc<dyn Hittable + Send + Sync>,
+        right: Arc<dyn Hittable + Send + Sync>,
+    ) -> Self {
+        return Csg {
+            operation,
+            left,
+            right,
+        };
+    }
+
+    pub fn union(
+        left: Arc<dyn Hittable + Send + Sync>,
+        right: Arc<dyn Hittable + Send + Sync>,
+    ) -> Self {
+        return Csg::new(CsgOperation::Union, left, right);
+    }
+
+    pub fn intersection(
+        left: Arc<dyn Hittable + Send + Sync>,
+        right: Arc<dyn Hittable + Send + Sync>,
+    ) -> Self {
+        return Csg::new(CsgOperation::Intersection, left, right);
+    }
+
+    pub fn difference(
+        left: Arc<dyn Hittable + Send + Sync>,
+        right: Arc<dyn Hittable + Send + Sync>,
+    ) -> Self {
+        return Csg::new(CsgOperation::Difference, left, right);
+    }
+}
+
+/// A point where the ray crosses the surface of one of the operands.
+struct Crossing {
+    hit_record: HitRecord,
+    is_left: bool,
+    is_entry: bool,
+}
+
+fn crossings(intervals: Vec<Interval>, is_left: bool) -> Vec<Crossing> {
+    let mut crossings = vec![];
+    for interval in intervals {
+        crossings.push(Crossing {
+            hit_record: interval.entry,
+            is_left,
+            is_entry: true,
+        });
+        crossings.push(Crossing {
+            hit_record: interval.exit,
+            is_left,
+            is_entry: false,
+        });
+    }
+    return crossings;
+}
+
+impl Hittable for Csg {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        for interval in self.intervals(ray) {
+            for hit_record in [interval.entry, interval.exit] {
+                if hit_record.time > t_max {
+                    return None;
+                }
+                if hit_record.time >= t_min {
+                    return Some(hit_record);
+                }
+            }
+        }
+        return None;
+    }
+
+    fn bounding_box(&self, t_0: f64, t_1: f64) -> Option<AABB> {
+        let left = self.left.bounding_box(t_0, t_1)?;
+        match self.operation {
+            CsgOperation::Difference => return Some(left),
+            CsgOperation::Union => {
+                let right = self.right.bounding_box(t_0, t_1)?;
+                return Some(left + right);
+            }
+            CsgOperation::Intersection => {
+                let right = self.right.bounding_box(t_0, t_1)?;
+                let minimum = V3::new(
+                    f64::max(left.minimum.x, right.minimum.x),
+                    f64::max(left.minimum.y, right.minimum.y),
+                    f64::max(left.minimum.z, right.minimum.z),
+                );
+                let maximum = V3::new(
+                    f64::min(left.maximum.x, right.maximum.x),
+                    f64::min(left.maximum.y, right.maximum.y),
+                    f64::min(left.maximum.z, right.maximum.z),
+                );
+                return Some(AABB::new(minimum, maximum));
+            }
+        }
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let mut all_crossings = crossings(self.left.intervals(ray), true);
+        all_crossings.extend(crossings(self.right.intervals(ray), false));
+        all_crossings
+            .sort_by(|a, b| f64::partial_cmp(&a.hit_record.time, &b.hit_record.time).unwrap());
+
+        // walk along the ray, keeping the crossings where being inside the
+        // result changes
+        let mut intervals = vec![];
+        let mut inside_left = false;
+        let mut inside_right = false;
+        let mut entry: Option<HitRecord> = None;
+        for crossing in all_crossings {
+            let was_inside = self.operation.is_inside(inside_left, inside_right);
+            if crossing.is_left {
+                inside_left = crossing.is_entry;
+            } else {
+                inside_right = crossing.is_entry;
+            }
+            let is_inside = self.operation.is_inside(inside_left, inside_right);
+            if was_inside == is_inside {
+                continue;
+            }
+            // the normal already faces against the ray, so only which side
+            // of the combined surface the ray is on needs updating
+            let mut hit_record = crossing.hit_record;
+            hit_record.front_face = is_inside;
+            if is_inside {
+                entry = Some(hit_record);
+            } else if let Some(entry) = entry.take() {
+                intervals.push(Interval {
+                    entry,
+                    exit: hit_record,
+                });
+            }
+        }
+        return intervals;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        colour::Colour,
+        hittable::{Cuboid, Cylinder, Sphere},
+        material::{Lambertian, Material},
+    };
+
+    fn material() -> Arc<dyn Material + Send + Sync> {
+        return Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+    }
+
+    fn sphere(x: f64) -> Arc<dyn Hittable + Send + Sync> {
+        return Arc::new(Sphere::new(V3::new(x, 0.0, 0.0), 1.0, material()));
+    }
+
+    fn along_x() -> Ray {
+        return Ray::new(V3::new(-10.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.0);
+    }
+
+    fn spans(csg: &Csg, ray: &Ray) -> Vec<(f64, f64)> {
+        return csg
+            .intervals(ray)
+            .into_iter()
+            .map(|interval| (interval.entry.time, interval.exit.time))
+            .collect();
+    }
+
+    fn assert_spans(actual: Vec<(f64, f64)>, expected: &[(f64, f64)]) {
+        assert_eq!(actual.len(), expected.len(), "wrong number of spans");
+        for ((a_0, a_1), (e_0, e_1)) in actual.into_iter().zip(expected) {
+            assert!(f64::abs(a_0 - e_0) < 1e-6 && f64::abs(a_1 - e_1) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn union_of_overlapping_spheres_is_one_span() {
+        let union = Csg::union(sphere(0.0), sphere(1.5));
+        assert_spans(spans(&union, &along_x()), &[(9.0, 12.5)]);
+        let hit = union.hit(&along_x(), 0.001, f64::INFINITY).unwrap();
+        assert!(hit.front_face);
+        // inside both, the ray leaves through the far side of the second
+        let hit = union.hit(&along_x(), 10.0, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 12.5) < 1e-6);
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn intersection_of_overlapping_spheres_is_the_lens() {
+        let intersection = Csg::intersection(sphere(0.0), sphere(1.5));
+        assert_spans(spans(&intersection, &along_x()), &[(10.5, 11.0)]);
+        let apart = Csg::intersection(sphere(0.0), sphere(5.0));
+        assert!(apart.hit(&along_x(), 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn difference_cuts_the_second_out_of_the_first() {
+        let difference = Csg::difference(sphere(0.0), sphere(1.5));
+        assert_spans(spans(&difference, &along_x()), &[(9.0, 10.5)]);
+        let hit = difference.hit(&along_x(), 9.5, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 10.5) < 1e-6);
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn difference_drills_a_hole_along_an_axis_parallel_ray() {
+        let block: Arc<dyn Hittable + Send + Sync> = Arc::new(Cuboid::new(
+            V3::new(-2.0, -2.0, -2.0),
+            V3::new(2.0, 2.0, 2.0),
+            material(),
+        ));
+        let drill = Arc::new(Cylinder::new(V3::new(0.0, -3.0, 0.0), 1.0, 6.0, material()));
+        let drilled = Csg::difference(block, drill);
+        // straight down the middle of the hole
+        let down = Ray::new(V3::new(0.0, 10.0, 0.0), V3::new(0.0, -1.0, 0.0), 0.0);
+        assert!(drilled.hit(&down, 0.001, f64::INFINITY).is_none());
+        // across the hole, level with neither cap
+        let across = Ray::new(V3::new(-10.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.0);
+        assert_spans(spans(&drilled, &across), &[(8.0, 9.0), (11.0, 12.0)]);
+        // down the solid part beside the hole
+        let beside = Ray::new(V3::new(1.5, 10.0, 0.0), V3::new(0.0, -1.0, 0.0), 0.0);
+        assert_spans(spans(&drilled, &beside), &[(8.0, 12.0)]);
+    }
+}
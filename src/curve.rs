@@ -0,0 +1,370 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    colour::Colour,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    texture::{SolidColour, Texture},
+    v3::{orthonormal_basis, unit_vector, V3},
+};
+
+#[derive(Clone, Copy)]
+pub enum CurveShape {
+    /// A flat strip which always faces the ray, for thin curves like hair
+    /// where the shading across them doesn't matter
+    Ribbon,
+    /// A tube, shaded as if it were round
+    Cylinder,
+}
+
+/// A cubic Bézier curve with a width that varies linearly from `width_0` at
+/// the start to `width_1` at the end, for hair, fur and grass. `u` runs along
+/// the curve and `v` across it.
+pub struct Curve {
+    control_points: [V3; 4],
+    width_0: f64,
+    width_1: f64,
+    shape: CurveShape,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+/// The deepest that a curve is split before it is treated as a straight
+/// segment.
+const MAX_DEPTH: u32 = 10;
+
+impl Curve {
+    pub fn new(
+        control_points: [V3; 4],
+        width_0: f64,
+        width_1: f64,
+        shape: CurveShape,
+        material: Arc<dyn Material + Send + Sync>,
+    ) -> Self {
+        return Curve {
+            control_points,
+            width_0,
+            width_1,
+            shape,
+            material,
+        };
+    }
+
+    fn width(&self, u: f64) -> f64 {
+        return self.width_0 + u * (self.width_1 - self.width_0);
+    }
+
+    /// Split the curve (in ray space) into halves until each is close
+    /// enough to straight, then test the ray against the straight segments.
+    /// Returns the distance along the ray, u, and v of the nearest hit
+    /// between `z_min` and `z_max`.
+    fn recursive_hit(
+        &self,
+        points: [V3; 4],
+        u_0: f64,
+        u_1: f64,
+        depth: u32,
+        z_min: f64,
+        z_max: f64,
+    ) -> Option<(f64, f64, f64)> {
+        // the ray runs from the origin along z; skip this part of the curve
+        // if its bounds don't contain the ray
+        let half_width = 0.5 * f64::max(self.width(u_0), self.width(u_1));
+        let (minimum, maximum) = points_bounds(&points);
+        if minimum.x - half_width > 0.0
+            || maximum.x + half_width < 0.0
+            || minimum.y - half_width > 0.0
+            || maximum.y + half_width < 0.0
+            || minimum.z - half_width > z_max
+            || maximum.z + half_width < z_min
+        {
+            return None;
+        }
+
+        if depth > 0 {
+            let (first, second) = split_bezier(&points);
+            let u_mid = 0.5 * (u_0 + u_1);
+            let first_hit = self.recursive_hit(first, u_0, u_mid, depth - 1, z_min, z_max);
+            let z_max = first_hit.map_or(z_max, |(z, _, _)| z);
+            let second_hit = self.recursive_hit(second, u_mid, u_1, depth - 1, z_min, z_max);
+            return second_hit.or(first_hit);
+        }
+
+        // the nearest point to the ray on the straight segment
+        let (start, end) = (points[0], points[3]);
+        let segment = end - start;
+        let length_squared = segment.x * segment.x + segment.y * segment.y;
+        let w = if length_squared > 0.0 {
+            f64::clamp(
+                -(start.x * segment.x + start.y * segment.y) / length_squared,
+                0.0,
+                1.0,
+            )
+        } else {
+            0.0
+        };
+        let u = u_0 + w * (u_1 - u_0);
+        let half_width = 0.5 * self.width(u);
+        let nearest = evaluate_bezier(&points, w);
+        let distance_squared = nearest.x * nearest.x + nearest.y * nearest.y;
+        if distance_squared > half_width * half_width {
+            return None;
+        }
+        let distance = f64::sqrt(distance_squared);
+
+        let mut z = nearest.z;
+        if let CurveShape::Cylinder = self.shape {
+            // the front of the tube is nearer than its centre line
+            z -= f64::sqrt(half_width * half_width - distance_squared);
+        }
+        if z < z_min || z > z_max {
+            return None;
+        }
+        // which side of the curve the ray passes, to run v from edge to edge
+        let side = segment.x * nearest.y - segment.y * nearest.x;
+        let v = if side > 0.0 {
+            0.5 + 0.5 * distance / half_width
+        } else {
+            0.5 - 0.5 * distance / half_width
+        };
+        return Some((z, u, v));
+    }
+
+    /// The hit at `t` along the ray, which points along `forward`.
+    fn hit_record(&self, ray: &Ray, forward: V3, t: f64, u: f64, v: f64) -> HitRecord {
+        let point = ray.at(t);
+        let outward_normal = match self.shape {
+            CurveShape::Ribbon => -forward,
+            CurveShape::Cylinder => {
+                // away from the centre line, square on to the curve
+                let centre = evaluate_bezier(&self.control_points, u);
+                let tangent = unit_vector(bezier_derivative(&self.control_points, u));
+                let offset = point - centre;
+                let normal = offset - V3::dot(offset, tangent) * tangent;
+                if normal.length_squared() > 0.0 {
+                    unit_vector(normal)
+                } else {
+                    -forward
+                }
+            }
+        };
+        return HitRecord::new(
+            t,
+            u,
+            v,
+            point,
+            outward_normal,
+            ray.direction,
+            self.material.clone(),
+        );
+    }
+}
+
+impl Hittable for Curve {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // move the curve into a space where the ray starts at the origin and
+        // points along z, with distances measured in world units
+        let ray_length = ray.direction.length();
+        let forward = ray.direction / ray_length;
+        let (across, up) = orthonormal_basis(forward);
+        let to_ray_space = |p: V3| {
+            let relative = p - ray.origin;
+            return V3::new(
+                V3::dot(relative, across),
+                V3::dot(relative, up),
+                V3::dot(relative, forward),
+            );
+        };
+        let points = self.control_points.map(to_ray_space);
+
+        // split until the curve is flat to within a fraction of its width
+        let mut bend = 0.0;
+        for i in 0..2 {
+            let second_difference = points[i] - 2.0 * points[i + 1] + points[i + 2];
+            bend = f64::max(bend, f64::hypot(second_difference.x, second_difference.y));
+        }
+        let epsilon = f64::max(self.width_0, self.width_1) / 20.0;
+        let depth = if bend > 0.0 {
+            let r = f64::log2(std::f64::consts::SQRT_2 * 6.0 * bend / (8.0 * epsilon)) / 2.0;
+            f64::clamp(r.ceil(), 0.0, MAX_DEPTH as f64) as u32
+        } else {
+            0
+        };
+
+        // keep looking further along the ray past any hit that is cut out
+        let mut z_min = t_min * ray_length;
+        loop {
+            let (z, u, v) =
+                self.recursive_hit(points, 0.0, 1.0, depth, z_min, t_max * ray_length)?;
+            let hit_record = self.hit_record(ray, forward, z / ray_length, u, v);
+            if !hit_record.is_cut_out() {
+                return Some(hit_record);
+            }
+            z_min = z + 0.0001;
+        }
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        // a Bézier curve lies within the hull of its control points
+        let (minimum, maximum) = points_bounds(&self.control_points);
+        let half_width = 0.5 * f64::max(self.width_0, self.width_1);
+        let padding = V3::new(half_width, half_width, half_width);
+        return Some(AABB::new(minimum - padding, maximum + padding));
+    }
+}
+
+/// Blends from one texture at the root of a curve to another at its tip.
+pub struct RootToTip {
+    root: Arc<dyn Texture + Send + Sync>,
+    tip: Arc<dyn Texture + Send + Sync>,
+}
+
+impl RootToTip {
+    pub fn new<S: Texture + Send + Sync + 'static, T: Texture + Send + Sync + 'static>(
+        root: S,
+        tip: T,
+    ) -> Self {
+        return RootToTip {
+            root: Arc::new(root),
+            tip: Arc::new(tip),
+        };
+    }
+
+    pub fn new_from_colours(root: Colour, tip: Colour) -> Self {
+        return RootToTip::new(SolidColour::new(root), SolidColour::new(tip));
+    }
+}
+
+impl Texture for RootToTip {
+    fn colour(&self, u: f64, v: f64, p: V3) -> Colour {
+        return (1.0 - u) * self.root.colour(u, v, p) + u * self.tip.colour(u, v, p);
+    }
+}
+
+fn points_bounds(points: &[V3; 4]) -> (V3, V3) {
+    let mut minimum = points[0];
+    let mut maximum = points[0];
+    for p in &points[1..] {
+        minimum = V3::new(
+            f64::min(minimum.x, p.x),
+            f64::min(minimum.y, p.y),
+            f64::min(minimum.z, p.z),
+        );
+        maximum = V3::new(
+            f64::max(maximum.x, p.x),
+            f64::max(maximum.y, p.y),
+            f64::max(maximum.z, p.z),
+        );
+    }
+    return (minimum, maximum);
+}
+
+/// Split a cubic Bézier curve in half with de Casteljau's algorithm.
+fn split_bezier(p: &[V3; 4]) -> ([V3; 4], [V3; 4]) {
+    let mid = |a: V3, b: V3| 0.5 * (a + b);
+    let p01 = mid(p[0], p[1]);
+    let p12 = mid(p[1], p[2]);
+    let p23 = mid(p[2], p[3]);
+    let p012 = mid(p01, p12);
+    let p123 = mid(p12, p23);
+    let centre = mid(p012, p123);
+    return ([p[0], p01, p012, centre], [centre, p123, p23, p[3]]);
+}
+
+fn evaluate_bezier(p: &[V3; 4], u: f64) -> V3 {
+    let s = 1.0 - u;
+    return (s * s * s) * p[0]
+        + (3.0 * s * s * u) * p[1]
+        + (3.0 * s * u * u) * p[2]
+        + (u * u * u) * p[3];
+}
+
+fn bezier_derivative(p: &[V3; 4], u: f64) -> V3 {
+    let s = 1.0 - u;
+    return (3.0 * s * s) * (p[1] - p[0])
+        + (6.0 * s * u) * (p[2] - p[1])
+        + (3.0 * u * u) * (p[3] - p[2]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{AlphaMask, AlphaMode, Lambertian};
+
+    fn material() -> Arc<dyn Material + Send + Sync> {
+        return Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+    }
+
+    /// A curve that crosses the z axis twice: at z = 0.2225 going out and
+    /// z = 4.7775 coming back.
+    fn hairpin(material: Arc<dyn Material + Send + Sync>) -> Curve {
+        let control_points = [
+            V3::new(-1.0, 0.0, 0.0),
+            V3::new(2.0, 0.0, 0.0),
+            V3::new(2.0, 0.0, 5.0),
+            V3::new(-1.0, 0.0, 5.0),
+        ];
+        return Curve::new(control_points, 0.02, 0.02, CurveShape::Ribbon, material);
+    }
+
+    fn along_z(z: f64) -> Ray {
+        return Ray::new(V3::new(0.0, 0.0, z), V3::new(0.0, 0.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn nearest_crossing_is_hit() {
+        let hit = hairpin(material())
+            .hit(&along_z(-5.0), 0.001, f64::INFINITY)
+            .unwrap();
+        assert!(f64::abs(hit.time - 5.2225) < 0.01, "got t = {}", hit.time);
+        assert!(f64::abs(hit.u - 0.1273) < 0.01);
+        let off_axis = Ray::new(V3::new(0.0, 1.0, -5.0), V3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(hairpin(material())
+            .hit(&off_axis, 0.001, f64::INFINITY)
+            .is_none());
+    }
+
+    #[test]
+    fn crossing_before_t_min_does_not_hide_the_far_one() {
+        let hit = hairpin(material())
+            .hit(&along_z(0.222), 0.001, f64::INFINITY)
+            .unwrap();
+        assert!(f64::abs(hit.time - 4.5555) < 0.01, "got t = {}", hit.time);
+    }
+
+    #[test]
+    fn cut_out_crossing_is_passed_through() {
+        // transparent at the root, so only the crossing near the tip is left
+        let opacity =
+            RootToTip::new_from_colours(Colour::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+        let masked = Arc::new(AlphaMask::new(
+            material(),
+            opacity,
+            AlphaMode::Threshold(0.5),
+        ));
+        let hit = hairpin(masked)
+            .hit(&along_z(-5.0), 0.001, f64::INFINITY)
+            .unwrap();
+        assert!(f64::abs(hit.time - 9.7775) < 0.01, "got t = {}", hit.time);
+    }
+
+    #[test]
+    fn cylinder_curve_is_hit_in_front_of_its_centre_line() {
+        let straight = Curve::new(
+            [
+                V3::new(-1.0, 0.0, 0.0),
+                V3::new(-0.3, 0.0, 0.0),
+                V3::new(0.3, 0.0, 0.0),
+                V3::new(1.0, 0.0, 0.0),
+            ],
+            0.2,
+            0.2,
+            CurveShape::Cylinder,
+            material(),
+        );
+        let hit = straight.hit(&along_z(-5.0), 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 4.9) < 1e-6);
+        assert!((hit.normal - V3::new(0.0, 0.0, -1.0)).length() < 1e-6);
+    }
+}
@@ -0,0 +1,473 @@
+use std::{io, path::Path, sync::Arc};
+
+use crate::{
+    aabb::AABB,
+    colour::Colour,
+    hittable::{HitRecord, Hittable},
+    image::read_image,
+    material::Material,
+    noise::Perlin,
+    ray::Ray,
+    texture::{SolidColour, Texture},
+    triangle::hit_triangle,
+    v3::{unit_vector, V3},
+};
+
+/// Heights sampled on a regular 2D grid, from 0 to 1.
+pub struct HeightGrid {
+    nx: usize,
+    nz: usize,
+    /// indexed with x varying fastest
+    heights: Vec<f64>,
+}
+
+impl HeightGrid {
+    pub fn new(nx: usize, nz: usize, heights: Vec<f64>) -> Self {
+        assert!(nx >= 2 && nz >= 2, "height grid needs at least 2x2 samples");
+        assert_eq!(heights.len(), nx * nz, "height grid has wrong size");
+        return HeightGrid { nx, nz, heights };
+    }
+
+    /// Load heights from the brightness of a greyscale PNG or PGM image.
+    /// The bottom row of the image is at the lowest z.
+    pub fn load_image<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let image = read_image(path)?;
+        let nz = image.len();
+        let nx = image.first().map_or(0, |row| row.len());
+        if nx < 2 || nz < 2 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "height map must be at least 2x2",
+            ));
+        }
+        let heights = image
+            .iter()
+            .flatten()
+            .map(|colour| (colour.red + colour.green + colour.blue) / 3.0)
+            .collect();
+        return Ok(HeightGrid::new(nx, nz, heights));
+    }
+
+    /// Rolling hills from several octaves of noise.
+    pub fn from_noise(nx: usize, nz: usize, seed: u64, frequency: f64) -> Self {
+        let perlin = Perlin::new(seed);
+        let mut heights = Vec::with_capacity(nx * nz);
+        for j in 0..nz {
+            for i in 0..nx {
+                let p = V3::new(
+                    frequency * i as f64 / (nx - 1) as f64,
+                    0.5,
+                    frequency * j as f64 / (nz - 1) as f64,
+                );
+                heights.push(f64::clamp(perlin.turbulence(p, 6), 0.0, 1.0));
+            }
+        }
+        return HeightGrid::new(nx, nz, heights);
+    }
+
+    fn height(&self, i: usize, j: usize) -> f64 {
+        return self.heights[i + self.nx * j];
+    }
+
+    /// The surface normal at grid sample `i`, `j`, when the grid is
+    /// stretched to `size`.
+    fn vertex_normal(&self, i: usize, j: usize, size: V3) -> V3 {
+        // central differences, falling back to one sided ones at the edges
+        let (i0, i1) = (i.saturating_sub(1), usize::min(i + 1, self.nx - 1));
+        let (j0, j1) = (j.saturating_sub(1), usize::min(j + 1, self.nz - 1));
+        let cell_x = size.x / (self.nx - 1) as f64;
+        let cell_z = size.z / (self.nz - 1) as f64;
+        let dh_dx =
+            size.y * (self.height(i1, j) - self.height(i0, j)) / ((i1 - i0) as f64 * cell_x);
+        let dh_dz =
+            size.y * (self.height(i, j1) - self.height(i, j0)) / ((j1 - j0) as f64 * cell_z);
+        return unit_vector(V3::new(-dh_dx, 1.0, -dh_dz));
+    }
+
+    /// The surface normal at `u`, `v` across the grid when it is stretched
+    /// to `size`, interpolated from the nearest samples.
+    pub fn normal(&self, u: f64, v: f64, size: V3) -> V3 {
+        let x = f64::clamp(u, 0.0, 1.0) * (self.nx - 1) as f64;
+        let z = f64::clamp(v, 0.0, 1.0) * (self.nz - 1) as f64;
+        let i = usize::min(x.floor() as usize, self.nx - 2);
+        let j = usize::min(z.floor() as usize, self.nz - 2);
+        let (fx, fz) = (x - i as f64, z - j as f64);
+        let n00 = self.vertex_normal(i, j, size);
+        let n10 = self.vertex_normal(i + 1, j, size);
+        let n01 = self.vertex_normal(i, j + 1, size);
+        let n11 = self.vertex_normal(i + 1, j + 1, size);
+        return unit_vector(
+            (1.0 - fz) * ((1.0 - fx) * n00 + fx * n10) + fz * ((1.0 - fx) * n01 + fx * n11),
+        );
+    }
+}
+
+/// Terrain built from a grid of heights, stretched over the box from
+/// `minimum` to `minimum + size` with a height of one reaching the top. Each
+/// grid cell is split into two triangles, and rays step through the cells in
+/// order so that only the cells under the ray are tested.
+pub struct Heightfield {
+    grid: Arc<HeightGrid>,
+    minimum: V3,
+    size: V3,
+    /// per sample normals, indexed like the grid's heights
+    normals: Vec<V3>,
+    bounds: AABB,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+impl Heightfield {
+    pub fn new(
+        grid: Arc<HeightGrid>,
+        minimum: V3,
+        size: V3,
+        material: Arc<dyn Material + Send + Sync>,
+    ) -> Self {
+        let mut normals = Vec::with_capacity(grid.nx * grid.nz);
+        for j in 0..grid.nz {
+            for i in 0..grid.nx {
+                normals.push(grid.vertex_normal(i, j, size));
+            }
+        }
+        // only as tall as the heights actually reach, to cull more rays
+        let lowest = grid.heights.iter().cloned().fold(f64::INFINITY, f64::min);
+        let highest = grid.heights.iter().cloned().fold(0.0, f64::max);
+        let bounds = AABB::new(
+            V3::new(minimum.x, minimum.y + lowest * size.y - 0.0001, minimum.z),
+            V3::new(
+                minimum.x + size.x,
+                minimum.y + highest * size.y + 0.0001,
+                minimum.z + size.z,
+            ),
+        );
+        return Heightfield {
+            grid,
+            minimum,
+            size,
+            normals,
+            bounds,
+            material,
+        };
+    }
+
+    fn vertex(&self, i: usize, j: usize) -> V3 {
+        let u = i as f64 / (self.grid.nx - 1) as f64;
+        let v = j as f64 / (self.grid.nz - 1) as f64;
+        return self.minimum
+            + V3::new(
+                u * self.size.x,
+                self.grid.height(i, j) * self.size.y,
+                v * self.size.z,
+            );
+    }
+
+    fn hit_cell(&self, ray: &Ray, i: usize, j: usize, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+        let mut hits = vec![];
+        for triangle in [
+            [corners[0], corners[1], corners[2]],
+            [corners[0], corners[2], corners[3]],
+        ] {
+            let [a, b, c] = triangle.map(|(i, j)| self.vertex(i, j));
+            if let Some((t, b1, b2)) = hit_triangle(ray, a, b, c, t_min, t_max) {
+                hits.push((t, b1, b2, triangle));
+            }
+        }
+        // the nearest hit that isn't cut out
+        hits.sort_by(|a, b| f64::total_cmp(&a.0, &b.0));
+        return hits
+            .into_iter()
+            .map(|(t, b1, b2, triangle)| self.triangle_hit(ray, t, b1, b2, triangle))
+            .find(|hit_record| !hit_record.is_cut_out());
+    }
+
+    /// The hit `t` along the ray, at barycentric coordinates `b1` and `b2` on
+    /// the triangle between the given samples.
+    fn triangle_hit(
+        &self,
+        ray: &Ray,
+        t: f64,
+        b1: f64,
+        b2: f64,
+        triangle: [(usize, usize); 3],
+    ) -> HitRecord {
+        let b0 = 1.0 - b1 - b2;
+        let [a, b, c] = triangle.map(|(i, j)| self.vertex(i, j));
+        let mut face_normal = V3::cross(b - a, c - a);
+        if face_normal.y < 0.0 {
+            face_normal = -face_normal;
+        }
+        // shade with the sample normals blended across the triangle
+        let [n0, n1, n2] = triangle.map(|(i, j)| self.normals[i + self.grid.nx * j]);
+        let shading_normal = b0 * n0 + b1 * n1 + b2 * n2;
+        let point = ray.at(t);
+        let u = (point.x - self.minimum.x) / self.size.x;
+        let v = (point.z - self.minimum.z) / self.size.z;
+        // u and v follow x and z across the grid, ignoring the slope
+        let hit_record = HitRecord::new(
+            t,
+            u,
+            v,
+            point,
+            unit_vector(face_normal),
+            ray.direction,
+            self.material.clone(),
+        )
+        .with_uv_derivatives(
+            V3::new(self.size.x, 0.0, 0.0),
+            V3::new(0.0, 0.0, self.size.z),
+        )
+        .with_shading_normal(shading_normal);
+        return hit_record;
+    }
+}
+
+impl Hittable for Heightfield {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t_enter, t_exit) = self.bounds.hit_interval(ray, t_min, t_max)?;
+
+        // walk the cells under the ray in order (Amanatides & Woo), in grid
+        // coordinates where each cell is one unit square
+        let cells_x = (self.grid.nx - 1) as f64;
+        let cells_z = (self.grid.nz - 1) as f64;
+        let to_grid_x = cells_x / self.size.x;
+        let to_grid_z = cells_z / self.size.z;
+        let start = ray.at(t_enter);
+        let gx = (start.x - self.minimum.x) * to_grid_x;
+        let gz = (start.z - self.minimum.z) * to_grid_z;
+        let mut i = f64::clamp(gx.floor(), 0.0, cells_x - 1.0) as i64;
+        let mut j = f64::clamp(gz.floor(), 0.0, cells_z - 1.0) as i64;
+
+        let axis = |g: f64, cell: i64, d: f64| {
+            // t per cell, and t until the first cell boundary, along an axis
+            if d > 0.0 {
+                return (1, 1.0 / d, ((cell + 1) as f64 - g) / d);
+            } else if d < 0.0 {
+                return (-1, -1.0 / d, (cell as f64 - g) / d);
+            }
+            return (0, f64::INFINITY, f64::INFINITY);
+        };
+        let (step_i, delta_x, mut next_x) = axis(gx, i, ray.direction.x * to_grid_x);
+        let (step_j, delta_z, mut next_z) = axis(gz, j, ray.direction.z * to_grid_z);
+
+        let mut t_cell_start = t_enter;
+        loop {
+            let t_cell_end = f64::min(t_enter + f64::min(next_x, next_z), t_exit);
+            // allow a little slack so hits right on cell edges aren't lost
+            let slack = 1e-9 * (1.0 + t_cell_end.abs());
+            if let Some(hit_record) = self.hit_cell(
+                ray,
+                i as usize,
+                j as usize,
+                f64::max(t_cell_start - slack, t_min),
+                f64::min(t_cell_end + slack, t_max),
+            ) {
+                return Some(hit_record);
+            }
+            if t_cell_end >= t_exit {
+                return None;
+            }
+            if next_x < next_z {
+                i += step_i;
+                next_x += delta_x;
+            } else {
+                j += step_j;
+                next_z += delta_z;
+            }
+            if i < 0 || j < 0 || i >= cells_x as i64 || j >= cells_z as i64 {
+                return None;
+            }
+            t_cell_start = t_cell_end;
+        }
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        return Some(self.bounds);
+    }
+}
+
+/// Blends from one texture on flat ground to another on steep slopes, using
+/// the normals of a height grid stretched to `size`.
+pub struct SlopeTexture {
+    grid: Arc<HeightGrid>,
+    size: V3,
+    flat: Arc<dyn Texture + Send + Sync>,
+    steep: Arc<dyn Texture + Send + Sync>,
+    /// slopes, as the sine of the angle from horizontal, between which the
+    /// textures blend
+    slope_start: f64,
+    slope_end: f64,
+}
+
+impl SlopeTexture {
+    pub fn new<S: Texture + Send + Sync + 'static, T: Texture + Send + Sync + 'static>(
+        grid: Arc<HeightGrid>,
+        size: V3,
+        flat: S,
+        steep: T,
+        slope_start: f64,
+        slope_end: f64,
+    ) -> Self {
+        return SlopeTexture {
+            grid,
+            size,
+            flat: Arc::new(flat),
+            steep: Arc::new(steep),
+            slope_start,
+            slope_end,
+        };
+    }
+}
+
+impl Texture for SlopeTexture {
+    fn colour(&self, u: f64, v: f64, p: V3) -> Colour {
+        let normal = self.grid.normal(u, v, self.size);
+        let slope = f64::sqrt(1.0 - normal.y * normal.y);
+        let blend = smoothstep(self.slope_start, self.slope_end, slope);
+        return (1.0 - blend) * self.flat.colour(u, v, p) + blend * self.steep.colour(u, v, p);
+    }
+}
+
+/// Blends from one texture at `low` to another at `high`, by the height of
+/// the point being coloured.
+pub struct HeightTexture {
+    low: f64,
+    high: f64,
+    below: Arc<dyn Texture + Send + Sync>,
+    above: Arc<dyn Texture + Send + Sync>,
+}
+
+impl HeightTexture {
+    pub fn new<S: Texture + Send + Sync + 'static, T: Texture + Send + Sync + 'static>(
+        low: f64,
+        high: f64,
+        below: S,
+        above: T,
+    ) -> Self {
+        return HeightTexture {
+            low,
+            high,
+            below: Arc::new(below),
+            above: Arc::new(above),
+        };
+    }
+
+    pub fn new_from_colours(low: f64, high: f64, below: Colour, above: Colour) -> Self {
+        return HeightTexture::new(low, high, SolidColour::new(below), SolidColour::new(above));
+    }
+}
+
+impl Texture for HeightTexture {
+    fn colour(&self, u: f64, v: f64, p: V3) -> Colour {
+        let blend = smoothstep(self.low, self.high, p.y);
+        return (1.0 - blend) * self.below.colour(u, v, p) + blend * self.above.colour(u, v, p);
+    }
+}
+
+fn smoothstep(edge_0: f64, edge_1: f64, x: f64) -> f64 {
+    let t = f64::clamp((x - edge_0) / (edge_1 - edge_0), 0.0, 1.0);
+    return t * t * (3.0 - 2.0 * t);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::Lambertian;
+
+    fn material() -> Arc<dyn Material + Send + Sync> {
+        return Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+    }
+
+    /// A 3x3 grid, flat at `height` except for a peak of 1 in the middle.
+    fn peaked_grid(height: f64) -> HeightGrid {
+        let mut heights = vec![height; 9];
+        heights[4] = 1.0;
+        return HeightGrid::new(3, 3, heights);
+    }
+
+    fn field(grid: HeightGrid) -> Heightfield {
+        return Heightfield::new(
+            Arc::new(grid),
+            V3::new(0.0, 0.0, 0.0),
+            V3::new(2.0, 1.0, 2.0),
+            material(),
+        );
+    }
+
+    #[test]
+    fn ray_straight_down_hits_the_surface_height() {
+        let terrain = field(peaked_grid(0.0));
+        let down = |x: f64, z: f64| Ray::new(V3::new(x, 5.0, z), V3::new(0.0, -1.0, 0.0), 0.0);
+        let peak = terrain.hit(&down(1.0, 1.0), 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(peak.time - 4.0) < 1e-6);
+        let corner = terrain.hit(&down(0.1, 0.1), 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(corner.point.y - 0.1) < 1e-6);
+        assert!(f64::abs(corner.u - 0.05) < 1e-6 && f64::abs(corner.v - 0.05) < 1e-6);
+        assert!(corner.front_face);
+        assert!(terrain.hit(&down(3.0, 1.0), 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn grazing_ray_walks_cells_until_it_meets_the_peak() {
+        let terrain = field(peaked_grid(0.0));
+        // level with half the peak's height, crossing the first cell clear of
+        // the ground
+        let ray = Ray::new(V3::new(-1.0, 0.5, 1.0), V3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = terrain.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.point.x - 0.5) < 1e-6);
+        assert!(hit.normal.x < 0.0 && hit.normal.y > 0.0);
+        // and above it misses entirely
+        let above = Ray::new(V3::new(-1.0, 1.5, 1.0), V3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(terrain.hit(&above, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn flat_grid_has_upright_normals() {
+        let grid = HeightGrid::new(2, 2, vec![0.5; 4]);
+        let normal = grid.normal(0.3, 0.7, V3::new(1.0, 1.0, 1.0));
+        assert!((normal - V3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn height_grid_loaded_from_image_brightness() {
+        let path = std::env::temp_dir().join("rt_heightmap.pgm");
+        std::fs::write(&path, "P2 2 2 255\n0 255\n255 0\n").unwrap();
+        let grid = HeightGrid::load_image(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // the bottom row of the image is at the lowest z
+        assert_eq!(grid.height(0, 0), 1.0);
+        assert_eq!(grid.height(1, 0), 0.0);
+        assert_eq!(grid.height(0, 1), 0.0);
+    }
+
+    #[test]
+    fn height_and_slope_textures_blend() {
+        let texture = HeightTexture::new_from_colours(
+            1.0,
+            2.0,
+            Colour::new(0.0, 0.0, 0.0),
+            Colour::new(1.0, 1.0, 1.0),
+        );
+        assert_eq!(texture.colour(0.0, 0.0, V3::new(0.0, 0.5, 0.0)).red, 0.0);
+        assert_eq!(texture.colour(0.0, 0.0, V3::new(0.0, 2.5, 0.0)).red, 1.0);
+        let slope = SlopeTexture::new(
+            Arc::new(peaked_grid(0.0)),
+            V3::new(2.0, 1.0, 2.0),
+            SolidColour::new(Colour::new(0.0, 0.0, 0.0)),
+            SolidColour::new(Colour::new(1.0, 1.0, 1.0)),
+            0.1,
+            0.2,
+        );
+        // steep beside the peak, flat out at the corner
+        assert!(slope.colour(0.25, 0.5, V3::new(0.0, 0.0, 0.0)).red > 0.9);
+        let flat = SlopeTexture::new(
+            Arc::new(HeightGrid::new(2, 2, vec![0.0; 4])),
+            V3::new(1.0, 1.0, 1.0),
+            SolidColour::new(Colour::new(0.0, 0.0, 0.0)),
+            SolidColour::new(Colour::new(1.0, 1.0, 1.0)),
+            0.1,
+            0.2,
+        );
+        assert_eq!(flat.colour(0.5, 0.5, V3::new(0.0, 0.0, 0.0)).red, 0.0);
+    }
+}
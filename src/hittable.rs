@@ -1,16 +1,32 @@
 use std::sync::Arc;
 
-use crate::{aabb::AABB, material::Material, ray::Ray, v3::V3};
+use crate::{
+    aabb::AABB,
+    material::Material,
+    ray::Ray,
+    texture::Footprint,
+    utils::solve_polynomial,
+    v3::{orthonormal_basis, unit_vector, V3},
+};
 
 #[derive(Clone)]
 pub struct HitRecord {
     pub point: V3,
+    /// The true normal of the surface, facing against the ray
     pub normal: V3,
+    /// The normal to shade with, such as one interpolated across a mesh or
+    /// perturbed by a bump map. It is on the same side as `normal`, which
+    /// should still be used to tell which side of the surface a direction
+    /// is on.
+    pub shading_normal: V3,
     pub time: f64,
     pub u: f64,
     pub v: f64,
     pub front_face: bool,
     pub material: Arc<dyn Material + Sync + Send>,
+    /// How the point moves with u and v, or zero if the shape doesn't say
+    pub dpdu: V3,
+    pub dpdv: V3,
 }
 
 impl HitRecord {
@@ -35,15 +51,148 @@ impl HitRecord {
             v,
             point,
             normal,
+            shading_normal: normal,
             front_face,
             material,
+            dpdu: V3::new(0.0, 0.0, 0.0),
+            dpdv: V3::new(0.0, 0.0, 0.0),
         };
     }
+
+    pub fn with_uv_derivatives(mut self, dpdu: V3, dpdv: V3) -> Self {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+        return self;
+    }
+
+    /// Shade with a different normal from the surface's true one, turned to
+    /// the same side of the surface as the true normal.
+    pub fn with_shading_normal(mut self, outward_shading_normal: V3) -> Self {
+        self.set_shading_normal(outward_shading_normal);
+        return self;
+    }
+
+    pub fn set_shading_normal(&mut self, outward_shading_normal: V3) {
+        let shading_normal = unit_vector(outward_shading_normal);
+        self.shading_normal = if V3::dot(shading_normal, self.normal) < 0.0 {
+            -shading_normal
+        } else {
+            shading_normal
+        };
+    }
+
+    /// Whether the material's alpha lets the ray pass through the surface
+    /// here, decided at random where it is only partly opaque.
+    pub fn is_cut_out(&self) -> bool {
+        let alpha = self.material.alpha(self.u, self.v, self.point);
+        if alpha >= 1.0 {
+            return false;
+        }
+        if alpha <= 0.0 {
+            return true;
+        }
+        return rand::random::<f64>() >= alpha;
+    }
+
+    /// The shading normal, facing out of the surface rather than against
+    /// the ray.
+    pub fn outward_shading_normal(&self) -> V3 {
+        if self.front_face {
+            return self.shading_normal;
+        } else {
+            return -self.shading_normal;
+        }
+    }
+
+    /// How much of the surface around the hit the pixel that `ray` was
+    /// cast through covers, if the ray has differentials. The neighbouring
+    /// rays are intersected with the plane tangent to the surface.
+    pub fn footprint(&self, ray: &Ray) -> Option<Footprint> {
+        let differentials = ray.differentials?;
+        let n = self.normal;
+        let offset_point = |origin: V3, direction: V3| {
+            let denominator = V3::dot(n, direction);
+            if f64::abs(denominator) < 1e-12 {
+                return None;
+            }
+            let t = V3::dot(self.point - origin, n) / denominator;
+            return Some(origin + t * direction);
+        };
+        let dpdx = offset_point(differentials.x_origin, differentials.x_direction)? - self.point;
+        let dpdy = offset_point(differentials.y_origin, differentials.y_direction)? - self.point;
+
+        // solve dp = dpdu * du + dpdv * dv in the two axes the surface is
+        // least edge on to
+        let (a, b) = if f64::abs(n.x) > f64::abs(n.y) && f64::abs(n.x) > f64::abs(n.z) {
+            (1, 2)
+        } else if f64::abs(n.y) > f64::abs(n.z) {
+            (0, 2)
+        } else {
+            (0, 1)
+        };
+        let (a_u, a_v) = (self.dpdu.get_by_index(a), self.dpdv.get_by_index(a));
+        let (b_u, b_v) = (self.dpdu.get_by_index(b), self.dpdv.get_by_index(b));
+        let determinant = a_u * b_v - a_v * b_u;
+        let solve = |dp: V3| {
+            if f64::abs(determinant) < 1e-12 {
+                return (0.0, 0.0);
+            }
+            let (dp_a, dp_b) = (dp.get_by_index(a), dp.get_by_index(b));
+            return (
+                (b_v * dp_a - a_v * dp_b) / determinant,
+                (a_u * dp_b - b_u * dp_a) / determinant,
+            );
+        };
+        let (dudx, dvdx) = solve(dpdx);
+        let (dudy, dvdy) = solve(dpdy);
+        return Some(Footprint {
+            dpdx,
+            dpdy,
+            dudx,
+            dvdx,
+            dudy,
+            dvdy,
+        });
+    }
+}
+
+/// A span along a ray that is inside a closed hittable, from where the ray
+/// enters the surface to where it leaves.
+#[derive(Clone)]
+pub struct Interval {
+    pub entry: HitRecord,
+    pub exit: HitRecord,
 }
 
 pub trait Hittable {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
     fn bounding_box(&self, t_0: f64, t_1: f64) -> Option<AABB>;
+
+    /// Every span of the whole (infinite) line of the ray which is inside the
+    /// hittable, in order. This only makes sense for closed hittables. By
+    /// default it is found by stepping from one hit to the next.
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        let mut intervals = vec![];
+        let mut entry: Option<HitRecord> = None;
+        let mut t_min = f64::NEG_INFINITY;
+        // guard against surfaces which keep reporting the same hit
+        for _ in 0..64 {
+            let hit_record = match self.hit(ray, t_min, f64::INFINITY) {
+                Some(hit_record) if hit_record.time.is_finite() => hit_record,
+                _ => break,
+            };
+            t_min = hit_record.time + 0.0001;
+            if hit_record.front_face {
+                entry = Some(hit_record);
+            } else if let Some(entry) = entry.take() {
+                intervals.push(Interval {
+                    entry,
+                    exit: hit_record,
+                });
+            }
+        }
+        return intervals;
+    }
 }
 
 pub struct HittableList {
@@ -73,7 +222,7 @@ impl Hittable for HittableList {
     }
 
     fn bounding_box(&self, t_0: f64, t_1: f64) -> Option<AABB> {
-        if self.hittables.len() == 0 {
+        if self.hittables.is_empty() {
             return None;
         }
         // let mut output_box = AABB::new(V3::new(0.0, 0.0, 0.0), V3::new(0.0, 0.0, 0.0));
@@ -103,50 +252,65 @@ impl Sphere {
             material,
         };
     }
+}
 
-    fn get_sphere_uv(&self, p: V3) -> (f64, f64) {
-        // p: a given point on the sphere of radius one, centered at the origin.
-        // u: returned value [0,1] of angle around the Y axis from X=-1.
-        // v: returned value [0,1] of angle from Y=-1 to Y=+1.
-        //     <1 0 0> yields <0.50 0.50>       <-1  0  0> yields <0.00 0.50>
-        //     <0 1 0> yields <0.50 1.00>       < 0 -1  0> yields <0.50 0.00>
-        //     <0 0 1> yields <0.25 0.50>       < 0  0 -1> yields <0.75 0.50>
+fn get_sphere_uv(p: V3) -> (f64, f64) {
+    // p: a given point on the sphere of radius one, centered at the origin.
+    // u: returned value [0,1] of angle around the Y axis from X=-1.
+    // v: returned value [0,1] of angle from Y=-1 to Y=+1.
+    //     <1 0 0> yields <0.50 0.50>       <-1  0  0> yields <0.00 0.50>
+    //     <0 1 0> yields <0.50 1.00>       < 0 -1  0> yields <0.50 0.00>
+    //     <0 0 1> yields <0.25 0.50>       < 0  0 -1> yields <0.75 0.50>
 
-        let theta = f64::acos(-p.y);
-        let phi = f64::atan2(-p.z, p.x) + std::f64::consts::PI;
+    let theta = f64::acos(-p.y);
+    let phi = f64::atan2(-p.z, p.x) + std::f64::consts::PI;
 
-        let u = phi / (2.0 * std::f64::consts::PI);
-        let v = theta / std::f64::consts::PI;
-        return (u, v);
-    }
+    let u = phi / (2.0 * std::f64::consts::PI);
+    let v = theta / std::f64::consts::PI;
+    return (u, v);
 }
 
-impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let oc = ray.origin - self.centre;
-        let a = ray.direction.length_squared();
-        let half_b = V3::dot(oc, ray.direction);
-        let c = oc.length_squared() - self.radius * self.radius;
+/// How a point on a sphere of the given radius moves with the uvs from
+/// `get_sphere_uv`, at the outward normal `n`.
+fn get_sphere_uv_derivatives(n: V3, radius: f64) -> (V3, V3) {
+    let pi = std::f64::consts::PI;
+    let dpdu = 2.0 * pi * radius * V3::new(n.z, 0.0, -n.x);
+    // at the poles every direction is down the sphere; pick any
+    let sin_theta = f64::max(f64::sqrt(f64::max(1.0 - n.y * n.y, 0.0)), 1e-6);
+    let dpdv = pi * radius * V3::new(-n.y * n.x / sin_theta, sin_theta, -n.y * n.z / sin_theta);
+    return (dpdu, dpdv);
+}
 
-        let discriminant = half_b * half_b - a * c;
-        if discriminant < 0.0 {
-            return None;
-        }
+fn hit_sphere(
+    centre: V3,
+    radius: f64,
+    material: &Arc<dyn Material + Sync + Send>,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<HitRecord> {
+    let oc = ray.origin - centre;
+    let a = ray.direction.length_squared();
+    let half_b = V3::dot(oc, ray.direction);
+    let c = oc.length_squared() - radius * radius;
+
+    let discriminant = half_b * half_b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
 
-        let sqrtd = f64::sqrt(discriminant);
+    let sqrtd = f64::sqrt(discriminant);
 
-        // Find the nearest root that lies in the acceptable range.
-        let mut t = (-half_b - sqrtd) / a;
+    // Find the nearest root that lies in the acceptable range and isn't
+    // cut out of the surface.
+    for t in [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a] {
         if t < t_min || t_max < t {
-            t = (-half_b + sqrtd) / a;
-            if t < t_min || t_max < t {
-                return None;
-            }
+            continue;
         }
-
         let point = ray.at(t);
-        let outward_normal = (point - self.centre) / self.radius;
-        let (u, v) = self.get_sphere_uv(point);
+        let outward_normal = (point - centre) / radius;
+        let (u, v) = get_sphere_uv(outward_normal);
+        let (dpdu, dpdv) = get_sphere_uv_derivatives(outward_normal, radius);
         let hit_record = HitRecord::new(
             t,
             u,
@@ -154,12 +318,22 @@ impl Hittable for Sphere {
             point,
             outward_normal,
             ray.direction,
-            self.material.clone(),
-        );
-        return Some(hit_record);
+            material.clone(),
+        )
+        .with_uv_derivatives(dpdu, dpdv);
+        if !hit_record.is_cut_out() {
+            return Some(hit_record);
+        }
     }
+    return None;
+}
 
-    fn bounding_box(&self, t_0: f64, t_1: f64) -> Option<AABB> {
+impl Hittable for Sphere {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        return hit_sphere(self.centre, self.radius, &self.material, ray, t_min, t_max);
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
         let minimum = self.centre - V3::new(self.radius, self.radius, self.radius);
         let maximum = self.centre + V3::new(self.radius, self.radius, self.radius);
         let aabb = AABB::new(minimum, maximum);
@@ -195,10 +369,6 @@ impl XYRect {
             material,
         };
     }
-
-    fn get_rect_uv(&self, p: V3) -> (f64, f64) {
-        todo!()
-    }
 }
 
 impl Hittable for XYRect {
@@ -226,7 +396,14 @@ impl Hittable for XYRect {
             outward_normal,
             ray.direction,
             self.material.clone(),
+        )
+        .with_uv_derivatives(
+            V3::new(self.x1 - self.x0, 0.0, 0.0),
+            V3::new(0.0, self.y1 - self.y0, 0.0),
         );
+        if hit_record.is_cut_out() {
+            return None;
+        }
         return Some(hit_record);
     }
 
@@ -238,3 +415,1080 @@ impl Hittable for XYRect {
         return Some(b);
     }
 }
+
+pub struct XZRect {
+    x0: f64,
+    x1: f64,
+    z0: f64,
+    z1: f64,
+    /// The y-value for the rect
+    k: f64,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+impl XZRect {
+    pub fn new(
+        x0: f64,
+        x1: f64,
+        z0: f64,
+        z1: f64,
+        k: f64,
+        material: Arc<dyn Material + Send + Sync>,
+    ) -> Self {
+        return XZRect {
+            x0,
+            x1,
+            z0,
+            z1,
+            k,
+            material,
+        };
+    }
+}
+
+impl Hittable for XZRect {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = (self.k - ray.origin.y) / ray.direction.y;
+        // does the ray hit the y-plane within the range
+        if t < t_min || t > t_max {
+            return None;
+        }
+        let x = ray.origin.x + t * ray.direction.x;
+        let z = ray.origin.z + t * ray.direction.z;
+        // are the x and z coords within the rectangle
+        if x < self.x0 || x > self.x1 || z < self.z0 || z > self.z1 {
+            return None;
+        }
+        let u = (x - self.x0) / (self.x1 - self.x0);
+        let v = (z - self.z0) / (self.z1 - self.z0);
+        let outward_normal = V3::new(0.0, 1.0, 0.0);
+        let point = ray.at(t);
+        let hit_record = HitRecord::new(
+            t,
+            u,
+            v,
+            point,
+            outward_normal,
+            ray.direction,
+            self.material.clone(),
+        )
+        .with_uv_derivatives(
+            V3::new(self.x1 - self.x0, 0.0, 0.0),
+            V3::new(0.0, 0.0, self.z1 - self.z0),
+        );
+        if hit_record.is_cut_out() {
+            return None;
+        }
+        return Some(hit_record);
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        let b = AABB::new(
+            V3::new(self.x0, self.k - 0.0001, self.z0),
+            V3::new(self.x1, self.k + 0.0001, self.z1),
+        );
+        return Some(b);
+    }
+}
+
+pub struct YZRect {
+    y0: f64,
+    y1: f64,
+    z0: f64,
+    z1: f64,
+    /// The x-value for the rect
+    k: f64,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+impl YZRect {
+    pub fn new(
+        y0: f64,
+        y1: f64,
+        z0: f64,
+        z1: f64,
+        k: f64,
+        material: Arc<dyn Material + Send + Sync>,
+    ) -> Self {
+        return YZRect {
+            y0,
+            y1,
+            z0,
+            z1,
+            k,
+            material,
+        };
+    }
+}
+
+impl Hittable for YZRect {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = (self.k - ray.origin.x) / ray.direction.x;
+        // does the ray hit the x-plane within the range
+        if t < t_min || t > t_max {
+            return None;
+        }
+        let y = ray.origin.y + t * ray.direction.y;
+        let z = ray.origin.z + t * ray.direction.z;
+        // are the y and z coords within the rectangle
+        if y < self.y0 || y > self.y1 || z < self.z0 || z > self.z1 {
+            return None;
+        }
+        let u = (y - self.y0) / (self.y1 - self.y0);
+        let v = (z - self.z0) / (self.z1 - self.z0);
+        let outward_normal = V3::new(1.0, 0.0, 0.0);
+        let point = ray.at(t);
+        let hit_record = HitRecord::new(
+            t,
+            u,
+            v,
+            point,
+            outward_normal,
+            ray.direction,
+            self.material.clone(),
+        )
+        .with_uv_derivatives(
+            V3::new(0.0, self.y1 - self.y0, 0.0),
+            V3::new(0.0, 0.0, self.z1 - self.z0),
+        );
+        if hit_record.is_cut_out() {
+            return None;
+        }
+        return Some(hit_record);
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        let b = AABB::new(
+            V3::new(self.k - 0.0001, self.y0, self.z0),
+            V3::new(self.k + 0.0001, self.y1, self.z1),
+        );
+        return Some(b);
+    }
+}
+
+/// An axis aligned box, from the corner `minimum` to the corner `maximum`.
+pub struct Cuboid {
+    minimum: V3,
+    maximum: V3,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+impl Cuboid {
+    pub fn new(minimum: V3, maximum: V3, material: Arc<dyn Material + Send + Sync>) -> Self {
+        return Cuboid {
+            minimum,
+            maximum,
+            material,
+        };
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // slab test, remembering which axis the ray enters and exits through
+        let mut t_enter = f64::NEG_INFINITY;
+        let mut t_exit = f64::INFINITY;
+        let mut enter_axis = 0;
+        let mut exit_axis = 0;
+        for a in 0..3 {
+            let inv_d = 1.0 / ray.direction.get_by_index(a);
+            let mut t0 = (self.minimum.get_by_index(a) - ray.origin.get_by_index(a)) * inv_d;
+            let mut t1 = (self.maximum.get_by_index(a) - ray.origin.get_by_index(a)) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            if t0 > t_enter {
+                t_enter = t0;
+                enter_axis = a;
+            }
+            if t1 < t_exit {
+                t_exit = t1;
+                exit_axis = a;
+            }
+        }
+        if t_exit <= t_enter {
+            return None;
+        }
+        // the ray leaves through the far side if the near side is out of range
+        // or cut out
+        for (t, axis) in [(t_enter, enter_axis), (t_exit, exit_axis)] {
+            if t < t_min || t > t_max {
+                continue;
+            }
+            let hit_record = self.face_hit(ray, t, axis);
+            if !hit_record.is_cut_out() {
+                return Some(hit_record);
+            }
+        }
+        return None;
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        return Some(AABB::new(self.minimum, self.maximum));
+    }
+}
+
+impl Cuboid {
+    /// The hit `t` along the ray, on the face square to `axis`.
+    fn face_hit(&self, ray: &Ray, t: f64, axis: usize) -> HitRecord {
+        let point = ray.at(t);
+        let size = self.maximum - self.minimum;
+        let centre = self.minimum + 0.5 * size;
+        let mut outward_normal = V3::new(0.0, 0.0, 0.0);
+        let side = if point.get_by_index(axis) > centre.get_by_index(axis) {
+            1.0
+        } else {
+            -1.0
+        };
+        outward_normal.set_by_index(axis, side);
+        // uv across the face that was hit
+        let (u_axis, v_axis) = ((axis + 1) % 3, (axis + 2) % 3);
+        let u = (point.get_by_index(u_axis) - self.minimum.get_by_index(u_axis))
+            / size.get_by_index(u_axis);
+        let v = (point.get_by_index(v_axis) - self.minimum.get_by_index(v_axis))
+            / size.get_by_index(v_axis);
+        let mut dpdu = V3::new(0.0, 0.0, 0.0);
+        dpdu.set_by_index(u_axis, size.get_by_index(u_axis));
+        let mut dpdv = V3::new(0.0, 0.0, 0.0);
+        dpdv.set_by_index(v_axis, size.get_by_index(v_axis));
+        let hit_record = HitRecord::new(
+            t,
+            u,
+            v,
+            point,
+            outward_normal,
+            ray.direction,
+            self.material.clone(),
+        )
+        .with_uv_derivatives(dpdu, dpdv);
+        return hit_record;
+    }
+}
+
+/// A sphere whose centre moves linearly from `centre_0` at `time_0` to
+/// `centre_1` at `time_1`.
+pub struct MovingSphere {
+    centre_0: V3,
+    centre_1: V3,
+    time_0: f64,
+    time_1: f64,
+    radius: f64,
+    material: Arc<dyn Material + Sync + Send>,
+}
+
+impl MovingSphere {
+    pub fn new(
+        centre_0: V3,
+        centre_1: V3,
+        time_0: f64,
+        time_1: f64,
+        radius: f64,
+        material: Arc<dyn Material + Sync + Send>,
+    ) -> Self {
+        return MovingSphere {
+            centre_0,
+            centre_1,
+            time_0,
+            time_1,
+            radius,
+            material,
+        };
+    }
+
+    fn centre(&self, time: f64) -> V3 {
+        // a sphere given no time to move stays at the start
+        if self.time_1 == self.time_0 {
+            return self.centre_0;
+        }
+        let proportion = (time - self.time_0) / (self.time_1 - self.time_0);
+        return self.centre_0 + proportion * (self.centre_1 - self.centre_0);
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let centre = self.centre(ray.time);
+        return hit_sphere(centre, self.radius, &self.material, ray, t_min, t_max);
+    }
+
+    fn bounding_box(&self, t_0: f64, t_1: f64) -> Option<AABB> {
+        let radius = V3::new(self.radius, self.radius, self.radius);
+        let centre_0 = self.centre(t_0);
+        let centre_1 = self.centre(t_1);
+        let box_0 = AABB::new(centre_0 - radius, centre_0 + radius);
+        let box_1 = AABB::new(centre_1 - radius, centre_1 + radius);
+        return Some(box_0 + box_1);
+    }
+}
+
+/// An infinite plane through `point`. It has no bounding box, so the BVH
+/// tests it against every ray.
+pub struct Plane {
+    point: V3,
+    normal: V3,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+impl Plane {
+    pub fn new(point: V3, normal: V3, material: Arc<dyn Material + Send + Sync>) -> Self {
+        return Plane {
+            point,
+            normal: unit_vector(normal),
+            material,
+        };
+    }
+}
+
+impl Hittable for Plane {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = hit_plane(self.point, self.normal, ray, t_min, t_max)?;
+        let point = ray.at(t);
+        // uv is the distance along two directions lying in the plane
+        let (s, r) = orthonormal_basis(self.normal);
+        let u = V3::dot(point - self.point, s);
+        let v = V3::dot(point - self.point, r);
+        let hit_record = HitRecord::new(
+            t,
+            u,
+            v,
+            point,
+            self.normal,
+            ray.direction,
+            self.material.clone(),
+        )
+        .with_uv_derivatives(s, r);
+        if hit_record.is_cut_out() {
+            return None;
+        }
+        return Some(hit_record);
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        return None;
+    }
+}
+
+/// The t at which the ray crosses the plane through `point` with the given
+/// normal, if it is within range.
+fn hit_plane(point: V3, normal: V3, ray: &Ray, t_min: f64, t_max: f64) -> Option<f64> {
+    let denominator = V3::dot(normal, ray.direction);
+    if f64::abs(denominator) < 1e-12 {
+        return None;
+    }
+    let t = V3::dot(point - ray.origin, normal) / denominator;
+    if t < t_min || t > t_max {
+        return None;
+    }
+    return Some(t);
+}
+
+/// The angle of `p` around the y axis, from 0 to 1.
+fn get_angle_around_y(p: V3) -> f64 {
+    let phi = f64::atan2(-p.z, p.x) + std::f64::consts::PI;
+    return phi / (2.0 * std::f64::consts::PI);
+}
+
+/// How `p` moves as its angle from `get_angle_around_y` goes from 0 to 1.
+fn get_angle_around_y_derivative(p: V3) -> V3 {
+    return 2.0 * std::f64::consts::PI * V3::new(p.z, 0.0, -p.x);
+}
+
+/// The horizontal unit vector from the y axis out through `p`, or along x
+/// for points on the axis.
+fn get_outward_from_y(p: V3) -> V3 {
+    let horizontal = V3::new(p.x, 0.0, p.z);
+    let length = horizontal.length();
+    if length == 0.0 {
+        return V3::new(1.0, 0.0, 0.0);
+    }
+    return horizontal / length;
+}
+
+pub struct Disk {
+    centre: V3,
+    normal: V3,
+    radius: f64,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+impl Disk {
+    pub fn new(
+        centre: V3,
+        normal: V3,
+        radius: f64,
+        material: Arc<dyn Material + Send + Sync>,
+    ) -> Self {
+        return Disk {
+            centre,
+            normal: unit_vector(normal),
+            radius,
+            material,
+        };
+    }
+}
+
+impl Hittable for Disk {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let t = hit_plane(self.centre, self.normal, ray, t_min, t_max)?;
+        let point = ray.at(t);
+        let from_centre = point - self.centre;
+        let distance = from_centre.length();
+        if distance > self.radius {
+            return None;
+        }
+        // u is the angle around the centre, v the distance out from it
+        let (s, r) = orthonormal_basis(self.normal);
+        let (x, y) = (V3::dot(from_centre, s), V3::dot(from_centre, r));
+        let angle = f64::atan2(y, x);
+        let u = (angle + std::f64::consts::PI) / (2.0 * std::f64::consts::PI);
+        let v = distance / self.radius;
+        let dpdu = 2.0 * std::f64::consts::PI * (x * r - y * s);
+        let outward = if distance > 0.0 {
+            from_centre / distance
+        } else {
+            s
+        };
+        let hit_record = HitRecord::new(
+            t,
+            u,
+            v,
+            point,
+            self.normal,
+            ray.direction,
+            self.material.clone(),
+        )
+        .with_uv_derivatives(dpdu, self.radius * outward);
+        if hit_record.is_cut_out() {
+            return None;
+        }
+        return Some(hit_record);
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        // how far the rim reaches along each axis
+        let extent = |n: f64| self.radius * f64::sqrt(f64::max(1.0 - n * n, 0.0)) + 0.0001;
+        let half_size = V3::new(
+            extent(self.normal.x),
+            extent(self.normal.y),
+            extent(self.normal.z),
+        );
+        return Some(AABB::new(self.centre - half_size, self.centre + half_size));
+    }
+}
+
+/// The nearest of a set of candidate hits that isn't cut out, each given as
+/// a t and the outward normal, uv and uv derivatives at that point.
+fn nearest_candidate(
+    mut candidates: Vec<(f64, V3, f64, f64, V3, V3)>,
+    ray: &Ray,
+    material: &Arc<dyn Material + Send + Sync>,
+) -> Option<HitRecord> {
+    candidates.sort_by(|a, b| f64::partial_cmp(&a.0, &b.0).unwrap());
+    return candidates
+        .into_iter()
+        .find_map(|(t, outward_normal, u, v, dpdu, dpdv)| {
+            let hit_record = HitRecord::new(
+                t,
+                u,
+                v,
+                ray.at(t),
+                outward_normal,
+                ray.direction,
+                material.clone(),
+            )
+            .with_uv_derivatives(dpdu, dpdv);
+            if hit_record.is_cut_out() {
+                return None;
+            }
+            return Some(hit_record);
+        });
+}
+
+/// The t at which the ray hits a horizontal cap of the given radius centred
+/// at `centre`, with the uv across the cap and its derivatives.
+fn hit_cap(
+    centre: V3,
+    radius: f64,
+    ray: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64, V3, V3)> {
+    // a ray parallel to the cap never crosses it, or lies in it everywhere
+    if f64::abs(ray.direction.y) < 1e-12 {
+        return None;
+    }
+    let t = (centre.y - ray.origin.y) / ray.direction.y;
+    if !t.is_finite() || !(t_min..=t_max).contains(&t) {
+        return None;
+    }
+    let from_centre = ray.at(t) - centre;
+    let distance = from_centre.length();
+    if distance > radius {
+        return None;
+    }
+    return Some((
+        t,
+        get_angle_around_y(from_centre),
+        distance / radius,
+        get_angle_around_y_derivative(from_centre),
+        radius * get_outward_from_y(from_centre),
+    ));
+}
+
+/// A cylinder standing upright on the disk at `base`, closed at both ends.
+pub struct Cylinder {
+    base: V3,
+    radius: f64,
+    height: f64,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+impl Cylinder {
+    pub fn new(
+        base: V3,
+        radius: f64,
+        height: f64,
+        material: Arc<dyn Material + Send + Sync>,
+    ) -> Self {
+        return Cylinder {
+            base,
+            radius,
+            height,
+            material,
+        };
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut candidates = vec![];
+
+        // the side: x^2 + z^2 = r^2, between the caps
+        let oc = ray.origin - self.base;
+        let a = ray.direction.x * ray.direction.x + ray.direction.z * ray.direction.z;
+        let half_b = oc.x * ray.direction.x + oc.z * ray.direction.z;
+        let c = oc.x * oc.x + oc.z * oc.z - self.radius * self.radius;
+        let discriminant = half_b * half_b - a * c;
+        if a > 0.0 && discriminant >= 0.0 {
+            let sqrtd = f64::sqrt(discriminant);
+            for t in [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a] {
+                let local = ray.at(t) - self.base;
+                if t >= t_min && t <= t_max && local.y >= 0.0 && local.y <= self.height {
+                    let outward_normal = V3::new(local.x, 0.0, local.z) / self.radius;
+                    candidates.push((
+                        t,
+                        outward_normal,
+                        get_angle_around_y(local),
+                        local.y / self.height,
+                        get_angle_around_y_derivative(local),
+                        V3::new(0.0, self.height, 0.0),
+                    ));
+                }
+            }
+        }
+
+        // the caps
+        let top = self.base + V3::new(0.0, self.height, 0.0);
+        if let Some((t, u, v, dpdu, dpdv)) = hit_cap(self.base, self.radius, ray, t_min, t_max) {
+            candidates.push((t, V3::new(0.0, -1.0, 0.0), u, v, dpdu, dpdv));
+        }
+        if let Some((t, u, v, dpdu, dpdv)) = hit_cap(top, self.radius, ray, t_min, t_max) {
+            candidates.push((t, V3::new(0.0, 1.0, 0.0), u, v, dpdu, dpdv));
+        }
+
+        return nearest_candidate(candidates, ray, &self.material);
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        let minimum = self.base - V3::new(self.radius, 0.0, self.radius);
+        let maximum = self.base + V3::new(self.radius, self.height, self.radius);
+        return Some(AABB::new(minimum, maximum));
+    }
+}
+
+/// A cone standing upright on the disk at `base`, with its point `height`
+/// above it. The base is closed.
+pub struct Cone {
+    base: V3,
+    radius: f64,
+    height: f64,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+impl Cone {
+    pub fn new(
+        base: V3,
+        radius: f64,
+        height: f64,
+        material: Arc<dyn Material + Send + Sync>,
+    ) -> Self {
+        return Cone {
+            base,
+            radius,
+            height,
+            material,
+        };
+    }
+}
+
+impl Hittable for Cone {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut candidates = vec![];
+
+        // the side: x^2 + z^2 = (k * h)^2, where h is the height below the
+        // apex and k the ratio of radius to height
+        let k_squared = (self.radius / self.height) * (self.radius / self.height);
+        let oc = ray.origin - self.base;
+        let d = ray.direction;
+        let below_apex = self.height - oc.y;
+        let a = d.x * d.x + d.z * d.z - k_squared * d.y * d.y;
+        let half_b = oc.x * d.x + oc.z * d.z + k_squared * below_apex * d.y;
+        let c = oc.x * oc.x + oc.z * oc.z - k_squared * below_apex * below_apex;
+        let discriminant = half_b * half_b - a * c;
+        if f64::abs(a) > 1e-12 && discriminant >= 0.0 {
+            let sqrtd = f64::sqrt(discriminant);
+            for t in [(-half_b - sqrtd) / a, (-half_b + sqrtd) / a] {
+                let local = ray.at(t) - self.base;
+                if t >= t_min && t <= t_max && local.y >= 0.0 && local.y <= self.height {
+                    let outward_normal = unit_vector(V3::new(
+                        local.x,
+                        k_squared * (self.height - local.y),
+                        local.z,
+                    ));
+                    // the side narrows in towards the apex as v rises
+                    let dpdv =
+                        V3::new(0.0, self.height, 0.0) - self.radius * get_outward_from_y(local);
+                    candidates.push((
+                        t,
+                        outward_normal,
+                        get_angle_around_y(local),
+                        local.y / self.height,
+                        get_angle_around_y_derivative(local),
+                        dpdv,
+                    ));
+                }
+            }
+        }
+
+        if let Some((t, u, v, dpdu, dpdv)) = hit_cap(self.base, self.radius, ray, t_min, t_max) {
+            candidates.push((t, V3::new(0.0, -1.0, 0.0), u, v, dpdu, dpdv));
+        }
+
+        return nearest_candidate(candidates, ray, &self.material);
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        let minimum = self.base - V3::new(self.radius, 0.0, self.radius);
+        let maximum = self.base + V3::new(self.radius, self.height, self.radius);
+        return Some(AABB::new(minimum, maximum));
+    }
+}
+
+/// A ring doughnut lying flat around `centre`. `major_radius` is the
+/// distance from the centre to the middle of the tube, and `minor_radius`
+/// the radius of the tube.
+pub struct Torus {
+    centre: V3,
+    major_radius: f64,
+    minor_radius: f64,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+impl Torus {
+    pub fn new(
+        centre: V3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: Arc<dyn Material + Send + Sync>,
+    ) -> Self {
+        return Torus {
+            centre,
+            major_radius,
+            minor_radius,
+            material,
+        };
+    }
+}
+
+impl Hittable for Torus {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // substitute the ray into (|p|^2 + R^2 - r^2)^2 = 4R^2(x^2 + z^2),
+        // giving a quartic in t. The ray is first moved up to its closest
+        // approach to the centre to keep the coefficients well conditioned.
+        let d = ray.direction;
+        let t_shift = -V3::dot(ray.origin - self.centre, d) / d.length_squared();
+        let o = ray.at(t_shift) - self.centre;
+        let major_squared = self.major_radius * self.major_radius;
+        let dd = d.length_squared();
+        let od = V3::dot(o, d);
+        let g = o.length_squared() + major_squared - self.minor_radius * self.minor_radius;
+        let coefficients = [
+            g * g - 4.0 * major_squared * (o.x * o.x + o.z * o.z),
+            4.0 * od * g - 8.0 * major_squared * (o.x * d.x + o.z * d.z),
+            4.0 * od * od + 2.0 * dd * g - 4.0 * major_squared * (d.x * d.x + d.z * d.z),
+            4.0 * dd * od,
+            dd * dd,
+        ];
+        return solve_polynomial(&coefficients)
+            .into_iter()
+            .map(|t| t + t_shift)
+            .filter(|t| *t >= t_min && *t <= t_max)
+            .map(|t| self.hit_at(ray, t))
+            .find(|hit_record| !hit_record.is_cut_out());
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        let outer = self.major_radius + self.minor_radius;
+        let half_size = V3::new(outer, self.minor_radius, outer);
+        return Some(AABB::new(self.centre - half_size, self.centre + half_size));
+    }
+}
+
+impl Torus {
+    fn hit_at(&self, ray: &Ray, t: f64) -> HitRecord {
+        let point = ray.at(t);
+        let local = point - self.centre;
+        // the normal points away from the nearest point on the tube's centre
+        let around = unit_vector(V3::new(local.x, 0.0, local.z));
+        let tube_centre = self.major_radius * around;
+        let outward_normal = unit_vector(local - tube_centre);
+        let u = get_angle_around_y(local);
+        let from_tube_axis = V3::dot(local, around) - self.major_radius;
+        let tube_angle = f64::atan2(local.y, from_tube_axis);
+        let v = (tube_angle + std::f64::consts::PI) / (2.0 * std::f64::consts::PI);
+        // v turns the point around the tube's own centre line
+        let dpdv = 2.0
+            * std::f64::consts::PI
+            * (from_tube_axis * V3::new(0.0, 1.0, 0.0) - local.y * around);
+        let hit_record = HitRecord::new(
+            t,
+            u,
+            v,
+            point,
+            outward_normal,
+            ray.direction,
+            self.material.clone(),
+        )
+        .with_uv_derivatives(get_angle_around_y_derivative(local), dpdv);
+        return hit_record;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{colour::Colour, material::Lambertian, ray::RayDifferentials};
+
+    fn material() -> Arc<dyn Material + Send + Sync> {
+        return Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!(f64::abs(a - b) < 1e-6, "expected {b}, got {a}");
+    }
+
+    fn assert_close_v3(a: V3, b: V3) {
+        assert!(
+            (a - b).length() < 1e-6,
+            "expected ({}, {}, {}), got ({}, {}, {})",
+            b.x,
+            b.y,
+            b.z,
+            a.x,
+            a.y,
+            a.z
+        );
+    }
+
+    fn ray(origin: V3, direction: V3) -> Ray {
+        return Ray::new(origin, direction, 0.0);
+    }
+
+    #[test]
+    fn plane_hit_from_above() {
+        let plane = Plane::new(V3::new(0.0, 1.0, 0.0), V3::new(0.0, 1.0, 0.0), material());
+        let r = ray(V3::new(2.0, 5.0, 3.0), V3::new(0.0, -2.0, 0.0));
+        let hit = plane.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert_close(hit.time, 2.0);
+        assert_close_v3(hit.point, V3::new(2.0, 1.0, 3.0));
+        assert_close_v3(hit.normal, V3::new(0.0, 1.0, 0.0));
+        assert!(hit.front_face);
+    }
+
+    #[test]
+    fn plane_missed_by_parallel_ray_and_is_unbounded() {
+        let plane = Plane::new(V3::new(0.0, 0.0, 0.0), V3::new(0.0, 1.0, 0.0), material());
+        let r = ray(V3::new(0.0, 1.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        assert!(plane.hit(&r, 0.001, f64::INFINITY).is_none());
+        assert!(plane.bounding_box(0.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn disk_hit_inside_and_miss_outside_radius() {
+        let disk = Disk::new(
+            V3::new(0.0, 0.0, -2.0),
+            V3::new(0.0, 0.0, 1.0),
+            1.0,
+            material(),
+        );
+        let hit = disk
+            .hit(
+                &ray(V3::new(0.5, 0.0, 0.0), V3::new(0.0, 0.0, -1.0)),
+                0.001,
+                10.0,
+            )
+            .unwrap();
+        assert_close(hit.time, 2.0);
+        assert_close(hit.v, 0.5);
+        assert_close_v3(hit.normal, V3::new(0.0, 0.0, 1.0));
+        let miss = disk.hit(
+            &ray(V3::new(1.5, 0.0, 0.0), V3::new(0.0, 0.0, -1.0)),
+            0.001,
+            10.0,
+        );
+        assert!(miss.is_none());
+    }
+
+    #[test]
+    fn cylinder_hit_on_side_and_cap() {
+        let cylinder = Cylinder::new(V3::new(0.0, 0.0, 0.0), 1.0, 2.0, material());
+        let side = cylinder
+            .hit(
+                &ray(V3::new(-5.0, 1.0, 0.0), V3::new(1.0, 0.0, 0.0)),
+                0.001,
+                100.0,
+            )
+            .unwrap();
+        assert_close(side.time, 4.0);
+        assert_close_v3(side.normal, V3::new(-1.0, 0.0, 0.0));
+        assert_close(side.v, 0.5);
+        let cap = cylinder
+            .hit(
+                &ray(V3::new(0.5, 5.0, 0.0), V3::new(0.0, -1.0, 0.0)),
+                0.001,
+                100.0,
+            )
+            .unwrap();
+        assert_close(cap.time, 3.0);
+        assert_close_v3(cap.normal, V3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn cylinder_hit_from_inside_is_back_face() {
+        let cylinder = Cylinder::new(V3::new(0.0, 0.0, 0.0), 1.0, 2.0, material());
+        let hit = cylinder
+            .hit(
+                &ray(V3::new(0.0, 1.0, 0.0), V3::new(0.0, 0.0, 1.0)),
+                0.001,
+                100.0,
+            )
+            .unwrap();
+        assert_close(hit.time, 1.0);
+        assert!(!hit.front_face);
+        assert_close_v3(hit.normal, V3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn cone_hit_on_side_and_base() {
+        let cone = Cone::new(V3::new(0.0, 0.0, 0.0), 1.0, 1.0, material());
+        // halfway up, the cone has radius 0.5
+        let side = cone
+            .hit(
+                &ray(V3::new(-5.0, 0.5, 0.0), V3::new(1.0, 0.0, 0.0)),
+                0.001,
+                100.0,
+            )
+            .unwrap();
+        assert_close(side.time, 4.5);
+        let expected_normal = unit_vector(V3::new(-1.0, 1.0, 0.0));
+        assert_close_v3(side.normal, expected_normal);
+        let base = cone
+            .hit(
+                &ray(V3::new(0.5, -3.0, 0.0), V3::new(0.0, 1.0, 0.0)),
+                0.001,
+                100.0,
+            )
+            .unwrap();
+        assert_close(base.time, 3.0);
+        assert_close_v3(base.normal, V3::new(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn cone_missed_above_apex() {
+        let cone = Cone::new(V3::new(0.0, 0.0, 0.0), 1.0, 1.0, material());
+        let r = ray(V3::new(-5.0, 1.5, 0.0), V3::new(1.0, 0.0, 0.0));
+        assert!(cone.hit(&r, 0.001, 100.0).is_none());
+    }
+
+    #[test]
+    fn torus_hit_outer_and_inner_walls() {
+        let torus = Torus::new(V3::new(0.0, 0.0, 0.0), 2.0, 0.5, material());
+        let r = ray(V3::new(-10.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        let outer = torus.hit(&r, 0.001, 100.0).unwrap();
+        assert_close(outer.time, 7.5);
+        assert_close_v3(outer.normal, V3::new(-1.0, 0.0, 0.0));
+        // starting inside the tube, the ray leaves through the inner wall
+        let inner = torus.hit(&r, 8.0, 100.0).unwrap();
+        assert_close(inner.time, 8.5);
+        assert!(!inner.front_face);
+    }
+
+    #[test]
+    fn torus_missed_through_hole() {
+        let torus = Torus::new(V3::new(0.0, 0.0, 0.0), 2.0, 0.5, material());
+        let r = ray(V3::new(0.0, 10.0, 0.0), V3::new(0.0, -1.0, 0.0));
+        assert!(torus.hit(&r, 0.001, 100.0).is_none());
+        let b = torus.bounding_box(0.0, 1.0).unwrap();
+        assert_close_v3(b.maximum, V3::new(2.5, 0.5, 2.5));
+    }
+
+    #[test]
+    fn moving_sphere_hit_where_it_is_at_the_ray_time() {
+        let sphere = MovingSphere::new(
+            V3::new(0.0, 0.0, 0.0),
+            V3::new(0.0, 2.0, 0.0),
+            0.0,
+            1.0,
+            1.0,
+            material(),
+        );
+        let r = Ray::new(V3::new(-5.0, 1.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.5);
+        let hit = sphere.hit(&r, 0.001, 100.0).unwrap();
+        assert_close(hit.time, 4.0);
+        let b = sphere.bounding_box(0.0, 1.0).unwrap();
+        assert_close_v3(b.minimum, V3::new(-1.0, -1.0, -1.0));
+        assert_close_v3(b.maximum, V3::new(1.0, 3.0, 1.0));
+    }
+
+    #[test]
+    fn moving_sphere_with_no_shutter_time_stays_at_start() {
+        let sphere = MovingSphere::new(
+            V3::new(0.0, 0.0, 0.0),
+            V3::new(0.0, 2.0, 0.0),
+            1.0,
+            1.0,
+            1.0,
+            material(),
+        );
+        let r = Ray::new(V3::new(-5.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 1.0);
+        let hit = sphere.hit(&r, 0.001, 100.0).unwrap();
+        assert_close(hit.time, 4.0);
+        let b = sphere.bounding_box(1.0, 1.0).unwrap();
+        assert_close_v3(b.maximum, V3::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn cylinder_missed_by_rays_parallel_to_its_caps() {
+        let cylinder = Cylinder::new(V3::new(0.0, 0.0, 0.0), 1.0, 2.0, material());
+        // level with each cap: within the radius the ray meets the rim
+        // where the side ends, and beyond it misses altogether
+        for y in [0.0, 2.0] {
+            let r = ray(V3::new(-5.0, y, 0.0), V3::new(1.0, 0.0, 0.0));
+            let hit = cylinder.hit(&r, 0.001, f64::INFINITY).unwrap();
+            assert_close(hit.time, 4.0);
+            assert_close_v3(hit.point, V3::new(-1.0, y, 0.0));
+            let r = ray(V3::new(-5.0, y, 1.5), V3::new(1.0, 0.0, 0.0));
+            assert!(cylinder.hit(&r, f64::NEG_INFINITY, f64::INFINITY).is_none());
+        }
+        // and passing above it
+        let r = ray(V3::new(-5.0, 3.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        assert!(cylinder.hit(&r, f64::NEG_INFINITY, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn uv_derivatives_match_nearby_hits() {
+        let shapes: Vec<(&str, Box<dyn Hittable>)> = vec![
+            (
+                "disk",
+                Box::new(Disk::new(
+                    V3::new(0.0, 0.0, 0.0),
+                    V3::new(1.0, 1.0, 0.0),
+                    2.0,
+                    material(),
+                )),
+            ),
+            (
+                "cylinder",
+                Box::new(Cylinder::new(V3::new(0.0, -1.0, 0.0), 1.0, 2.0, material())),
+            ),
+            (
+                "cone",
+                Box::new(Cone::new(V3::new(0.0, -1.0, 0.0), 1.0, 2.0, material())),
+            ),
+            (
+                "torus",
+                Box::new(Torus::new(V3::new(0.0, 0.0, 0.0), 1.0, 0.5, material())),
+            ),
+        ];
+        // side on, from above onto a cap, and from below
+        let origins = [
+            V3::new(-5.0, 0.3, 0.4),
+            V3::new(0.3, 5.0, 0.2),
+            V3::new(0.2, -5.0, 0.6),
+        ];
+        let targets = [V3::new(0.9, 0.0, 0.0), V3::new(0.2, 0.0, 0.1)];
+        for (name, shape) in shapes {
+            for (origin, target) in origins.into_iter().flat_map(|o| targets.map(|t| (o, t))) {
+                let towards = target - origin;
+                let Some(hit) = shape.hit(&ray(origin, towards), 0.001, f64::INFINITY) else {
+                    continue;
+                };
+                assert!(hit.dpdu.length() > 0.0 || hit.dpdv.length() > 0.0, "{name}");
+                // a ray aimed just along each derivative lands where the
+                // uv has moved by the same small step
+                let step = 1e-4;
+                let to_surface = hit.point - origin;
+                for (derivative, is_u) in [(hit.dpdu, true), (hit.dpdv, false)] {
+                    let nearby = ray(origin, to_surface + step * derivative);
+                    let next = shape.hit(&nearby, 0.001, f64::INFINITY).unwrap();
+                    let moved = if is_u { next.u - hit.u } else { next.v - hit.v };
+                    if f64::abs(moved) > 0.5 {
+                        // wrapped around the seam
+                        continue;
+                    }
+                    assert!(
+                        f64::abs(moved - step) < 1e-5,
+                        "{name}: {} moved by {moved}",
+                        if is_u { "u" } else { "v" },
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cylinder_intervals_along_a_horizontal_ray() {
+        let cylinder = Cylinder::new(V3::new(0.0, 0.0, 0.0), 1.0, 2.0, material());
+        let r = ray(V3::new(-5.0, 1.0, 0.0), V3::new(1.0, 0.0, 0.0));
+        let intervals = cylinder.intervals(&r);
+        assert_eq!(intervals.len(), 1);
+        assert_close(intervals[0].entry.time, 4.0);
+        assert_close(intervals[0].exit.time, 6.0);
+    }
+
+    #[test]
+    fn footprint_from_ray_differentials() {
+        let rect = XZRect::new(0.0, 4.0, 0.0, 2.0, 0.0, material());
+        let origin = V3::new(1.0, 2.0, 1.0);
+        let down = V3::new(0.0, -1.0, 0.0);
+        let plain = ray(origin, down);
+        let hit_record = rect.hit(&plain, 0.001, f64::INFINITY).unwrap();
+        assert!(hit_record.footprint(&plain).is_none());
+
+        let differentials = RayDifferentials {
+            x_origin: origin,
+            x_direction: V3::new(0.1, -1.0, 0.0),
+            y_origin: origin,
+            y_direction: V3::new(0.0, -1.0, 0.1),
+        };
+        let camera_ray = Ray::new_with_differentials(origin, down, 0.0, differentials);
+        let hit_record = rect.hit(&camera_ray, 0.001, f64::INFINITY).unwrap();
+        let footprint = hit_record.footprint(&camera_ray).unwrap();
+        assert_close_v3(footprint.dpdx, V3::new(0.2, 0.0, 0.0));
+        assert_close_v3(footprint.dpdy, V3::new(0.0, 0.0, 0.2));
+        assert_close(footprint.dudx, 0.05);
+        assert_close(footprint.dvdx, 0.0);
+        assert_close(footprint.dudy, 0.0);
+        assert_close(footprint.dvdy, 0.1);
+    }
+}
@@ -1,3 +1,5 @@
+use std::{fs, fs::File, io, path::Path};
+
 use crate::colour::Colour;
 use rayon::prelude::*;
 
@@ -25,23 +27,12 @@ fn print_colour(colour: &Colour) {
     println!("{red} {green} {blue}");
 }
 
-pub fn generate_gradient(width: u64, height: u64) -> Vec<Vec<Colour>> {
-    return generate_image(width, height, |row, col| {
-        return Colour {
-            red: col as f64 / (width - 1) as f64,
-            green: row as f64 / (height - 1) as f64,
-            blue: 0.25,
-        };
-    });
-}
-
 pub fn generate_image<F>(width: u64, height: u64, f: F) -> Vec<Vec<Colour>>
 where
     F: Fn(u64, u64) -> Colour + Sync,
 {
     return (0..height)
         // .into_par_iter()
-        .into_iter()
         .map(|row| {
             let output = (0..width)
                 .into_par_iter()
@@ -55,3 +46,282 @@ where
         })
         .collect();
 }
+
+fn invalid_image(message: &str) -> io::Error {
+    return io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+}
+
+/// The next whitespace separated token in a PNM file, skipping # comments.
+fn next_pnm_token(bytes: &[u8], position: &mut usize) -> io::Result<String> {
+    loop {
+        while *position < bytes.len() && bytes[*position].is_ascii_whitespace() {
+            *position += 1;
+        }
+        if *position < bytes.len() && bytes[*position] == b'#' {
+            while *position < bytes.len() && bytes[*position] != b'\n' {
+                *position += 1;
+            }
+            continue;
+        }
+        break;
+    }
+    let start = *position;
+    while *position < bytes.len() && !bytes[*position].is_ascii_whitespace() {
+        *position += 1;
+    }
+    if start == *position {
+        return Err(invalid_image("unexpected end of image"));
+    }
+    return Ok(String::from_utf8_lossy(&bytes[start..*position]).to_string());
+}
+
+fn next_pnm_number(bytes: &[u8], position: &mut usize) -> io::Result<u64> {
+    return next_pnm_token(bytes, position)?
+        .parse()
+        .map_err(|_| invalid_image("expected a number in image"));
+}
+
+/// Read a PPM or PGM image (binary or plain), in the same layout as
+/// `generate_image`: rows from the bottom up, with values from 0 to 1.
+pub fn read_pnm<P: AsRef<Path>>(path: P) -> io::Result<Vec<Vec<Colour>>> {
+    let bytes = fs::read(path)?;
+    let mut position = 0;
+
+    let (channels, binary) = match next_pnm_token(&bytes, &mut position)?.as_str() {
+        "P2" => (1, false),
+        "P3" => (3, false),
+        "P5" => (1, true),
+        "P6" => (3, true),
+        _ => return Err(invalid_image("not a PPM or PGM image")),
+    };
+    let width = next_pnm_number(&bytes, &mut position)?;
+    let height = next_pnm_number(&bytes, &mut position)?;
+    let max_value = next_pnm_number(&bytes, &mut position)?;
+    if max_value == 0 || max_value > 65535 {
+        return Err(invalid_image("invalid maximum value in image"));
+    }
+
+    if width == 0 || height == 0 {
+        return Err(invalid_image("image has no pixels"));
+    }
+    let count = width
+        .checked_mul(height)
+        .and_then(|n| n.checked_mul(channels))
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or_else(|| invalid_image("image is too large"))?;
+    // every sample takes at least a byte, so a short file can't ask for more
+    let mut samples: Vec<f64> = Vec::with_capacity(usize::min(count, bytes.len()));
+    if binary {
+        // exactly one whitespace character separates the header from the data
+        let mut offset = position + 1;
+        let sample_size = if max_value < 256 { 1 } else { 2 };
+        let end = count
+            .checked_mul(sample_size)
+            .and_then(|n| n.checked_add(offset))
+            .ok_or_else(|| invalid_image("image is too large"))?;
+        if bytes.len() < end {
+            return Err(invalid_image("image data is too short"));
+        }
+        for _ in 0..count {
+            let value = if sample_size == 1 {
+                bytes[offset] as u64
+            } else {
+                (bytes[offset] as u64) << 8 | bytes[offset + 1] as u64
+            };
+            samples.push(value as f64 / max_value as f64);
+            offset += sample_size;
+        }
+    } else {
+        for _ in 0..count {
+            samples.push(next_pnm_number(&bytes, &mut position)? as f64 / max_value as f64);
+        }
+    }
+
+    let mut image: Vec<Vec<Colour>> = samples
+        .chunks_exact((width * channels) as usize)
+        .map(|row| {
+            return row
+                .chunks_exact(channels as usize)
+                .map(|pixel| match pixel {
+                    [grey] => Colour::new(*grey, *grey, *grey),
+                    _ => Colour::new(pixel[0], pixel[1], pixel[2]),
+                })
+                .collect();
+        })
+        .collect();
+    // files store the top row first
+    image.reverse();
+    return Ok(image);
+}
+
+/// Read a PNG image, in the same layout as `read_pnm`. Palettes and low bit
+/// depths are expanded, and any alpha channel is dropped.
+pub fn read_png<P: AsRef<Path>>(path: P) -> io::Result<Vec<Vec<Colour>>> {
+    return decode_png(path, false);
+}
+
+/// Read the alpha channel of a PNG image as a greyscale image, from 0 for
+/// transparent to 1 for opaque. Images without alpha are opaque everywhere.
+pub fn read_png_alpha<P: AsRef<Path>>(path: P) -> io::Result<Vec<Vec<Colour>>> {
+    return decode_png(path, true);
+}
+
+/// The colours of a PNG image, or its alphas as greys.
+fn decode_png<P: AsRef<Path>>(path: P, alpha: bool) -> io::Result<Vec<Vec<Colour>>> {
+    let mut decoder = png::Decoder::new(File::open(path)?);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| invalid_image(&e.to_string()))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|e| invalid_image(&e.to_string()))?;
+
+    let channels = info.color_type.samples();
+    let samples: Vec<f64> = match info.bit_depth {
+        png::BitDepth::Sixteen => buffer[..info.buffer_size()]
+            .chunks_exact(2)
+            .map(|b| ((b[0] as u64) << 8 | b[1] as u64) as f64 / 65535.0)
+            .collect(),
+        _ => buffer[..info.buffer_size()]
+            .iter()
+            .map(|&b| b as f64 / 255.0)
+            .collect(),
+    };
+    let mut image: Vec<Vec<Colour>> = samples
+        .chunks_exact(info.width as usize * channels)
+        .map(|row| {
+            return row
+                .chunks_exact(channels)
+                .map(|pixel| match (alpha, pixel) {
+                    (true, [_, a] | [_, _, _, a]) => Colour::new(*a, *a, *a),
+                    (true, _) => Colour::new(1.0, 1.0, 1.0),
+                    (false, [grey] | [grey, _]) => Colour::new(*grey, *grey, *grey),
+                    (false, _) => Colour::new(pixel[0], pixel[1], pixel[2]),
+                })
+                .collect();
+        })
+        .collect();
+    // files store the top row first
+    image.reverse();
+    return Ok(image);
+}
+
+/// Read a PNG, PPM or PGM image, chosen by the file's extension.
+pub fn read_image<P: AsRef<Path>>(path: P) -> io::Result<Vec<Vec<Colour>>> {
+    let extension = path
+        .as_ref()
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("png") => return read_png(path),
+        _ => return read_pnm(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, bytes).unwrap();
+        return path;
+    }
+
+    #[test]
+    fn plain_ppm_is_read_bottom_row_first() {
+        let path = temp_file(
+            "rt_plain.ppm",
+            b"P3\n# two by two\n2 2\n255\n255 0 0  0 255 0\n0 0 255  255 255 255\n",
+        );
+        let image = read_image(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(image.len(), 2);
+        // the first row in the file is the top one
+        assert_eq!(image[1][0].red, 1.0);
+        assert_eq!(image[1][1].green, 1.0);
+        assert_eq!(image[0][0].blue, 1.0);
+    }
+
+    #[test]
+    fn binary_pgm_with_sixteen_bit_samples() {
+        let mut bytes = b"P5 2 1 65535\n".to_vec();
+        bytes.extend_from_slice(&[0, 0, 255, 255]);
+        let path = temp_file("rt_binary.pgm", &bytes);
+        let image = read_image(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(image[0][0].red, 0.0);
+        assert_eq!(image[0][1].green, 1.0);
+    }
+
+    #[test]
+    fn truncated_pnm_is_invalid() {
+        let path = temp_file("rt_short.ppm", b"P6 4 4 255\n\x00\x00");
+        let error = read_image(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn pnm_without_pixels_is_invalid() {
+        let path = temp_file("rt_empty.ppm", b"P3 0 0 255\n");
+        let error = read_image(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn pnm_too_large_to_hold_is_invalid() {
+        for (name, header) in [
+            ("rt_huge.ppm", "P6 4294967296 4294967296 255\n"),
+            ("rt_large.pgm", "P2 3000000000 3000000000 255\n0 0 0\n"),
+        ] {
+            let path = temp_file(name, header.as_bytes());
+            let error = read_image(&path).err().unwrap();
+            fs::remove_file(&path).unwrap();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn png_colours_and_alphas_are_read() {
+        let path = std::env::temp_dir().join("rt_image.png");
+        {
+            let file = File::create(&path).unwrap();
+            let mut encoder = png::Encoder::new(file, 2, 1);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer
+                .write_image_data(&[255, 0, 0, 255, 0, 0, 255, 0])
+                .unwrap();
+        }
+        let image = read_image(&path).unwrap();
+        let alphas = read_png_alpha(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(image[0][0].red, 1.0);
+        assert_eq!(image[0][1].blue, 1.0);
+        assert_eq!(alphas[0][0].red, 1.0);
+        assert_eq!(alphas[0][1].green, 0.0);
+    }
+
+    #[test]
+    fn png_without_alpha_is_opaque() {
+        let path = std::env::temp_dir().join("rt_opaque.png");
+        {
+            let file = File::create(&path).unwrap();
+            let mut encoder = png::Encoder::new(file, 2, 1);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&[0, 128]).unwrap();
+        }
+        let alphas = read_png_alpha(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(alphas[0][0].red, 1.0);
+        assert_eq!(alphas[0][1].red, 1.0);
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    bvh::BVHNode,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    transform::Transformed,
+    v3::M4,
+};
+
+/// Geometry that is built once and shared between many instances. It owns
+/// its own BVH (the bottom level of the acceleration structure) in object
+/// space.
+#[derive(Clone)]
+pub struct Geometry {
+    bvh: Arc<BVHNode>,
+}
+
+impl Geometry {
+    pub fn new(hittables: Vec<Arc<dyn Hittable + Send + Sync>>, t_0: f64, t_1: f64) -> Self {
+        return Geometry {
+            bvh: Arc::new(BVHNode::new(hittables, t_0, t_1)),
+        };
+    }
+}
+
+/// One placement of some shared `Geometry` in the world, optionally
+/// replacing the materials of the geometry with its own.
+pub struct Instance {
+    placement: Transformed,
+    material: Option<Arc<dyn Material + Send + Sync>>,
+}
+
+impl Instance {
+    pub fn new(geometry: &Geometry, transform: M4) -> Self {
+        return Instance {
+            placement: Transformed::new(geometry.bvh.clone(), transform),
+            material: None,
+        };
+    }
+
+    pub fn new_with_material(
+        geometry: &Geometry,
+        transform: M4,
+        material: Arc<dyn Material + Send + Sync>,
+    ) -> Self {
+        return Instance {
+            placement: Transformed::new(geometry.bvh.clone(), transform),
+            material: Some(material),
+        };
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let material = match &self.material {
+            Some(material) => material,
+            None => return self.placement.hit(ray, t_min, t_max),
+        };
+        // the geometry only knows its own materials' alpha, so step past
+        // any hit that the replacement material cuts out
+        let mut t_min = t_min;
+        loop {
+            let mut hit_record = self.placement.hit(ray, t_min, t_max)?;
+            hit_record.material = material.clone();
+            if !hit_record.is_cut_out() {
+                return Some(hit_record);
+            }
+            t_min = hit_record.time + 0.0001;
+        }
+    }
+
+    fn bounding_box(&self, t_0: f64, t_1: f64) -> Option<AABB> {
+        return self.placement.bounding_box(t_0, t_1);
+    }
+}
+
+/// Build the top level of the acceleration structure: a BVH over the world
+/// space bounds of each instance.
+pub fn build_instance_bvh(instances: Vec<Instance>, t_0: f64, t_1: f64) -> BVHNode {
+    let hittables = instances
+        .into_iter()
+        .map(|instance| Arc::new(instance) as Arc<dyn Hittable + Send + Sync>)
+        .collect();
+    return BVHNode::new(hittables, t_0, t_1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        colour::Colour,
+        hittable::Sphere,
+        material::{AlphaMask, AlphaMode, Lambertian},
+        texture::SolidColour,
+        v3::V3,
+    };
+
+    fn geometry() -> Geometry {
+        let material = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+        let sphere = Arc::new(Sphere::new(V3::new(0.0, 0.0, 0.0), 1.0, material));
+        return Geometry::new(vec![sphere], 0.0, 1.0);
+    }
+
+    fn ray_down_z() -> Ray {
+        return Ray::new(V3::new(0.0, 0.0, 10.0), V3::new(0.0, 0.0, -1.0), 0.0);
+    }
+
+    #[test]
+    fn instances_share_geometry_in_different_places() {
+        let geometry = geometry();
+        let near = Instance::new(&geometry, M4::translation(V3::new(0.0, 0.0, 5.0)));
+        let far = Instance::new(&geometry, M4::translation(V3::new(0.0, 0.0, -5.0)));
+        let world = build_instance_bvh(vec![near, far], 0.0, 1.0);
+        let hit = world.hit(&ray_down_z(), 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 4.0) < 1e-6);
+        let hit = world.hit(&ray_down_z(), 7.0, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 14.0) < 1e-6);
+    }
+
+    #[test]
+    fn cut_out_override_material_lets_rays_through() {
+        let geometry = geometry();
+        let transparent = Arc::new(AlphaMask::new(
+            Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5))),
+            SolidColour::new(Colour::new(0.0, 0.0, 0.0)),
+            AlphaMode::Threshold(0.5),
+        ));
+        let instance = Instance::new_with_material(&geometry, M4::identity(), transparent);
+        assert!(instance.hit(&ray_down_z(), 0.001, f64::INFINITY).is_none());
+    }
+}
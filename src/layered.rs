@@ -0,0 +1,434 @@
+use std::{f64::consts::PI, sync::Arc, sync::OnceLock};
+
+use crate::{
+    colour::Colour,
+    hittable::HitRecord,
+    material::{BsdfSample, Material},
+    microfacet::{
+        fresnel_schlick, ggx_g1, ggx_g2, ggx_reflection_pdf, random_cosine_direction,
+        sample_ggx_visible_normal, Frame, MIN_ALPHA,
+    },
+    ray::Ray,
+    v3::{reflect, unit_vector, V3},
+};
+
+/// How much a clearcoat reflects head on, for an index of refraction of 1.5.
+const CLEARCOAT_F0: f64 = 0.04;
+
+/// The smallest alpha used for sheen, below which the Charlie distribution
+/// is too sharp to sample well.
+const MIN_SHEEN_ALPHA: f64 = 0.01;
+
+/// The number of angles and roughnesses the sheen's albedo is tabulated at.
+const SHEEN_TABLE_SIZE: usize = 16;
+
+/// A base material under a clear varnish and a soft sheen, for car paint,
+/// lacquered wood and cloth. Light reflects off the clearcoat as a GGX
+/// dielectric; what isn't reflected meets the sheen, a layer of fibres
+/// scattering at grazing angles; and what the sheen doesn't scatter reaches
+/// the base. Each layer only gets the light the ones above leave it, so the
+/// whole never reflects more than comes in.
+pub struct Layered {
+    base: Arc<dyn Material + Send + Sync>,
+    /// How much clearcoat there is, from 0 for none to 1 for a full coat
+    clearcoat: f64,
+    clearcoat_roughness: f64,
+    /// The colour of the sheen, or black for none
+    sheen: Colour,
+    sheen_roughness: f64,
+}
+
+impl Layered {
+    pub fn new(
+        base: Arc<dyn Material + Send + Sync>,
+        clearcoat: f64,
+        clearcoat_roughness: f64,
+        sheen: Colour,
+        sheen_roughness: f64,
+    ) -> Self {
+        return Layered {
+            base,
+            clearcoat: f64::clamp(clearcoat, 0.0, 1.0),
+            clearcoat_roughness,
+            sheen,
+            sheen_roughness,
+        };
+    }
+
+    pub fn new_clearcoat(
+        base: Arc<dyn Material + Send + Sync>,
+        clearcoat: f64,
+        clearcoat_roughness: f64,
+    ) -> Self {
+        return Layered::new(
+            base,
+            clearcoat,
+            clearcoat_roughness,
+            Colour::new(0.0, 0.0, 0.0),
+            0.0,
+        );
+    }
+
+    pub fn new_sheen(
+        base: Arc<dyn Material + Send + Sync>,
+        sheen: Colour,
+        sheen_roughness: f64,
+    ) -> Self {
+        return Layered::new(base, 0.0, 0.0, sheen, sheen_roughness);
+    }
+
+    /// The proportion of light the clearcoat reflects at an angle with the
+    /// given cosine.
+    fn clearcoat_reflectance(&self, cosine: f64) -> f64 {
+        let f0 = Colour::new(CLEARCOAT_F0, CLEARCOAT_F0, CLEARCOAT_F0);
+        return self.clearcoat * fresnel_schlick(f0, cosine).red;
+    }
+}
+
+/// The layers of a `Layered` surface at a hit, seen along the incoming ray.
+struct Coating {
+    frame: Frame,
+    /// The direction back along the ray, in `frame`
+    wo: V3,
+    clearcoat_alpha: f64,
+    /// The chance of reflecting off the clearcoat
+    clearcoat_probability: f64,
+    /// What the sheen is scaled by to keep it from scattering more light
+    /// than comes in
+    sheen_scale: f64,
+    /// The chance of the sheen scattering what gets through the clearcoat
+    sheen_probability: f64,
+}
+
+impl Layered {
+    fn coating(&self, ray_in: &Ray, hit_record: &HitRecord) -> Coating {
+        let frame = Frame::new(hit_record.shading_normal);
+        let wo = frame.to_local(-unit_vector(ray_in.direction));
+        let wo = unit_vector(V3::new(wo.x, wo.y, f64::max(wo.z, 1e-4)));
+        let clearcoat_alpha = f64::max(
+            self.clearcoat_roughness * self.clearcoat_roughness,
+            MIN_ALPHA,
+        );
+
+        // the Charlie model scatters more than comes in at grazing angles,
+        // so scale it down to all of it there
+        let albedo = sheen_albedo(wo.z, self.sheen_roughness);
+        let sheen_scale = if albedo > 1.0 { 1.0 / albedo } else { 1.0 };
+        let sheen_strength = f64::max(self.sheen.red, f64::max(self.sheen.green, self.sheen.blue));
+        return Coating {
+            frame,
+            wo,
+            clearcoat_alpha,
+            clearcoat_probability: self.clearcoat_reflectance(wo.z),
+            sheen_scale,
+            sheen_probability: f64::min(sheen_strength * sheen_scale * albedo, 1.0),
+        };
+    }
+
+    /// How much of the light leaving from under the clearcoat along the
+    /// world direction `wi` gets through it, rather than reflecting back in.
+    fn transmitted(&self, hit_record: &HitRecord, wi: V3) -> f64 {
+        let cos_out = V3::dot(wi, hit_record.shading_normal);
+        return 1.0 - self.clearcoat_reflectance(f64::abs(cos_out));
+    }
+
+    /// The pdf of sampling the world direction `wi` from any of the smooth
+    /// lobes, and the BSDF times the cosine for it.
+    fn evaluate(
+        &self,
+        coating: &Coating,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        wi: V3,
+    ) -> (f64, Colour) {
+        let wo = coating.wo;
+        let under = 1.0 - coating.clearcoat_probability;
+        let transmitted = self.transmitted(hit_record, wi);
+        let mut pdf =
+            under * (1.0 - coating.sheen_probability) * self.base.pdf(ray_in, hit_record, wi);
+        let mut value = (under * (1.0 - coating.sheen_probability) * transmitted)
+            * self.base.eval(ray_in, hit_record, wi);
+
+        let local = coating.frame.to_local(wi);
+        if local.z > 0.0 && V3::dot(wi, hit_record.normal) > 0.0 {
+            // the clearcoat reflects as much as its Fresnel term says,
+            // leaving the shadowing
+            let alpha = coating.clearcoat_alpha;
+            let m = unit_vector(wo + local);
+            let clearcoat_pdf = coating.clearcoat_probability * ggx_reflection_pdf(wo, m, alpha);
+            let shadowing = ggx_g2(wo, local, alpha) / ggx_g1(wo, alpha);
+            pdf += clearcoat_pdf;
+            value = value + (clearcoat_pdf * shadowing) * Colour::new(1.0, 1.0, 1.0);
+
+            pdf += under * coating.sheen_probability * local.z / PI;
+            let sheen = under
+                * transmitted
+                * coating.sheen_scale
+                * sheen_brdf(wo, local, self.sheen_roughness)
+                * local.z;
+            value = value + sheen * self.sheen;
+        }
+        return (pdf, value);
+    }
+}
+
+impl Material for Layered {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        let sample = self.sample(ray_in, hit_record)?;
+        return Some(sample.scattered(ray_in));
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let coating = self.coating(ray_in, hit_record);
+        let wo = coating.wo;
+
+        // reflect off the clearcoat, then scatter off the sheen, then the
+        // base gets what's left
+        let (origin, direction) = if coating.clearcoat_probability > rand::random::<f64>() {
+            let wi = reflect(-wo, sample_ggx_visible_normal(wo, coating.clearcoat_alpha));
+            let direction = coating.frame.to_world(wi);
+            if wi.z <= 0.0 || V3::dot(direction, hit_record.normal) <= 0.0 {
+                return None;
+            }
+            (hit_record.point, direction)
+        } else if coating.sheen_probability > rand::random::<f64>() {
+            let direction = coating.frame.to_world(random_cosine_direction());
+            if V3::dot(direction, hit_record.normal) <= 0.0 {
+                return None;
+            }
+            (hit_record.point, direction)
+        } else {
+            let sample = self.base.sample(ray_in, hit_record)?;
+            if sample.is_delta {
+                // which the smooth lobes can't have picked
+                let under =
+                    (1.0 - coating.clearcoat_probability) * (1.0 - coating.sheen_probability);
+                return Some(BsdfSample {
+                    weight: self.transmitted(hit_record, sample.direction) * sample.weight,
+                    pdf: under * sample.pdf,
+                    ..sample
+                });
+            }
+            (sample.origin, sample.direction)
+        };
+
+        // weigh the direction by the chance of any smooth lobe picking it
+        let (pdf, value) = self.evaluate(&coating, ray_in, hit_record, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+        return Some(BsdfSample {
+            origin,
+            direction,
+            weight: value / pdf,
+            pdf,
+            is_delta: false,
+        });
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> Colour {
+        let coating = self.coating(ray_in, hit_record);
+        return self.evaluate(&coating, ray_in, hit_record, wi).1;
+    }
+
+    fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> f64 {
+        let coating = self.coating(ray_in, hit_record);
+        return self.evaluate(&coating, ray_in, hit_record, wi).0;
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: V3) -> Colour {
+        return self.base.emitted(u, v, p);
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: V3) -> f64 {
+        return self.base.alpha(u, v, p);
+    }
+}
+
+/// The sheen's reflectance for a white sheen colour: the Charlie
+/// distribution of fibres (Estevez and Kulla, "Production Friendly
+/// Microfacet Sheen BRDF") with Ashikhmin's visibility term.
+fn sheen_brdf(wo: V3, wi: V3, roughness: f64) -> f64 {
+    if wo.z <= 0.0 || wi.z <= 0.0 {
+        return 0.0;
+    }
+    let alpha = f64::max(roughness * roughness, MIN_SHEEN_ALPHA);
+    let m = unit_vector(wo + wi);
+    let sin_theta = f64::sqrt(f64::max(1.0 - m.z * m.z, 0.0));
+    let distribution = (2.0 + 1.0 / alpha) * f64::powf(sin_theta, 1.0 / alpha) / (2.0 * PI);
+    let visibility = 1.0 / (4.0 * (wi.z + wo.z - wi.z * wo.z));
+    return distribution * visibility;
+}
+
+/// The proportion of light a white sheen scatters, seen from an angle with
+/// the given cosine. This is worked out once for a table of angles and
+/// roughnesses, then interpolated.
+fn sheen_albedo(cosine: f64, roughness: f64) -> f64 {
+    static TABLE: OnceLock<Vec<f64>> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let last = (SHEEN_TABLE_SIZE - 1) as f64;
+        let mut table = Vec::with_capacity(SHEEN_TABLE_SIZE * SHEEN_TABLE_SIZE);
+        for j in 0..SHEEN_TABLE_SIZE {
+            let roughness = j as f64 / last;
+            for i in 0..SHEEN_TABLE_SIZE {
+                let cosine = f64::max(i as f64 / last, 1e-3);
+                let wo = V3::new(f64::sqrt(1.0 - cosine * cosine), 0.0, cosine);
+                // integrate over a grid of cosine weighted directions
+                let steps = 64;
+                let mut total = 0.0;
+                for a in 0..steps {
+                    for b in 0..steps {
+                        let r = f64::sqrt((a as f64 + 0.5) / steps as f64);
+                        let phi = 2.0 * PI * (b as f64 + 0.5) / steps as f64;
+                        let z = f64::sqrt(f64::max(0.0, 1.0 - r * r));
+                        let wi = V3::new(r * f64::cos(phi), r * f64::sin(phi), z);
+                        total += PI * sheen_brdf(wo, wi, roughness);
+                    }
+                }
+                table.push(total / (steps * steps) as f64);
+            }
+        }
+        return table;
+    });
+
+    let last = (SHEEN_TABLE_SIZE - 1) as f64;
+    let x = f64::clamp(cosine, 0.0, 1.0) * last;
+    let y = f64::clamp(roughness, 0.0, 1.0) * last;
+    let (i, j) = (
+        f64::min(x.floor(), last - 1.0) as usize,
+        f64::min(y.floor(), last - 1.0) as usize,
+    );
+    let (s, t) = (x - i as f64, y - j as f64);
+    let at = |i: usize, j: usize| table[j * SHEEN_TABLE_SIZE + i];
+    return (1.0 - t) * ((1.0 - s) * at(i, j) + s * at(i + 1, j))
+        + t * ((1.0 - s) * at(i, j + 1) + s * at(i + 1, j + 1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{
+        tests::{check_sampling, integrate_sphere},
+        Lambertian, Metal,
+    };
+
+    fn hit_from(direction: V3, material: Arc<dyn Material + Send + Sync>) -> HitRecord {
+        return HitRecord::new(
+            1.0,
+            0.5,
+            0.5,
+            V3::new(0.0, 0.0, 0.0),
+            V3::new(0.0, 0.0, 1.0),
+            direction,
+            material,
+        );
+    }
+
+    /// The average weight of light `material` scatters back from a ray
+    /// along `direction`, so its albedo in that direction.
+    fn albedo(material: Arc<dyn Material + Send + Sync>, direction: V3) -> Colour {
+        let ray = Ray::new(-direction, direction, 0.0);
+        let hit_record = hit_from(direction, material.clone());
+        let samples = 20000;
+        let mut total = Colour::new(0.0, 0.0, 0.0);
+        for _ in 0..samples {
+            if let Some((scattered, attenuation)) = material.scatter(&ray, &hit_record) {
+                assert!(V3::dot(scattered.direction, hit_record.normal) > 0.0);
+                total = total + attenuation;
+            }
+        }
+        return total / samples as f64;
+    }
+
+    #[test]
+    fn no_coat_or_sheen_is_just_the_base() {
+        let base = Arc::new(Lambertian::new(Colour::new(0.2, 0.4, 0.6)));
+        let layered = Arc::new(Layered::new_clearcoat(base, 0.0, 0.5));
+        let albedo = albedo(layered, V3::new(0.0, 0.0, -1.0));
+        assert!(f64::abs(albedo.red - 0.2) < 1e-9);
+        assert!(f64::abs(albedo.blue - 0.6) < 1e-9);
+    }
+
+    #[test]
+    fn smooth_clearcoat_over_black_reflects_its_fresnel() {
+        let base = Arc::new(Lambertian::new(Colour::new(0.0, 0.0, 0.0)));
+        let layered = Arc::new(Layered::new_clearcoat(base, 1.0, 0.0));
+        let albedo = albedo(layered, V3::new(0.0, 0.0, -1.0));
+        assert!(f64::abs(albedo.green - CLEARCOAT_F0) < 0.01);
+    }
+
+    #[test]
+    fn layers_do_not_add_energy() {
+        let white = Colour::new(1.0, 1.0, 1.0);
+        for roughness in [0.1, 0.5, 1.0] {
+            let base = Arc::new(Lambertian::new(white));
+            let layered = Arc::new(Layered::new(base, 1.0, roughness, white, roughness));
+            for direction in [
+                V3::new(0.0, 0.0, -1.0),
+                unit_vector(V3::new(3.0, 0.0, -1.0)),
+            ] {
+                let albedo = albedo(layered.clone(), direction);
+                assert!(albedo.red < 1.03, "albedo {} at {roughness}", albedo.red);
+                assert!(albedo.red > 0.7, "albedo {} at {roughness}", albedo.red);
+            }
+        }
+    }
+
+    #[test]
+    fn sheen_scatters_most_at_grazing_angles() {
+        assert!(sheen_albedo(0.1, 0.5) > sheen_albedo(1.0, 0.5));
+        // but never more than comes in, even where the model alone would
+        let black = Arc::new(Lambertian::new(Colour::new(0.0, 0.0, 0.0)));
+        let white = Colour::new(1.0, 1.0, 1.0);
+        for roughness in [0.25, 0.5] {
+            let sheen = Arc::new(Layered::new_sheen(black.clone(), white, roughness));
+            let grazing = unit_vector(V3::new(1.0, 0.0, -0.02));
+            // integrated rather than sampled, as the grazing sheen is noisy
+            let ray = Ray::new(-grazing, grazing, 0.0);
+            let hit_record = hit_from(grazing, sheen.clone());
+            let albedo = integrate_sphere(|wi| sheen.eval(&ray, &hit_record, wi).red);
+            assert!(albedo < 1.03, "albedo {albedo} at {roughness}");
+            assert!(albedo > 0.5, "albedo {albedo} at {roughness}");
+        }
+    }
+
+    #[test]
+    fn layers_sample_consistently() {
+        let white = Colour::new(1.0, 1.0, 1.0);
+        let direction = unit_vector(V3::new(1.0, 0.0, -1.0));
+        for base in [
+            Arc::new(Lambertian::new(Colour::new(0.6, 0.3, 0.2)))
+                as Arc<dyn Material + Send + Sync>,
+            Arc::new(Metal::new(Colour::new(0.9, 0.9, 0.9), 0.4)),
+        ] {
+            check_sampling(
+                Arc::new(Layered::new(base, 1.0, 0.5, white, 0.5)),
+                direction,
+            );
+        }
+    }
+
+    #[test]
+    fn mirror_base_stays_a_delta_lobe_under_the_coat() {
+        let mirror = Arc::new(Metal::new(Colour::new(1.0, 1.0, 1.0), 0.0));
+        let material: Arc<dyn Material + Send + Sync> =
+            Arc::new(Layered::new_clearcoat(mirror, 1.0, 0.5));
+        let direction = V3::new(0.0, 0.0, -1.0);
+        let ray = Ray::new(-direction, direction, 0.0);
+        let hit_record = hit_from(direction, material.clone());
+        let under = 1.0 - CLEARCOAT_F0;
+        let mut deltas = 0;
+        for _ in 0..1000 {
+            let Some(sample) = material.sample(&ray, &hit_record) else {
+                continue;
+            };
+            if sample.is_delta {
+                deltas += 1;
+                // straight back out, losing what the coat reflects back in
+                assert!(f64::abs(sample.pdf - under) < 1e-9);
+                assert!(f64::abs(sample.weight.red - under) < 1e-9);
+            }
+        }
+        assert!(deltas > 900);
+    }
+}
@@ -1,19 +1,37 @@
-use rand;
+// the code returns explicitly everywhere, and names acronyms like AABB in
+// capitals
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
 
 use colour::Colour;
 use hittable::Hittable;
 use ray::Ray;
 
 mod aabb;
+mod bump;
 mod bvh;
 mod camera;
 mod colour;
+mod csg;
+mod curve;
+mod heightfield;
 mod hittable;
 mod image;
+mod instance;
+mod layered;
 mod material;
+mod medium;
+mod mesh;
+mod microfacet;
+mod noise;
+mod point_cloud;
 mod ray;
 mod scenes;
+mod sdf;
+mod spectrum;
+mod subsurface;
 mod texture;
+mod transform;
+mod triangle;
 mod utils;
 mod v3;
 
@@ -22,6 +40,14 @@ enum Fidelity {
     Full,
 }
 
+/// How light is carried along paths: as red, green and blue together, or as
+/// one randomly chosen wavelength per path so that materials can bend each
+/// wavelength differently.
+enum ColourMode {
+    Rgb,
+    Spectral,
+}
+
 fn get_fidelity(fidelity: Fidelity, aspect_ratio: f64) -> (u64, u64, u64, u64) {
     let image_width: u64 = match fidelity {
         Fidelity::Small => 200,
@@ -41,10 +67,33 @@ fn get_fidelity(fidelity: Fidelity, aspect_ratio: f64) -> (u64, u64, u64, u64) {
 }
 
 fn main() {
-    render_scene(scenes::SceneConfig::NTS, Fidelity::Full);
+    // the scene to render by name, with --small for a quick preview and
+    // --spectral to render with dispersion
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let scene = match args.iter().find(|arg| !arg.starts_with("--")) {
+        Some(name) => match scenes::SceneConfig::from_name(name) {
+            Some(scene) => scene,
+            None => {
+                eprintln!("Unknown scene {name}");
+                std::process::exit(1);
+            }
+        },
+        None => scenes::SceneConfig::NTS,
+    };
+    let fidelity = if args.iter().any(|arg| arg == "--small") {
+        Fidelity::Small
+    } else {
+        Fidelity::Full
+    };
+    let colour_mode = if args.iter().any(|arg| arg == "--spectral") {
+        ColourMode::Spectral
+    } else {
+        ColourMode::Rgb
+    };
+    render_scene(scene, fidelity, colour_mode);
 }
 
-fn render_scene(scene: scenes::SceneConfig, fidelity: Fidelity) {
+fn render_scene(scene: scenes::SceneConfig, fidelity: Fidelity, colour_mode: ColourMode) {
     let scene = scenes::get_scene(scene);
     let (samples_per_pixel, max_depth, image_width, image_height) =
         get_fidelity(fidelity, scene.aspect_ratio);
@@ -57,18 +106,36 @@ fn render_scene(scene: scenes::SceneConfig, fidelity: Fidelity) {
             let v_d: f64 = rand::random();
             let u = (col as f64 + u_d) / (image_width as f64 - 1.0);
             let v = (row as f64 + v_d) / (image_height as f64 - 1.0);
-            let ray = &scene.camera.get_ray(u, v);
-            colour = colour + ray_colour(&ray, &scene.world, max_depth);
+            let mut ray = scene.camera.get_ray(
+                u,
+                v,
+                1.0 / (image_width as f64 - 1.0),
+                1.0 / (image_height as f64 - 1.0),
+            );
+            match colour_mode {
+                ColourMode::Rgb => {
+                    colour = colour + ray_colour(&ray, scene.world.as_ref(), max_depth);
+                }
+                ColourMode::Spectral => {
+                    let wavelength = spectrum::random_wavelength();
+                    ray.wavelength = Some(wavelength);
+                    colour = colour
+                        + spectrum::wavelength_weight(wavelength)
+                            * ray_colour(&ray, scene.world.as_ref(), max_depth);
+                }
+            }
         }
-        return colour.gamma_correct(1.0 / samples_per_pixel as f64);
+        // a pixel that only saw a few saturated wavelengths can come out with
+        // negative parts, which have no square root
+        return colour
+            .clamp_non_negative()
+            .gamma_correct(1.0 / samples_per_pixel as f64);
     });
     image::print_image(image_width, image_height, i);
 }
 
-fn ray_colour(ray: &Ray, world: &Box<dyn Hittable + Send + Sync>, depth: u64) -> colour::Colour
-where
-{
-    if depth <= 0 {
+fn ray_colour(ray: &Ray, world: &(dyn Hittable + Send + Sync), depth: u64) -> colour::Colour {
+    if depth == 0 {
         return Colour::new(0.0, 0.0, 0.0);
     }
 
@@ -76,11 +143,14 @@ where
         let emitted = hit_record
             .material
             .emitted(hit_record.u, hit_record.v, hit_record.point);
-        if let Some((scattered_ray, attenuation)) = hit_record.material.scatter(ray, &hit_record) {
-            return emitted + attenuation * ray_colour(&scattered_ray, world, depth - 1);
-        } else {
+        let Some(sample) = hit_record.material.sample(ray, &hit_record) else {
+            return emitted;
+        };
+        if !sample.is_delta && sample.pdf <= 0.0 {
             return emitted;
         }
+        let (scattered_ray, attenuation) = sample.scattered(ray);
+        return emitted + attenuation * ray_colour(&scattered_ray, world, depth - 1);
     } else {
         return Colour::new(0.0, 0.0, 0.0);
         // let unit_direction = v3::unit_vector(ray.direction);
@@ -1,18 +1,75 @@
-use std::sync::Arc;
+use std::{f64::consts::PI, sync::Arc};
 
 use crate::{
     colour::Colour,
     hittable::HitRecord,
     ray::Ray,
-    texture::{SolidColour, Texture},
-    v3::{random_in_unit_sphere, random_unit_vector, reflect, unit_vector, V3},
+    spectrum::WAVELENGTH_D_LINE,
+    texture::{brightness, SolidColour, Texture},
+    v3::{orthonormal_basis, random_in_unit_sphere, random_unit_vector, reflect, unit_vector, V3},
 };
 
+/// A direction picked from a material's scattering distribution.
+pub struct BsdfSample {
+    /// Where the scattered light leaves from: the hit point, except for
+    /// materials which scatter light inside themselves
+    pub origin: V3,
+    /// The direction light scatters into, as a unit vector
+    pub direction: V3,
+    /// The BSDF times the cosine over the pdf, which multiplies the light
+    /// arriving back along `direction`
+    pub weight: Colour,
+    /// The probability density of `direction` over solid angle, or for a
+    /// delta lobe the probability of having picked that lobe
+    pub pdf: f64,
+    /// Whether `direction` came from a mirror-like lobe, which `eval` and
+    /// `pdf` can't see and so light sampling can never hit
+    pub is_delta: bool,
+}
+
+impl BsdfSample {
+    /// The ray leaving along the sample, carrying on the time and wavelength
+    /// of `ray_in`, and its weight, as `Material::scatter` returns them.
+    pub fn scattered(&self, ray_in: &Ray) -> (Ray, Colour) {
+        let mut ray = Ray::new(self.origin, self.direction, ray_in.time);
+        ray.wavelength = ray_in.wavelength;
+        return (ray, self.weight);
+    }
+}
+
 pub trait Material {
     fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)>;
-    fn emitted(&self, u: f64, v: f64, p: V3) -> Colour {
+    /// Pick a direction to scatter light into. Materials which only
+    /// implement `scatter` come out as a delta lobe wrapping it.
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let (scattered, attenuation) = self.scatter(ray_in, hit_record)?;
+        return Some(BsdfSample {
+            origin: scattered.origin,
+            direction: unit_vector(scattered.direction),
+            weight: attenuation,
+            pdf: 1.0,
+            is_delta: true,
+        });
+    }
+    /// The BSDF times the cosine of the incoming angle, for light arriving
+    /// from the unit vector `wi` and leaving back along `ray_in`. Delta
+    /// lobes are left out.
+    fn eval(&self, _ray_in: &Ray, _hit_record: &HitRecord, _wi: V3) -> Colour {
         return Colour::new(0.0, 0.0, 0.0);
     }
+    /// The probability density over solid angle of `sample` picking `wi`,
+    /// leaving out delta lobes.
+    fn pdf(&self, _ray_in: &Ray, _hit_record: &HitRecord, _wi: V3) -> f64 {
+        return 0.0;
+    }
+    fn emitted(&self, _u: f64, _v: f64, _p: V3) -> Colour {
+        return Colour::new(0.0, 0.0, 0.0);
+    }
+    /// How opaque the surface is at a point, from 0 where rays pass straight
+    /// through it to 1 where they always hit it.
+    fn alpha(&self, _u: f64, _v: f64, _p: V3) -> f64 {
+        return 1.0;
+    }
 }
 
 pub struct Lambertian {
@@ -26,27 +83,70 @@ impl Lambertian {
             albedo: Arc::new(texture),
         };
     }
-    pub fn new_from_texture<T: 'static>(texture: T) -> Self
-    where
-        T: Texture + Send + Sync,
-    {
+    pub fn new_from_texture<T: Texture + Send + Sync + 'static>(texture: T) -> Self {
         return Lambertian {
             albedo: Arc::new(texture),
         };
     }
+
+    /// The density of cosine sampling about the shading normal, with the
+    /// directions that fall into the surface folded back out across it.
+    fn direction_pdf(hit_record: &HitRecord, wi: V3) -> f64 {
+        let into_surface = V3::dot(wi, hit_record.normal);
+        if into_surface <= 0.0 {
+            return 0.0;
+        }
+        let folded = wi - 2.0 * into_surface * hit_record.normal;
+        let normal = hit_record.shading_normal;
+        let cosine = f64::max(V3::dot(wi, normal), 0.0) + f64::max(V3::dot(folded, normal), 0.0);
+        return cosine / PI;
+    }
+
+    /// The albedo at the hit, averaged over the pixel's footprint.
+    fn colour(&self, ray_in: &Ray, hit_record: &HitRecord) -> Colour {
+        let (u, v, p) = (hit_record.u, hit_record.v, hit_record.point);
+        match hit_record.footprint(ray_in) {
+            Some(footprint) => return self.albedo.colour_filtered(u, v, p, &footprint),
+            None => return self.albedo.colour(u, v, p),
+        }
+    }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
-        let mut scatter_direction = hit_record.normal + random_unit_vector();
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        let sample = self.sample(ray_in, hit_record)?;
+        return Some(sample.scattered(ray_in));
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let normal = hit_record.shading_normal;
+        let mut scatter_direction = normal + random_unit_vector();
         if scatter_direction.near_zero() {
-            scatter_direction = hit_record.normal;
+            scatter_direction = normal;
         }
-        let scattered = Ray::new(hit_record.point, scatter_direction);
-        let colour = self
-            .albedo
-            .colour(hit_record.u, hit_record.v, hit_record.point);
-        return Some((scattered, colour));
+        // a tilted shading normal can send light into the surface; reflect
+        // it back out rather than letting it leak through
+        let into_surface = V3::dot(scatter_direction, hit_record.normal);
+        if into_surface < 0.0 {
+            scatter_direction = scatter_direction - 2.0 * into_surface * hit_record.normal;
+        }
+        let direction = unit_vector(scatter_direction);
+        return Some(BsdfSample {
+            origin: hit_record.point,
+            direction,
+            weight: self.colour(ray_in, hit_record),
+            pdf: Lambertian::direction_pdf(hit_record, direction),
+            is_delta: false,
+        });
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> Colour {
+        // matches the sampling exactly, so the weight is just the albedo
+        return Lambertian::direction_pdf(hit_record, wi) * self.colour(ray_in, hit_record);
+    }
+
+    fn pdf(&self, _ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> f64 {
+        return Lambertian::direction_pdf(hit_record, wi);
     }
 }
 
@@ -63,71 +163,209 @@ impl Metal {
             fuzz: limited_fuzz,
         };
     }
+
+    /// The density of a unit direction `wi` when the mirror direction of
+    /// `wo` is nudged by a random point in a ball of radius `fuzz`: the
+    /// share of the ball's volume inside the cone of directions around `wi`.
+    fn fuzz_pdf(&self, hit_record: &HitRecord, wo: V3, wi: V3) -> f64 {
+        if V3::dot(wi, hit_record.normal) <= 0.0 {
+            return 0.0;
+        }
+        let reflected = reflect(-wo, hit_record.shading_normal);
+        // where the line along wi enters and leaves the ball
+        let middle = V3::dot(wi, reflected);
+        let discriminant = middle * middle - reflected.length_squared() + self.fuzz * self.fuzz;
+        if discriminant <= 0.0 {
+            return 0.0;
+        }
+        let near = f64::max(middle - f64::sqrt(discriminant), 0.0);
+        let far = middle + f64::sqrt(discriminant);
+        if far <= 0.0 {
+            return 0.0;
+        }
+        return (far.powi(3) - near.powi(3)) / (4.0 * PI * self.fuzz.powi(3));
+    }
 }
 
 impl Material for Metal {
     fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
-        let reflected = reflect(unit_vector(ray_in.direction), hit_record.normal);
-        let scattered = Ray::new(
-            hit_record.point,
-            reflected + self.fuzz * random_in_unit_sphere(),
-        );
-        if V3::dot(scattered.direction, hit_record.normal) <= 0.0 {
+        let sample = self.sample(ray_in, hit_record)?;
+        return Some(sample.scattered(ray_in));
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let wo = -unit_vector(ray_in.direction);
+        let reflected = reflect(-wo, hit_record.shading_normal);
+        let direction = unit_vector(reflected + self.fuzz * random_in_unit_sphere());
+        if V3::dot(direction, hit_record.normal) <= 0.0 {
             return None;
         }
-        return Some((scattered, self.albedo));
+        if self.fuzz <= 0.0 {
+            return Some(BsdfSample {
+                origin: hit_record.point,
+                direction,
+                weight: self.albedo,
+                pdf: 1.0,
+                is_delta: true,
+            });
+        }
+        return Some(BsdfSample {
+            origin: hit_record.point,
+            direction,
+            weight: self.albedo,
+            pdf: self.fuzz_pdf(hit_record, wo, direction),
+            is_delta: false,
+        });
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> Colour {
+        return self.pdf(ray_in, hit_record, wi) * self.albedo;
+    }
+
+    fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> f64 {
+        if self.fuzz <= 0.0 {
+            return 0.0;
+        }
+        return self.fuzz_pdf(hit_record, -unit_vector(ray_in.direction), wi);
+    }
+}
+
+/// How a material's index of refraction varies with wavelength. Only
+/// spectral rendering sees the variation; RGB rendering uses the index at
+/// the helium d line (587.6 nm).
+#[derive(Clone, Copy)]
+pub enum IndexOfRefraction {
+    Constant(f64),
+    /// `a + b / λ²`, with λ in micrometres
+    Cauchy {
+        a: f64,
+        b: f64,
+    },
+    /// `n² = 1 + Σ b λ² / (λ² - c)`, with λ in micrometres
+    Sellmeier {
+        b: [f64; 3],
+        c: [f64; 3],
+    },
+}
+
+impl IndexOfRefraction {
+    /// Schott N-BK7 crown glass.
+    pub fn crown_glass() -> Self {
+        return IndexOfRefraction::Sellmeier {
+            b: [1.03961212, 0.231792344, 1.01046945],
+            c: [0.00600069867, 0.0200179144, 103.560653],
+        };
+    }
+
+    pub fn diamond() -> Self {
+        return IndexOfRefraction::Sellmeier {
+            b: [0.3306, 4.3356, 0.0],
+            c: [0.030625, 0.011236, 0.0],
+        };
+    }
+
+    /// The index at a wavelength in nanometres, or at the D line if none.
+    pub fn at(&self, wavelength: Option<f64>) -> f64 {
+        let micrometres = wavelength.unwrap_or(WAVELENGTH_D_LINE) / 1000.0;
+        let squared = micrometres * micrometres;
+        match *self {
+            IndexOfRefraction::Constant(index) => return index,
+            IndexOfRefraction::Cauchy { a, b } => return a + b / squared,
+            IndexOfRefraction::Sellmeier { b, c } => {
+                let sum: f64 = (0..3).map(|i| b[i] * squared / (squared - c[i])).sum();
+                return f64::sqrt(1.0 + sum);
+            }
+        }
     }
 }
 
 pub struct Dielectric {
-    index_of_refraction: f64,
+    index_of_refraction: IndexOfRefraction,
 }
 
 impl Dielectric {
     pub fn new(index_of_refraction: f64) -> Self {
+        return Dielectric {
+            index_of_refraction: IndexOfRefraction::Constant(index_of_refraction),
+        };
+    }
+
+    /// A dielectric which splits white light into colours when rendering
+    /// spectrally.
+    pub fn new_dispersive(index_of_refraction: IndexOfRefraction) -> Self {
         return Dielectric {
             index_of_refraction,
         };
     }
 }
 
-fn refract(uv: V3, n: V3, etai_over_etat: f64) -> V3 {
+pub fn refract(uv: V3, n: V3, etai_over_etat: f64) -> V3 {
     let cos_theta = f64::min(V3::dot(-uv, n), 1.0);
     let r_out_perp = etai_over_etat * (uv + cos_theta * n);
     let r_out_parallel = -f64::sqrt(f64::abs(1.0 - r_out_perp.length_squared())) * n;
     return r_out_perp + r_out_parallel;
 }
 
-fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
+pub fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
     // Use Schlick's approximation for reflectance.
     let mut r0 = (1.0 - ref_idx) / (1.0 + ref_idx);
     r0 = r0 * r0;
     return r0 + (1.0 - r0) * f64::powi(1.0 - cosine, 5);
 }
 
+/// The direction light takes at a smooth boundary between two dielectrics,
+/// reflecting or refracting at random in proportion to the Fresnel term.
+/// `normal` faces against `unit_direction`.
+pub fn reflect_or_refract(unit_direction: V3, normal: V3, refraction_ratio: f64) -> V3 {
+    let cos_theta = f64::clamp(V3::dot(-unit_direction, normal), 0.0, 1.0);
+    let sin_theta = f64::sqrt(1.0 - cos_theta * cos_theta);
+    // due to snells law, and a sin cannot be bigger than 0
+    let cannot_refract = refraction_ratio * sin_theta > 1.0;
+    if cannot_refract || reflectance(cos_theta, refraction_ratio) > rand::random() {
+        return reflect(unit_direction, normal);
+    } else {
+        return refract(unit_direction, normal, refraction_ratio);
+    }
+}
+
 impl Material for Dielectric {
     fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
-        let attenuation = Colour::new(1.0, 1.0, 1.0);
+        let sample = self.sample(ray_in, hit_record)?;
+        return Some(sample.scattered(ray_in));
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let index_of_refraction = self.index_of_refraction.at(ray_in.wavelength);
         let refraction_ratio = if hit_record.front_face {
-            1.0 / self.index_of_refraction
+            1.0 / index_of_refraction
         } else {
-            self.index_of_refraction
+            index_of_refraction
         };
         let unit_direction = unit_vector(ray_in.direction);
+        let normal = hit_record.shading_normal;
 
-        let cos_theta = f64::min(V3::dot(-unit_direction, hit_record.normal), 1.0);
+        let cos_theta = f64::clamp(V3::dot(-unit_direction, normal), 0.0, 1.0);
         let sin_theta = f64::sqrt(1.0 - cos_theta * cos_theta);
-        // due to snells law, and a sin cannot be bigger than 0
-        let cannot_refract = refraction_ratio * sin_theta > 1.0;
-        let direction =
-            if cannot_refract || reflectance(cos_theta, refraction_ratio) > rand::random() {
-                reflect(unit_direction, hit_record.normal)
-            } else {
-                refract(unit_direction, hit_record.normal, refraction_ratio)
-            };
-
-        let scattered_ray = Ray::new(hit_record.point, direction);
-        return Some((scattered_ray, attenuation));
+        let reflect_probability = if refraction_ratio * sin_theta > 1.0 {
+            1.0
+        } else {
+            reflectance(cos_theta, refraction_ratio)
+        };
+        // both lobes are perfectly smooth, so picking one in proportion to
+        // the Fresnel term leaves a weight of one
+        let (direction, pdf) = if reflect_probability > rand::random() {
+            (reflect(unit_direction, normal), reflect_probability)
+        } else {
+            let refracted = refract(unit_direction, normal, refraction_ratio);
+            (refracted, 1.0 - reflect_probability)
+        };
+        return Some(BsdfSample {
+            origin: hit_record.point,
+            direction: unit_vector(direction),
+            weight: Colour::new(1.0, 1.0, 1.0),
+            pdf,
+            is_delta: true,
+        });
     }
 }
 
@@ -142,10 +380,7 @@ impl DiffuseLight {
             emit: Arc::new(texture),
         };
     }
-    pub fn new_from_texture<T: 'static>(texture: T) -> Self
-    where
-        T: Texture + Send + Sync,
-    {
+    pub fn new_from_texture<T: Texture + Send + Sync + 'static>(texture: T) -> Self {
         return DiffuseLight {
             emit: Arc::new(texture),
         };
@@ -158,7 +393,551 @@ impl Material for DiffuseLight {
         return None;
     }
 
+    fn sample(&self, _ray_in: &Ray, _hit_record: &HitRecord) -> Option<BsdfSample> {
+        return None;
+    }
+
     fn emitted(&self, u: f64, v: f64, p: V3) -> Colour {
         return self.emit.colour(u, v, p);
     }
 }
+
+/// Scatters light equally in all directions. Used as the phase function of
+/// participating media.
+pub struct Isotropic {
+    albedo: Arc<dyn Texture + Send + Sync>,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Colour) -> Self {
+        let texture = SolidColour::new(albedo);
+        return Isotropic {
+            albedo: Arc::new(texture),
+        };
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        let sample = self.sample(ray_in, hit_record)?;
+        return Some(sample.scattered(ray_in));
+    }
+
+    fn sample(&self, _ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let colour = self
+            .albedo
+            .colour(hit_record.u, hit_record.v, hit_record.point);
+        return Some(BsdfSample {
+            origin: hit_record.point,
+            direction: random_unit_vector(),
+            weight: colour,
+            pdf: 1.0 / (4.0 * PI),
+            is_delta: false,
+        });
+    }
+
+    // phase functions have no cosine, as there's no surface to foreshorten
+    fn eval(&self, _ray_in: &Ray, hit_record: &HitRecord, _wi: V3) -> Colour {
+        let colour = self
+            .albedo
+            .colour(hit_record.u, hit_record.v, hit_record.point);
+        return (1.0 / (4.0 * PI)) * colour;
+    }
+
+    fn pdf(&self, _ray_in: &Ray, _hit_record: &HitRecord, _wi: V3) -> f64 {
+        return 1.0 / (4.0 * PI);
+    }
+}
+
+/// The Henyey-Greenstein phase function, for media which scatter more light
+/// forwards (`g` > 0) or backwards (`g` < 0) than to the sides.
+pub struct HenyeyGreenstein {
+    albedo: Colour,
+    /// The anisotropy, from -1 to 1. Zero scatters equally in all directions.
+    g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Colour, g: f64) -> Self {
+        return HenyeyGreenstein {
+            albedo,
+            g: f64::clamp(g, -0.99, 0.99),
+        };
+    }
+
+    /// Sample the cosine of the angle between the incoming and scattered
+    /// directions.
+    fn sample_cos_theta(&self) -> f64 {
+        let xi: f64 = rand::random();
+        if f64::abs(self.g) < 1e-3 {
+            return 1.0 - 2.0 * xi;
+        }
+        let g = self.g;
+        let sqr_term = (1.0 - g * g) / (1.0 - g + 2.0 * g * xi);
+        return (1.0 + g * g - sqr_term * sqr_term) / (2.0 * g);
+    }
+
+    /// The density of scattering to `wi` from a ray travelling along
+    /// `ray_in`, which is also the phase function.
+    fn phase(&self, ray_in: &Ray, wi: V3) -> f64 {
+        let g = self.g;
+        let cos_theta = V3::dot(unit_vector(ray_in.direction), wi);
+        let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+        return (1.0 - g * g) / (4.0 * PI * denominator * f64::sqrt(denominator));
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        let sample = self.sample(ray_in, hit_record)?;
+        return Some(sample.scattered(ray_in));
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let forward = unit_vector(ray_in.direction);
+        let cos_theta = f64::clamp(self.sample_cos_theta(), -1.0, 1.0);
+        let sin_theta = f64::sqrt(1.0 - cos_theta * cos_theta);
+        let phi = 2.0 * PI * rand::random::<f64>();
+        let (s, t) = orthonormal_basis(forward);
+        let direction =
+            sin_theta * f64::cos(phi) * s + sin_theta * f64::sin(phi) * t + cos_theta * forward;
+        return Some(BsdfSample {
+            origin: hit_record.point,
+            direction,
+            weight: self.albedo,
+            pdf: self.phase(ray_in, direction),
+            is_delta: false,
+        });
+    }
+
+    fn eval(&self, ray_in: &Ray, _hit_record: &HitRecord, wi: V3) -> Colour {
+        return self.phase(ray_in, wi) * self.albedo;
+    }
+
+    fn pdf(&self, ray_in: &Ray, _hit_record: &HitRecord, wi: V3) -> f64 {
+        return self.phase(ray_in, wi);
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum AlphaMode {
+    /// Cut out wherever the opacity is below the threshold, for hard edged
+    /// masks like leaves and fences
+    Threshold(f64),
+    /// Let rays through at random as often as the surface is transparent,
+    /// for partly see-through surfaces which average out over many samples
+    Stochastic,
+}
+
+/// Cuts holes in a material with the brightness of an opacity texture, from
+/// black for transparent to white for opaque. Rays passing through a hole
+/// carry on as if the surface were not there.
+pub struct AlphaMask {
+    material: Arc<dyn Material + Send + Sync>,
+    opacity: Arc<dyn Texture + Send + Sync>,
+    mode: AlphaMode,
+}
+
+impl AlphaMask {
+    pub fn new<T: Texture + Send + Sync + 'static>(
+        material: Arc<dyn Material + Send + Sync>,
+        opacity: T,
+        mode: AlphaMode,
+    ) -> Self {
+        return AlphaMask {
+            material,
+            opacity: Arc::new(opacity),
+            mode,
+        };
+    }
+}
+
+impl Material for AlphaMask {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        return self.material.scatter(ray_in, hit_record);
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        return self.material.sample(ray_in, hit_record);
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> Colour {
+        return self.material.eval(ray_in, hit_record, wi);
+    }
+
+    fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> f64 {
+        return self.material.pdf(ray_in, hit_record, wi);
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: V3) -> Colour {
+        return self.material.emitted(u, v, p);
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: V3) -> f64 {
+        let opacity = brightness(self.opacity.colour(u, v, p)) * self.material.alpha(u, v, p);
+        match self.mode {
+            AlphaMode::Threshold(threshold) => {
+                return if opacity < threshold { 0.0 } else { 1.0 };
+            }
+            AlphaMode::Stochastic => return f64::clamp(opacity, 0.0, 1.0),
+        }
+    }
+}
+
+/// A blend of two materials, by a fixed amount or by the brightness of a
+/// mask texture, from all of `a` at 0 to all of `b` at 1, such as rust
+/// patches on metal. Each hit scatters off one of the two, picked with the
+/// chance of its share of the blend.
+pub struct MixMaterial {
+    a: Arc<dyn Material + Send + Sync>,
+    b: Arc<dyn Material + Send + Sync>,
+    mask: Arc<dyn Texture + Send + Sync>,
+}
+
+impl MixMaterial {
+    pub fn new(
+        a: Arc<dyn Material + Send + Sync>,
+        b: Arc<dyn Material + Send + Sync>,
+        factor: f64,
+    ) -> Self {
+        return MixMaterial::new_with_mask(
+            a,
+            b,
+            SolidColour::new(Colour::new(factor, factor, factor)),
+        );
+    }
+
+    pub fn new_with_mask<T: Texture + Send + Sync + 'static>(
+        a: Arc<dyn Material + Send + Sync>,
+        b: Arc<dyn Material + Send + Sync>,
+        mask: T,
+    ) -> Self {
+        return MixMaterial {
+            a,
+            b,
+            mask: Arc::new(mask),
+        };
+    }
+
+    /// The share of `b` at a point. Emission and alpha are looked up without
+    /// a ray, so the mask is never filtered, keeping every part of the
+    /// material using the same blend.
+    fn factor(&self, u: f64, v: f64, p: V3) -> f64 {
+        return f64::clamp(brightness(self.mask.colour(u, v, p)), 0.0, 1.0);
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        let sample = self.sample(ray_in, hit_record)?;
+        return Some(sample.scattered(ray_in));
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let factor = self.factor(hit_record.u, hit_record.v, hit_record.point);
+        let (chosen, share) = if factor > rand::random::<f64>() {
+            (&self.b, factor)
+        } else {
+            (&self.a, 1.0 - factor)
+        };
+        let sample = chosen.sample(ray_in, hit_record)?;
+        if sample.is_delta {
+            // the other material can't pick the same direction, so picking by
+            // the blend's share weights it correctly without scaling
+            return Some(BsdfSample {
+                pdf: share * sample.pdf,
+                ..sample
+            });
+        }
+        // either material could have picked a direction from a smooth lobe,
+        // so weigh it by the blend of both
+        let pdf = self.pdf(ray_in, hit_record, sample.direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+        return Some(BsdfSample {
+            weight: self.eval(ray_in, hit_record, sample.direction) / pdf,
+            pdf,
+            ..sample
+        });
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> Colour {
+        let factor = self.factor(hit_record.u, hit_record.v, hit_record.point);
+        return (1.0 - factor) * self.a.eval(ray_in, hit_record, wi)
+            + factor * self.b.eval(ray_in, hit_record, wi);
+    }
+
+    fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> f64 {
+        let factor = self.factor(hit_record.u, hit_record.v, hit_record.point);
+        return (1.0 - factor) * self.a.pdf(ray_in, hit_record, wi)
+            + factor * self.b.pdf(ray_in, hit_record, wi);
+    }
+
+    fn emitted(&self, u: f64, v: f64, p: V3) -> Colour {
+        let factor = self.factor(u, v, p);
+        return (1.0 - factor) * self.a.emitted(u, v, p) + factor * self.b.emitted(u, v, p);
+    }
+
+    fn alpha(&self, u: f64, v: f64, p: V3) -> f64 {
+        let factor = self.factor(u, v, p);
+        return (1.0 - factor) * self.a.alpha(u, v, p) + factor * self.b.alpha(u, v, p);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        hittable::{Hittable, XZRect},
+        ray::RayDifferentials,
+        texture::Checkers,
+    };
+
+    fn assert_close(a: f64, b: f64, tolerance: f64) {
+        assert!(f64::abs(a - b) < tolerance, "expected {b}, got {a}");
+    }
+
+    /// A hit on the xy plane, facing up z, by a ray along `direction`.
+    fn hit_from(direction: V3, material: Arc<dyn Material + Send + Sync>) -> HitRecord {
+        return HitRecord::new(
+            1.0,
+            0.5,
+            0.5,
+            V3::new(0.0, 0.0, 0.0),
+            V3::new(0.0, 0.0, 1.0),
+            direction,
+            material,
+        );
+    }
+
+    #[test]
+    fn index_of_refraction_by_wavelength() {
+        // N-BK7 is specified as 1.5168 at the d line
+        let crown = IndexOfRefraction::crown_glass();
+        assert_close(crown.at(None), 1.5168, 1e-4);
+        assert!(crown.at(Some(450.0)) > crown.at(Some(650.0)));
+        assert!(IndexOfRefraction::diamond().at(None) > 2.4);
+        let cauchy = IndexOfRefraction::Cauchy { a: 1.5, b: 0.01 };
+        assert_close(cauchy.at(Some(500.0)), 1.54, 1e-12);
+        assert_close(IndexOfRefraction::Constant(1.3).at(Some(400.0)), 1.3, 1e-12);
+    }
+
+    #[test]
+    fn dispersive_glass_bends_blue_more_than_red() {
+        let material: Arc<dyn Material + Send + Sync> =
+            Arc::new(Dielectric::new_dispersive(IndexOfRefraction::diamond()));
+        let direction = unit_vector(V3::new(1.0, 0.0, -1.0));
+        let hit_record = hit_from(direction, material.clone());
+        let refracted_x = |wavelength: f64| {
+            let mut ray = Ray::new(V3::new(-1.0, 0.0, 1.0), direction, 0.0);
+            ray.wavelength = Some(wavelength);
+            // keep trying until the ray refracts rather than reflects
+            loop {
+                let sample = material.sample(&ray, &hit_record).unwrap();
+                if sample.direction.z < 0.0 {
+                    return sample.direction.x;
+                }
+            }
+        };
+        // more bending leaves the ray closer to the normal
+        assert!(refracted_x(450.0) < refracted_x(650.0));
+    }
+
+    /// The integral of `f` over every direction, by the midpoint rule in
+    /// the cosine of the angle from z and the angle around it.
+    pub(crate) fn integrate_sphere<F: Fn(V3) -> f64>(f: F) -> f64 {
+        let steps = 500;
+        let d_cos = 2.0 / steps as f64;
+        let d_phi = 2.0 * PI / steps as f64;
+        let mut total = 0.0;
+        for i in 0..steps {
+            let cos_theta = -1.0 + (i as f64 + 0.5) * d_cos;
+            let sin_theta = f64::sqrt(1.0 - cos_theta * cos_theta);
+            for j in 0..steps {
+                let phi = (j as f64 + 0.5) * d_phi;
+                let w = V3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta);
+                total += f(w);
+            }
+        }
+        return total * d_cos * d_phi;
+    }
+
+    /// Check that `material`'s pdf for light arriving along `direction`
+    /// integrates to one, less the chance of a sample being thrown away for
+    /// going under the surface, and that the samples it takes have the
+    /// weight and pdf that `eval` and `pdf` give them.
+    pub(crate) fn check_sampling(material: Arc<dyn Material + Send + Sync>, direction: V3) {
+        let hit_record = hit_from(direction, material);
+        check_sampling_at(&Ray::new(-direction, direction, 0.0), &hit_record);
+    }
+
+    /// `check_sampling` for the material of a given hit.
+    pub(crate) fn check_sampling_at(ray: &Ray, hit_record: &HitRecord) {
+        let material = &hit_record.material;
+        let total = integrate_sphere(|wi| material.pdf(ray, hit_record, wi));
+
+        let samples = 2000;
+        let mut taken = 0;
+        for _ in 0..samples {
+            let Some(sample) = material.sample(ray, hit_record) else {
+                continue;
+            };
+            taken += 1;
+            assert!(!sample.is_delta);
+            let pdf = material.pdf(ray, hit_record, sample.direction);
+            let value = material.eval(ray, hit_record, sample.direction);
+            assert_close(sample.pdf, pdf, 1e-6 * pdf + 1e-9);
+            for (weight, value) in [
+                (sample.weight.red, value.red),
+                (sample.weight.green, value.green),
+                (sample.weight.blue, value.blue),
+            ] {
+                assert_close(weight * pdf, value, 1e-6 * value + 1e-9);
+            }
+        }
+        assert_close(total, taken as f64 / samples as f64, 3e-2);
+    }
+
+    #[test]
+    fn lambertian_and_fuzzy_metal_sample_consistently() {
+        let direction = unit_vector(V3::new(1.0, 0.0, -1.0));
+        check_sampling(
+            Arc::new(Lambertian::new(Colour::new(0.5, 0.6, 0.7))),
+            direction,
+        );
+        check_sampling(
+            Arc::new(Metal::new(Colour::new(0.9, 0.8, 0.7), 0.3)),
+            direction,
+        );
+    }
+
+    #[test]
+    fn lambertian_evaluates_the_filtered_albedo() {
+        let checkers =
+            Checkers::new_from_colours(PI, Colour::new(1.0, 1.0, 1.0), Colour::new(0.0, 0.0, 0.0));
+        let material = Arc::new(Lambertian::new_from_texture(checkers));
+        let rect = XZRect::new(-4.0, 4.0, -4.0, 4.0, 0.0, material);
+        let origin = V3::new(0.5, 2.0, 0.5);
+        let down = V3::new(0.0, -1.0, 0.0);
+        // a pixel wide enough to cover whole checks, which blend to grey
+        let differentials = RayDifferentials {
+            x_origin: origin,
+            x_direction: V3::new(1.0, -1.0, 0.0),
+            y_origin: origin,
+            y_direction: V3::new(0.0, -1.0, 1.0),
+        };
+        let ray = Ray::new_with_differentials(origin, down, 0.0, differentials);
+        let hit_record = rect.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        let value = hit_record.material.eval(&ray, &hit_record, -down);
+        assert_close(value.red * PI, 0.5, 1e-2);
+        check_sampling_at(&ray, &hit_record);
+    }
+
+    #[test]
+    fn phase_functions_sample_consistently() {
+        let direction = unit_vector(V3::new(1.0, 2.0, -1.0));
+        check_sampling(
+            Arc::new(Isotropic::new(Colour::new(0.8, 0.8, 0.8))),
+            direction,
+        );
+        for g in [-0.5, 0.0, 0.3] {
+            let material = Arc::new(HenyeyGreenstein::new(Colour::new(0.8, 0.8, 0.8), g));
+            check_sampling(material, direction);
+        }
+    }
+
+    #[test]
+    fn henyey_greenstein_mean_cosine_is_g() {
+        let material: Arc<dyn Material + Send + Sync> =
+            Arc::new(HenyeyGreenstein::new(Colour::new(1.0, 1.0, 1.0), 0.6));
+        let direction = V3::new(0.0, 0.0, -1.0);
+        let hit_record = hit_from(direction, material.clone());
+        let ray = Ray::new(-direction, direction, 0.0);
+        let samples = 20000;
+        let total: f64 = (0..samples)
+            .map(|_| {
+                V3::dot(
+                    material.sample(&ray, &hit_record).unwrap().direction,
+                    direction,
+                )
+            })
+            .sum();
+        assert_close(total / samples as f64, 0.6, 2e-2);
+    }
+
+    #[test]
+    fn mix_weights_each_lobe_by_the_blend() {
+        let red = Arc::new(Lambertian::new(Colour::new(1.0, 0.0, 0.0)));
+        let blue = Arc::new(Lambertian::new(Colour::new(0.0, 0.0, 1.0)));
+        let material: Arc<dyn Material + Send + Sync> = Arc::new(MixMaterial::new(red, blue, 0.25));
+        let direction = V3::new(0.0, 0.0, -1.0);
+        let hit_record = hit_from(direction, material.clone());
+        let ray = Ray::new(-direction, direction, 0.0);
+        // both lobes are the same shape, so every sample has the blend
+        for _ in 0..100 {
+            let sample = material.sample(&ray, &hit_record).unwrap();
+            assert_close(sample.weight.red, 0.75, 1e-9);
+            assert_close(sample.weight.blue, 0.25, 1e-9);
+        }
+        check_sampling(material, direction);
+    }
+
+    #[test]
+    fn mix_with_a_mirror_keeps_the_mirror_a_delta_lobe() {
+        let grey = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+        let mirror = Arc::new(Metal::new(Colour::new(1.0, 1.0, 1.0), 0.0));
+        let material: Arc<dyn Material + Send + Sync> =
+            Arc::new(MixMaterial::new(grey, mirror, 0.5));
+        let direction = unit_vector(V3::new(1.0, 0.0, -1.0));
+        let hit_record = hit_from(direction, material.clone());
+        let ray = Ray::new(-direction, direction, 0.0);
+        let samples = 20000;
+        let mut total = 0.0;
+        for _ in 0..samples {
+            let sample = material.sample(&ray, &hit_record).unwrap();
+            if sample.is_delta {
+                assert_close(sample.pdf, 0.5, 1e-9);
+            }
+            total += sample.weight.red;
+        }
+        assert_close(total / samples as f64, 0.75, 1e-2);
+    }
+
+    #[test]
+    fn mix_of_smooth_lobes_samples_consistently() {
+        let grey = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+        let fuzzy = Arc::new(Metal::new(Colour::new(0.9, 0.6, 0.3), 0.5));
+        let direction = unit_vector(V3::new(1.0, 0.0, -2.0));
+        check_sampling(Arc::new(MixMaterial::new(grey, fuzzy, 0.6)), direction);
+    }
+
+    /// Black below the x axis, white above it.
+    struct HalfAndHalf;
+
+    impl Texture for HalfAndHalf {
+        fn colour(&self, _u: f64, _v: f64, p: V3) -> Colour {
+            if p.y < 0.0 {
+                return Colour::new(0.0, 0.0, 0.0);
+            }
+            return Colour::new(1.0, 1.0, 1.0);
+        }
+    }
+
+    #[test]
+    fn mix_uses_the_mask_for_emission_and_alpha() {
+        let light = Arc::new(DiffuseLight::new(Colour::new(2.0, 2.0, 2.0)));
+        let hole = Arc::new(AlphaMask::new(
+            Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5))),
+            SolidColour::new(Colour::new(0.0, 0.0, 0.0)),
+            AlphaMode::Threshold(0.5),
+        ));
+        let material = MixMaterial::new_with_mask(light, hole, HalfAndHalf);
+        let below = V3::new(0.0, -1.0, 0.0);
+        let above = V3::new(0.0, 1.0, 0.0);
+        assert_close(material.emitted(0.0, 0.0, below).red, 2.0, 1e-12);
+        assert_close(material.emitted(0.0, 0.0, above).red, 0.0, 1e-12);
+        assert_close(material.alpha(0.0, 0.0, below), 1.0, 1e-12);
+        assert_close(material.alpha(0.0, 0.0, above), 0.0, 1e-12);
+    }
+}
@@ -0,0 +1,393 @@
+use std::{fs, io, path::Path, sync::Arc};
+
+use crate::{
+    aabb::AABB,
+    colour::Colour,
+    hittable::{HitRecord, Hittable},
+    material::{HenyeyGreenstein, Isotropic, Material},
+    noise::Perlin,
+    ray::Ray,
+    v3::V3,
+};
+
+/// A volume of uniform density, such as fog or smoke, filling the inside of
+/// a closed boundary. Rays passing through it may scatter at any point, with
+/// the chance of scattering increasing with the density and the distance
+/// travelled.
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable + Send + Sync>,
+    phase_function: Arc<dyn Material + Send + Sync>,
+    neg_inv_density: f64,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Arc<dyn Hittable + Send + Sync>, density: f64, colour: Colour) -> Self {
+        return ConstantMedium {
+            boundary,
+            phase_function: Arc::new(Isotropic::new(colour)),
+            neg_inv_density: -1.0 / density,
+        };
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // find where the ray enters and leaves the boundary, regardless of
+        // where the ray starts
+        let entry = self.boundary.hit(ray, f64::NEG_INFINITY, f64::INFINITY)?;
+        let exit = self.boundary.hit(ray, entry.time + 0.0001, f64::INFINITY)?;
+
+        let t_enter = f64::max(entry.time, t_min);
+        let t_exit = f64::min(exit.time, t_max);
+        if t_enter >= t_exit {
+            return None;
+        }
+        let t_enter = f64::max(t_enter, 0.0);
+
+        let ray_length = ray.direction.length();
+        let distance_inside_boundary = (t_exit - t_enter) * ray_length;
+        let hit_distance = self.neg_inv_density * f64::ln(rand::random());
+        if hit_distance > distance_inside_boundary {
+            return None;
+        }
+
+        let t = t_enter + hit_distance / ray_length;
+        let point = ray.at(t);
+        // the normal is arbitrary, as the phase function does not use it
+        let hit_record = HitRecord::new(
+            t,
+            0.0,
+            0.0,
+            point,
+            V3::new(1.0, 0.0, 0.0),
+            ray.direction,
+            self.phase_function.clone(),
+        );
+        return Some(hit_record);
+    }
+
+    fn bounding_box(&self, t_0: f64, t_1: f64) -> Option<AABB> {
+        return self.boundary.bounding_box(t_0, t_1);
+    }
+}
+
+/// Densities sampled on a regular 3D grid, interpolated between the samples.
+pub struct DensityGrid {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    /// indexed with x varying fastest, then y, then z
+    values: Vec<f64>,
+    max_density: f64,
+}
+
+impl DensityGrid {
+    pub fn new(nx: usize, ny: usize, nz: usize, values: Vec<f64>) -> Self {
+        assert!(nx > 0 && ny > 0 && nz > 0, "density grid has no samples");
+        assert_eq!(values.len(), nx * ny * nz, "density grid has wrong size");
+        let max_density = values.iter().cloned().fold(0.0, f64::max);
+        return DensityGrid {
+            nx,
+            ny,
+            nz,
+            values,
+            max_density,
+        };
+    }
+
+    /// Build a grid by evaluating `f` at each sample, where the position
+    /// passed to `f` runs from 0 to 1 along each axis.
+    pub fn from_fn<F>(nx: usize, ny: usize, nz: usize, f: F) -> Self
+    where
+        F: Fn(V3) -> f64,
+    {
+        let mut values = Vec::with_capacity(nx * ny * nz);
+        for z in 0..nz {
+            for y in 0..ny {
+                for x in 0..nx {
+                    // a grid one sample thick has it at the start
+                    let p = V3::new(
+                        x as f64 / usize::max(nx - 1, 1) as f64,
+                        y as f64 / usize::max(ny - 1, 1) as f64,
+                        z as f64 / usize::max(nz - 1, 1) as f64,
+                    );
+                    values.push(f64::max(f(p), 0.0));
+                }
+            }
+        }
+        return DensityGrid::new(nx, ny, nz, values);
+    }
+
+    /// A cloud-like grid: turbulent noise, faded out towards the edges so
+    /// that the volume has no hard boundary.
+    pub fn from_noise(resolution: usize, seed: u64, frequency: f64) -> Self {
+        let perlin = Perlin::new(seed);
+        return DensityGrid::from_fn(resolution, resolution, resolution, |p| {
+            let from_centre = (p - V3::new(0.5, 0.5, 0.5)).length() * 2.0;
+            let falloff = f64::max(1.0 - from_centre * from_centre, 0.0);
+            return falloff * perlin.turbulence(frequency * p, 5) * 2.0;
+        });
+    }
+
+    /// Load a grid from a raw file: three little-endian u32s giving the
+    /// dimensions, followed by a little-endian f32 density for each sample
+    /// (x varying fastest).
+    pub fn load_raw<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message);
+        if bytes.len() < 12 {
+            return Err(invalid("density grid is missing its header"));
+        }
+        let read_u32 = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        let (nx, ny, nz) = (
+            read_u32(0) as usize,
+            read_u32(4) as usize,
+            read_u32(8) as usize,
+        );
+        if nx == 0 || ny == 0 || nz == 0 {
+            return Err(invalid("density grid has no samples"));
+        }
+        let expected_length = nx
+            .checked_mul(ny)
+            .and_then(|n| n.checked_mul(nz))
+            .and_then(|n| n.checked_mul(4))
+            .and_then(|n| n.checked_add(12))
+            .ok_or_else(|| invalid("density grid is too large"))?;
+        if bytes.len() != expected_length {
+            return Err(invalid("density grid size does not match its header"));
+        }
+        let values = bytes[12..]
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes(c.try_into().unwrap()) as f64)
+            .collect();
+        return Ok(DensityGrid::new(nx, ny, nz, values));
+    }
+
+    fn value(&self, x: usize, y: usize, z: usize) -> f64 {
+        return self.values[x + self.nx * (y + self.ny * z)];
+    }
+
+    /// The density at `p`, which runs from 0 to 1 along each axis of the grid.
+    pub fn density(&self, p: V3) -> f64 {
+        let cell = |p: f64, n: usize| {
+            let x = f64::clamp(p, 0.0, 1.0) * (n - 1) as f64;
+            let i = usize::min(x.floor() as usize, n.saturating_sub(2));
+            return (i, x - i as f64);
+        };
+        let (x, fx) = cell(p.x, self.nx);
+        let (y, fy) = cell(p.y, self.ny);
+        let (z, fz) = cell(p.z, self.nz);
+        let lerp = |a: f64, b: f64, t: f64| a + t * (b - a);
+        let x1 = usize::min(x + 1, self.nx - 1);
+        let y1 = usize::min(y + 1, self.ny - 1);
+        let z1 = usize::min(z + 1, self.nz - 1);
+        let c00 = lerp(self.value(x, y, z), self.value(x1, y, z), fx);
+        let c10 = lerp(self.value(x, y1, z), self.value(x1, y1, z), fx);
+        let c01 = lerp(self.value(x, y, z1), self.value(x1, y, z1), fx);
+        let c11 = lerp(self.value(x, y1, z1), self.value(x1, y1, z1), fx);
+        return lerp(lerp(c00, c10, fy), lerp(c01, c11, fy), fz);
+    }
+}
+
+/// A volume whose density varies through space, such as a cloud, filling a
+/// box. Rays are tracked through it using delta tracking against the
+/// maximum density.
+pub struct GridMedium {
+    bounds: AABB,
+    grid: DensityGrid,
+    /// extinction coefficient (absorption + scattering) at a density of one
+    sigma_t: f64,
+    phase_function: Arc<dyn Material + Send + Sync>,
+}
+
+impl GridMedium {
+    /// `sigma_a` and `sigma_s` are the absorption and scattering coefficients
+    /// at a density of one, and `g` the anisotropy of the phase function.
+    pub fn new(
+        bounds: AABB,
+        grid: DensityGrid,
+        sigma_a: f64,
+        sigma_s: f64,
+        colour: Colour,
+        g: f64,
+    ) -> Self {
+        let sigma_t = sigma_a + sigma_s;
+        // absorption is accounted for by darkening every scattering event
+        // rather than by terminating paths
+        let albedo = (sigma_s / sigma_t) * colour;
+        return GridMedium {
+            bounds,
+            grid,
+            sigma_t,
+            phase_function: Arc::new(HenyeyGreenstein::new(albedo, g)),
+        };
+    }
+
+    fn density_at(&self, p: V3) -> f64 {
+        let size = self.bounds.maximum - self.bounds.minimum;
+        let local = p - self.bounds.minimum;
+        return self.grid.density(V3::new(
+            local.x / size.x,
+            local.y / size.y,
+            local.z / size.z,
+        ));
+    }
+
+    fn majorant(&self) -> f64 {
+        return self.sigma_t * self.grid.max_density;
+    }
+}
+
+impl Hittable for GridMedium {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t_enter, t_exit) = self.bounds.hit_interval(ray, f64::max(t_min, 0.0), t_max)?;
+        let majorant = self.majorant();
+        if majorant <= 0.0 {
+            return None;
+        }
+
+        // delta tracking: take exponential steps as if the medium were at its
+        // densest everywhere, and accept each as a real collision with
+        // probability proportional to the actual density there
+        let step = 1.0 / (majorant * ray.direction.length());
+        let mut t = t_enter;
+        loop {
+            t -= f64::ln(1.0 - rand::random::<f64>()) * step;
+            if t >= t_exit {
+                return None;
+            }
+            let point = ray.at(t);
+            let sigma_t = self.sigma_t * self.density_at(point);
+            if rand::random::<f64>() < sigma_t / majorant {
+                let hit_record = HitRecord::new(
+                    t,
+                    0.0,
+                    0.0,
+                    point,
+                    V3::new(1.0, 0.0, 0.0),
+                    ray.direction,
+                    self.phase_function.clone(),
+                );
+                return Some(hit_record);
+            }
+        }
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        return Some(self.bounds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hittable::Sphere, material::Lambertian};
+
+    fn unit_sphere() -> Arc<dyn Hittable + Send + Sync> {
+        let material = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+        return Arc::new(Sphere::new(V3::new(0.0, 0.0, 0.0), 1.0, material));
+    }
+
+    #[test]
+    fn constant_medium_scatters_inside_its_boundary() {
+        let medium = ConstantMedium::new(unit_sphere(), 1.0, Colour::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(V3::new(-5.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.0);
+        for _ in 0..100 {
+            if let Some(hit) = medium.hit(&ray, 0.001, f64::INFINITY) {
+                assert!(hit.time >= 4.0 && hit.time <= 6.0);
+            }
+        }
+    }
+
+    #[test]
+    fn constant_medium_scatters_with_the_chance_its_density_gives() {
+        // over a distance of 2 at density 0.5, 1 - e^-1 of rays scatter
+        let medium = ConstantMedium::new(unit_sphere(), 0.5, Colour::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(V3::new(-5.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.0);
+        let n = 20000;
+        let hits = (0..n)
+            .filter(|_| medium.hit(&ray, 0.001, f64::INFINITY).is_some())
+            .count();
+        let expected = 1.0 - f64::exp(-1.0);
+        assert!(f64::abs(hits as f64 / n as f64 - expected) < 0.02);
+    }
+
+    #[test]
+    fn constant_medium_missed_outside_its_boundary() {
+        let medium = ConstantMedium::new(unit_sphere(), 100.0, Colour::new(1.0, 1.0, 1.0));
+        let ray = Ray::new(V3::new(-5.0, 2.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(medium.hit(&ray, 0.001, f64::INFINITY).is_none());
+        assert!(medium.bounding_box(0.0, 1.0).is_some());
+    }
+
+    #[test]
+    fn density_grid_interpolates_between_samples() {
+        let grid = DensityGrid::from_fn(3, 3, 3, |p| p.x);
+        assert!(f64::abs(grid.density(V3::new(0.25, 0.5, 0.5)) - 0.25) < 1e-9);
+        assert!(f64::abs(grid.density(V3::new(2.0, 0.5, 0.5)) - 1.0) < 1e-9);
+    }
+
+    #[test]
+    fn density_grid_one_sample_thick_has_no_nans() {
+        let grid = DensityGrid::from_fn(4, 1, 4, |p| p.x + p.y);
+        for y in [0.0, 0.5, 1.0] {
+            let density = grid.density(V3::new(0.5, y, 0.5));
+            assert!(f64::abs(density - 0.5) < 1e-9);
+        }
+    }
+
+    fn write_raw(name: &str, header: [u32; 3], values: &[f32]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let mut bytes = vec![];
+        for n in header {
+            bytes.extend_from_slice(&n.to_le_bytes());
+        }
+        for value in values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        fs::write(&path, bytes).unwrap();
+        return path;
+    }
+
+    #[test]
+    fn density_grid_loaded_from_raw_file() {
+        let path = write_raw("rt_grid_ok.raw", [2, 1, 1], &[0.0, 2.0]);
+        let grid = DensityGrid::load_raw(&path).unwrap();
+        assert!(f64::abs(grid.density(V3::new(0.5, 0.0, 0.0)) - 1.0) < 1e-9);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn density_grid_with_overflowing_header_is_invalid() {
+        let path = write_raw("rt_grid_overflow.raw", [u32::MAX; 3], &[0.0]);
+        let error = DensityGrid::load_raw(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn density_grid_without_samples_is_invalid() {
+        let path = write_raw("rt_grid_empty.raw", [0, 4, 4], &[]);
+        let error = DensityGrid::load_raw(&path).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "density grid has no samples")]
+    fn empty_density_grid_is_rejected() {
+        DensityGrid::new(2, 0, 2, vec![]);
+    }
+
+    #[test]
+    fn grid_medium_only_scatters_inside_its_box() {
+        let bounds = AABB::new(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 1.0, 1.0));
+        let grid = DensityGrid::from_fn(2, 2, 2, |_| 50.0);
+        let medium = GridMedium::new(bounds, grid, 0.0, 1.0, Colour::new(1.0, 1.0, 1.0), 0.0);
+        let through = Ray::new(V3::new(-5.0, 0.5, 0.5), V3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = medium.hit(&through, 0.001, f64::INFINITY).unwrap();
+        assert!(hit.time >= 5.0 && hit.time <= 6.0);
+        let beside = Ray::new(V3::new(-5.0, 2.0, 0.5), V3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(medium.hit(&beside, 0.001, f64::INFINITY).is_none());
+    }
+}
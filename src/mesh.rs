@@ -0,0 +1,432 @@
+use std::{collections::HashMap, fs, io, mem::size_of, path::Path, sync::Arc, time::Instant};
+
+use crate::{
+    aabb::AABB,
+    bvh::BVHNode,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    texture::Texture,
+    triangle::{hit_triangle, triangle_uv_derivatives},
+    v3::{unit_vector, V3},
+};
+
+/// A triangle mesh, as loaded or generated, before it is turned into
+/// hittables. Each vertex has a position and a uv.
+#[derive(Clone)]
+pub struct Mesh {
+    positions: Vec<V3>,
+    uvs: Vec<(f64, f64)>,
+    faces: Vec<[usize; 3]>,
+}
+
+impl Mesh {
+    pub fn new(positions: Vec<V3>, uvs: Vec<(f64, f64)>, faces: Vec<[usize; 3]>) -> Self {
+        assert_eq!(positions.len(), uvs.len(), "every vertex needs a uv");
+        return Mesh {
+            positions,
+            uvs,
+            faces,
+        };
+    }
+
+    /// Load the vertices (`v`), texture coordinates (`vt`) and faces (`f`)
+    /// of a Wavefront OBJ file. Polygons are split into fans of triangles.
+    /// Each vertex takes the first texture coordinate it is used with.
+    pub fn load_obj<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let invalid = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid OBJ line: {line}"),
+            )
+        };
+
+        let mut positions = vec![];
+        let mut texture_coordinates = vec![];
+        let mut uvs: Vec<Option<(f64, f64)>> = vec![];
+        let mut faces = vec![];
+        for line in text.lines() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("v") => {
+                    let coordinates: Vec<f64> = parts
+                        .take(3)
+                        .map(|p| p.parse().map_err(|_| invalid(line)))
+                        .collect::<io::Result<_>>()?;
+                    if coordinates.len() != 3 {
+                        return Err(invalid(line));
+                    }
+                    positions.push(V3::new(coordinates[0], coordinates[1], coordinates[2]));
+                    uvs.push(None);
+                }
+                Some("vt") => {
+                    let coordinates: Vec<f64> = parts
+                        .take(2)
+                        .map(|p| p.parse().map_err(|_| invalid(line)))
+                        .collect::<io::Result<_>>()?;
+                    let u = coordinates.first().cloned().unwrap_or(0.0);
+                    let v = coordinates.get(1).cloned().unwrap_or(0.0);
+                    texture_coordinates.push((u, v));
+                }
+                Some("f") => {
+                    // each corner is v, v/vt, v//vn or v/vt/vn, counting
+                    // from 1 (or from the end, if negative)
+                    let resolve = |index: &str, count: usize| -> io::Result<usize> {
+                        let index: i64 = index.parse().map_err(|_| invalid(line))?;
+                        let resolved = if index < 0 {
+                            count as i64 + index
+                        } else {
+                            index - 1
+                        };
+                        if resolved < 0 || resolved >= count as i64 {
+                            return Err(invalid(line));
+                        }
+                        return Ok(resolved as usize);
+                    };
+                    let mut corners = vec![];
+                    for corner in parts {
+                        let mut indices = corner.split('/');
+                        let vertex = resolve(indices.next().unwrap_or(""), positions.len())?;
+                        if let Some(uv) = indices.next().filter(|uv| !uv.is_empty()) {
+                            let uv = resolve(uv, texture_coordinates.len())?;
+                            uvs[vertex].get_or_insert(texture_coordinates[uv]);
+                        }
+                        corners.push(vertex);
+                    }
+                    if corners.len() < 3 {
+                        return Err(invalid(line));
+                    }
+                    for i in 1..corners.len() - 1 {
+                        faces.push([corners[0], corners[i], corners[i + 1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        let uvs = uvs.into_iter().map(|uv| uv.unwrap_or((0.0, 0.0))).collect();
+        return Ok(Mesh::new(positions, uvs, faces));
+    }
+
+    /// Smooth the mesh by applying `levels` rounds of Loop subdivision, each
+    /// of which splits every triangle into four.
+    pub fn subdivide(&self, levels: u32) -> Mesh {
+        let start = Instant::now();
+        let mut mesh = self.clone();
+        for _ in 0..levels {
+            mesh = mesh.loop_subdivide();
+        }
+        eprintln!(
+            "Subdivided {} triangles into {} in {:.1?}",
+            self.faces.len(),
+            mesh.faces.len(),
+            start.elapsed()
+        );
+        return mesh;
+    }
+
+    fn loop_subdivide(&self) -> Mesh {
+        // for each edge, the vertices opposite it in the faces either side
+        let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for &[a, b, c] in &self.faces {
+            for (from, to, opposite) in [(a, b, c), (b, c, a), (c, a, b)] {
+                edges.entry(edge_key(from, to)).or_default().push(opposite);
+            }
+        }
+
+        // every vertex's neighbours, and the neighbours along the boundary
+        // for vertices on it
+        let mut neighbours: Vec<Vec<usize>> = vec![vec![]; self.positions.len()];
+        let mut boundary_neighbours: Vec<Vec<usize>> = vec![vec![]; self.positions.len()];
+        for (&(a, b), opposites) in &edges {
+            neighbours[a].push(b);
+            neighbours[b].push(a);
+            if opposites.len() == 1 {
+                boundary_neighbours[a].push(b);
+                boundary_neighbours[b].push(a);
+            }
+        }
+
+        // move the existing ("even") vertices
+        let mut positions: Vec<V3> = (0..self.positions.len())
+            .map(|i| {
+                let p = self.positions[i];
+                if boundary_neighbours[i].len() == 2 {
+                    let [a, b] = [boundary_neighbours[i][0], boundary_neighbours[i][1]];
+                    return 0.75 * p + 0.125 * (self.positions[a] + self.positions[b]);
+                }
+                if !boundary_neighbours[i].is_empty() || neighbours[i].is_empty() {
+                    // corners and other irregular boundaries stay put
+                    return p;
+                }
+                let n = neighbours[i].len() as f64;
+                let inner = 0.375 + 0.25 * f64::cos(2.0 * std::f64::consts::PI / n);
+                let beta = (0.625 - inner * inner) / n;
+                let sum = neighbours[i]
+                    .iter()
+                    .fold(V3::new(0.0, 0.0, 0.0), |sum, &j| sum + self.positions[j]);
+                return (1.0 - n * beta) * p + beta * sum;
+            })
+            .collect();
+        let mut uvs = self.uvs.clone();
+
+        // add a new ("odd") vertex on every edge
+        let mut edge_vertices: HashMap<(usize, usize), usize> = HashMap::new();
+        for (&(a, b), opposites) in &edges {
+            let (pa, pb) = (self.positions[a], self.positions[b]);
+            let position = match opposites.as_slice() {
+                [c, d] => 0.375 * (pa + pb) + 0.125 * (self.positions[*c] + self.positions[*d]),
+                _ => 0.5 * (pa + pb),
+            };
+            let ((ua, va), (ub, vb)) = (self.uvs[a], self.uvs[b]);
+            edge_vertices.insert((a, b), positions.len());
+            positions.push(position);
+            uvs.push((0.5 * (ua + ub), 0.5 * (va + vb)));
+        }
+
+        let mut faces = Vec::with_capacity(self.faces.len() * 4);
+        for &[a, b, c] in &self.faces {
+            let ab = edge_vertices[&edge_key(a, b)];
+            let bc = edge_vertices[&edge_key(b, c)];
+            let ca = edge_vertices[&edge_key(c, a)];
+            faces.push([a, ab, ca]);
+            faces.push([ab, b, bc]);
+            faces.push([ca, bc, c]);
+            faces.push([ab, bc, ca]);
+        }
+        return Mesh::new(positions, uvs, faces);
+    }
+
+    /// Area weighted normals at each vertex.
+    fn vertex_normals(&self) -> Vec<V3> {
+        let mut normals = vec![V3::new(0.0, 0.0, 0.0); self.positions.len()];
+        for &[a, b, c] in &self.faces {
+            let (pa, pb, pc) = (self.positions[a], self.positions[b], self.positions[c]);
+            // the cross product's length is twice the area, giving the weighting
+            let face_normal = V3::cross(pb - pa, pc - pa);
+            for i in [a, b, c] {
+                normals[i] = normals[i] + face_normal;
+            }
+        }
+        return normals
+            .into_iter()
+            .map(|n| {
+                if n.length_squared() > 0.0 {
+                    unit_vector(n)
+                } else {
+                    n
+                }
+            })
+            .collect();
+    }
+
+    /// Move every vertex along its normal by `scale` times the brightness of
+    /// the texture there.
+    pub fn displace<T: Texture + ?Sized>(&self, texture: &T, scale: f64) -> Mesh {
+        let normals = self.vertex_normals();
+        let positions = self
+            .positions
+            .iter()
+            .zip(&normals)
+            .zip(&self.uvs)
+            .map(|((&p, &n), &(u, v))| {
+                let colour = texture.colour(u, v, p);
+                let height = (colour.red + colour.green + colour.blue) / 3.0;
+                return p + (scale * height) * n;
+            })
+            .collect();
+        return Mesh::new(positions, self.uvs.clone(), self.faces.clone());
+    }
+
+    /// Turn the mesh into a BVH of triangles with smoothly interpolated
+    /// normals, all sharing the one copy of the vertex data.
+    pub fn build(self, material: Arc<dyn Material + Send + Sync>) -> BVHNode {
+        let start = Instant::now();
+        let normals = self.vertex_normals();
+        let data = Arc::new(MeshData {
+            positions: self.positions,
+            normals,
+            uvs: self.uvs,
+            faces: self.faces,
+            material,
+        });
+        let triangles: Vec<Arc<dyn Hittable + Send + Sync>> = (0..data.faces.len())
+            .map(|face| {
+                Arc::new(MeshTriangle {
+                    mesh: data.clone(),
+                    face,
+                }) as Arc<dyn Hittable + Send + Sync>
+            })
+            .collect();
+        let triangle_count = triangles.len();
+        let bvh = BVHNode::new(triangles, 0.0, 1.0);
+
+        // roughly: the vertex data, a handle per triangle, and a node (two
+        // pointers and a box) for every triangle in the tree
+        let vertex_bytes = data.positions.len() * (2 * size_of::<V3>() + size_of::<(f64, f64)>())
+            + data.faces.len() * size_of::<[usize; 3]>();
+        let triangle_bytes = triangle_count * (size_of::<MeshTriangle>() + size_of::<usize>() * 2);
+        let bvh_bytes = triangle_count * size_of::<BVHNode>();
+        eprintln!(
+            "Built mesh of {} triangles in {:.1?}, using about {:.1} MB",
+            triangle_count,
+            start.elapsed(),
+            (vertex_bytes + triangle_bytes + bvh_bytes) as f64 / (1024.0 * 1024.0)
+        );
+        return bvh;
+    }
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    return if a < b { (a, b) } else { (b, a) };
+}
+
+/// The vertex data of a built mesh, shared between its triangles.
+struct MeshData {
+    positions: Vec<V3>,
+    normals: Vec<V3>,
+    uvs: Vec<(f64, f64)>,
+    faces: Vec<[usize; 3]>,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+/// One triangle of a built mesh.
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    face: usize,
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let [a, b, c] = self.mesh.faces[self.face];
+        let positions = &self.mesh.positions;
+        let (t, b1, b2) =
+            hit_triangle(ray, positions[a], positions[b], positions[c], t_min, t_max)?;
+        let b0 = 1.0 - b1 - b2;
+
+        // shade with the vertex normals blended across the face
+        let face_normal = V3::cross(positions[b] - positions[a], positions[c] - positions[a]);
+        let normals = &self.mesh.normals;
+        let mut shading_normal = b0 * normals[a] + b1 * normals[b] + b2 * normals[c];
+        if shading_normal.length_squared() == 0.0 {
+            shading_normal = face_normal;
+        }
+        let uvs = &self.mesh.uvs;
+        let u = b0 * uvs[a].0 + b1 * uvs[b].0 + b2 * uvs[c].0;
+        let v = b0 * uvs[a].1 + b1 * uvs[b].1 + b2 * uvs[c].1;
+        let (dpdu, dpdv) = triangle_uv_derivatives(
+            [positions[a], positions[b], positions[c]],
+            [uvs[a], uvs[b], uvs[c]],
+        );
+        let hit_record = HitRecord::new(
+            t,
+            u,
+            v,
+            ray.at(t),
+            unit_vector(face_normal),
+            ray.direction,
+            self.mesh.material.clone(),
+        )
+        .with_uv_derivatives(dpdu, dpdv)
+        .with_shading_normal(shading_normal);
+        if hit_record.is_cut_out() {
+            return None;
+        }
+        return Some(hit_record);
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        let [a, b, c] = self.mesh.faces[self.face];
+        let positions = &self.mesh.positions;
+        let padding = V3::new(0.0001, 0.0001, 0.0001);
+        let b = AABB::new(positions[a] - padding, positions[a] + padding)
+            + AABB::new(positions[b], positions[b])
+            + AABB::new(positions[c], positions[c]);
+        return Some(b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{colour::Colour, material::Lambertian, texture::SolidColour};
+
+    fn octahedron() -> Mesh {
+        let positions = vec![
+            V3::new(1.0, 0.0, 0.0),
+            V3::new(-1.0, 0.0, 0.0),
+            V3::new(0.0, 1.0, 0.0),
+            V3::new(0.0, -1.0, 0.0),
+            V3::new(0.0, 0.0, 1.0),
+            V3::new(0.0, 0.0, -1.0),
+        ];
+        let faces = vec![
+            [0, 2, 4],
+            [4, 2, 1],
+            [1, 2, 5],
+            [5, 2, 0],
+            [4, 3, 0],
+            [1, 3, 4],
+            [5, 3, 1],
+            [0, 3, 5],
+        ];
+        return Mesh::new(positions, vec![(0.0, 0.0); 6], faces);
+    }
+
+    #[test]
+    fn obj_polygons_are_split_into_fans() {
+        let path = std::env::temp_dir().join("rt_quad.obj");
+        let obj = "# a unit quad\n\
+            v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+            vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+            f 1/1 2/2 3/3 -1/-1\n";
+        fs::write(&path, obj).unwrap();
+        let mesh = Mesh::load_obj(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(mesh.faces, vec![[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(mesh.uvs[2], (1.0, 1.0));
+    }
+
+    #[test]
+    fn obj_with_bad_index_is_invalid() {
+        let path = std::env::temp_dir().join("rt_bad.obj");
+        fs::write(&path, "v 0 0 0\nv 1 0 0\nf 1 2 3\n").unwrap();
+        let error = Mesh::load_obj(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn loop_subdivision_splits_faces_and_smooths_inwards() {
+        let mesh = octahedron().subdivide(2);
+        assert_eq!(mesh.faces.len(), 8 * 16);
+        // a closed mesh with 12 edges gains one vertex per edge per level
+        assert_eq!(octahedron().subdivide(1).positions.len(), 6 + 12);
+        // every vertex stays within the original octahedron, and the
+        // corners are pulled in
+        for p in &mesh.positions {
+            let l1 = f64::abs(p.x) + f64::abs(p.y) + f64::abs(p.z);
+            assert!(l1 < 1.0 + 1e-9, "vertex outside the octahedron");
+        }
+        assert!(mesh.positions[0].x < 0.9);
+    }
+
+    #[test]
+    fn displacement_moves_vertices_along_their_normals() {
+        let white = SolidColour::new(Colour::new(1.0, 1.0, 1.0));
+        let mesh = octahedron().displace(&white, 0.5);
+        assert!((mesh.positions[2] - V3::new(0.0, 1.5, 0.0)).length() < 1e-9);
+    }
+
+    #[test]
+    fn built_mesh_is_hit_with_interpolated_normals() {
+        let material = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+        let bvh = octahedron().build(material);
+        let ray = Ray::new(V3::new(0.0, 5.0, 0.0), V3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = bvh.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 4.0) < 1e-9);
+        // right on the top vertex, the shading normal is that vertex's
+        assert!((hit.shading_normal - V3::new(0.0, 1.0, 0.0)).length() < 1e-9);
+        assert!(hit.front_face);
+    }
+}
@@ -0,0 +1,754 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    colour::Colour,
+    hittable::HitRecord,
+    material::{reflectance, refract, BsdfSample, Material},
+    ray::Ray,
+    texture::{Multiply, SolidColour, Texture},
+    v3::{orthonormal_basis, reflect, unit_vector, V3},
+};
+
+/// The smallest GGX alpha used, as perfectly smooth surfaces make the
+/// distribution infinitely sharp.
+pub const MIN_ALPHA: f64 = 1e-3;
+
+/// Axes around a normal, for working with directions in a local space where
+/// the normal is z.
+#[derive(Clone, Copy)]
+pub struct Frame {
+    s: V3,
+    t: V3,
+    n: V3,
+}
+
+impl Frame {
+    pub fn new(normal: V3) -> Self {
+        let n = unit_vector(normal);
+        let (s, t) = orthonormal_basis(n);
+        return Frame { s, t, n };
+    }
+
+    pub fn to_local(self, v: V3) -> V3 {
+        return V3::new(V3::dot(v, self.s), V3::dot(v, self.t), V3::dot(v, self.n));
+    }
+
+    pub fn to_world(self, v: V3) -> V3 {
+        return v.x * self.s + v.y * self.t + v.z * self.n;
+    }
+}
+
+/// The GGX (Trowbridge-Reitz) density of microfacets facing along the local
+/// direction `m`.
+pub fn ggx_d(m: V3, alpha: f64) -> f64 {
+    if m.z <= 0.0 {
+        return 0.0;
+    }
+    let alpha_squared = alpha * alpha;
+    let denominator = m.z * m.z * (alpha_squared - 1.0) + 1.0;
+    return alpha_squared / (PI * denominator * denominator);
+}
+
+/// Smith's Λ for GGX, the shadowed area of the microfacets seen from the
+/// local direction `w`.
+fn ggx_lambda(w: V3, alpha: f64) -> f64 {
+    let cos_squared = w.z * w.z;
+    if cos_squared <= 0.0 {
+        return f64::INFINITY;
+    }
+    let tan_squared = f64::max(1.0 - cos_squared, 0.0) / cos_squared;
+    return 0.5 * (f64::sqrt(1.0 + alpha * alpha * tan_squared) - 1.0);
+}
+
+/// The proportion of microfacets visible from the local direction `w`.
+pub fn ggx_g1(w: V3, alpha: f64) -> f64 {
+    return 1.0 / (1.0 + ggx_lambda(w, alpha));
+}
+
+/// The proportion of microfacets visible from both `wo` and `wi`, allowing
+/// for those hidden from one also being likely to be hidden from the other.
+pub fn ggx_g2(wo: V3, wi: V3, alpha: f64) -> f64 {
+    return 1.0 / (1.0 + ggx_lambda(wo, alpha) + ggx_lambda(wi, alpha));
+}
+
+/// Pick a microfacet normal in proportion to how much of it is seen from the
+/// local direction `wo`, which must be above the surface (Heitz, "Sampling
+/// the GGX Distribution of Visible Normals").
+pub fn sample_ggx_visible_normal(wo: V3, alpha: f64) -> V3 {
+    // stretch the view so the microfacets form a hemisphere
+    let view = unit_vector(V3::new(alpha * wo.x, alpha * wo.y, wo.z));
+    let length_squared = view.x * view.x + view.y * view.y;
+    let t_1 = if length_squared > 0.0 {
+        V3::new(-view.y, view.x, 0.0) / f64::sqrt(length_squared)
+    } else {
+        V3::new(1.0, 0.0, 0.0)
+    };
+    let t_2 = V3::cross(view, t_1);
+
+    // a point on the projected disk, squashed onto the visible half of it
+    let r = f64::sqrt(rand::random::<f64>());
+    let phi = 2.0 * PI * rand::random::<f64>();
+    let p_1 = r * f64::cos(phi);
+    let s = 0.5 * (1.0 + view.z);
+    let p_2 = (1.0 - s) * f64::sqrt(1.0 - p_1 * p_1) + s * r * f64::sin(phi);
+    let p_3 = f64::sqrt(f64::max(0.0, 1.0 - p_1 * p_1 - p_2 * p_2));
+    let normal = p_1 * t_1 + p_2 * t_2 + p_3 * view;
+
+    // and unstretch
+    return unit_vector(V3::new(
+        alpha * normal.x,
+        alpha * normal.y,
+        f64::max(normal.z, 1e-6),
+    ));
+}
+
+/// The density of `wi` when it is `wo` reflected about a normal picked by
+/// `sample_ggx_visible_normal`, where `m` is the half vector between them.
+pub fn ggx_reflection_pdf(wo: V3, m: V3, alpha: f64) -> f64 {
+    if wo.z <= 0.0 {
+        return 0.0;
+    }
+    return ggx_g1(wo, alpha) * ggx_d(m, alpha) / (4.0 * wo.z);
+}
+
+/// Schlick's approximation of the reflectance at an angle with the given
+/// cosine, for a surface reflecting `f0` head on.
+pub fn fresnel_schlick(f0: Colour, cosine: f64) -> Colour {
+    let weight = f64::powi(1.0 - f64::clamp(cosine, 0.0, 1.0), 5);
+    return (1.0 - weight) * f0 + weight * Colour::new(1.0, 1.0, 1.0);
+}
+
+/// A random local direction above the surface, more likely nearer the
+/// normal in proportion to the cosine of the angle to it.
+pub fn random_cosine_direction() -> V3 {
+    let r = f64::sqrt(rand::random::<f64>());
+    let phi = 2.0 * PI * rand::random::<f64>();
+    let z = f64::sqrt(f64::max(0.0, 1.0 - r * r));
+    return V3::new(r * f64::cos(phi), r * f64::sin(phi), z);
+}
+
+fn average(c: Colour) -> f64 {
+    return (c.red + c.green + c.blue) / 3.0;
+}
+
+/// A physically based material in the metal-roughness style used by glTF: a
+/// diffuse base under a GGX specular layer, blending to a pure GGX metal
+/// tinted by the base colour as `metallic` goes from 0 to 1.
+pub struct Principled {
+    base_colour: Arc<dyn Texture + Send + Sync>,
+    /// Roughness in green and metallic in blue, as glTF packs them
+    metallic_roughness: Arc<dyn Texture + Send + Sync>,
+    /// How reflective the non-metal surface is, where 0.5 reflects 4% head
+    /// on like most plastics
+    specular: f64,
+}
+
+impl Principled {
+    pub fn new(base_colour: Colour, metallic: f64, roughness: f64, specular: f64) -> Self {
+        return Principled::new_from_textures(
+            SolidColour::new(base_colour),
+            SolidColour::new(Colour::new(0.0, roughness, metallic)),
+            specular,
+        );
+    }
+
+    /// Use textures for the base colour and, in glTF's layout, the metallic
+    /// and roughness.
+    pub fn new_from_textures<
+        B: Texture + Send + Sync + 'static,
+        M: Texture + Send + Sync + 'static,
+    >(
+        base_colour: B,
+        metallic_roughness: M,
+        specular: f64,
+    ) -> Self {
+        return Principled {
+            base_colour: Arc::new(base_colour),
+            metallic_roughness: Arc::new(metallic_roughness),
+            specular,
+        };
+    }
+
+    /// A glTF metal-roughness material, where each texture is scaled by its
+    /// factor. The base colour texture should be loaded as sRGB and the
+    /// metallic-roughness texture as linear data.
+    pub fn new_gltf<B: Texture + Send + Sync + 'static, M: Texture + Send + Sync + 'static>(
+        base_colour_factor: Colour,
+        base_colour: B,
+        metallic_factor: f64,
+        roughness_factor: f64,
+        metallic_roughness: M,
+    ) -> Self {
+        return Principled::new_from_textures(
+            Multiply::new(base_colour, SolidColour::new(base_colour_factor)),
+            Multiply::new(
+                metallic_roughness,
+                SolidColour::new(Colour::new(1.0, roughness_factor, metallic_factor)),
+            ),
+            0.5,
+        );
+    }
+}
+
+/// What the lobes of a `Principled` surface look like at a hit, seen along
+/// the incoming ray.
+struct PrincipledLobes {
+    frame: Frame,
+    /// The direction back along the ray, in `frame`
+    wo: V3,
+    alpha: f64,
+    f0: Colour,
+    diffuse_colour: Colour,
+    /// The chance of sampling the specular lobe rather than the diffuse one
+    specular_probability: f64,
+}
+
+impl Principled {
+    fn lobes(&self, ray_in: &Ray, hit_record: &HitRecord) -> PrincipledLobes {
+        let (u, v, p) = (hit_record.u, hit_record.v, hit_record.point);
+        let footprint = hit_record.footprint(ray_in);
+        let sample = |texture: &Arc<dyn Texture + Send + Sync>| match &footprint {
+            Some(footprint) => texture.colour_filtered(u, v, p, footprint),
+            None => texture.colour(u, v, p),
+        };
+        let base_colour = sample(&self.base_colour);
+        let metallic_roughness = sample(&self.metallic_roughness);
+        let metallic = f64::clamp(metallic_roughness.blue, 0.0, 1.0);
+        let roughness = f64::clamp(metallic_roughness.green, 0.0, 1.0);
+        let alpha = f64::max(roughness * roughness, MIN_ALPHA);
+
+        let frame = Frame::new(hit_record.shading_normal);
+        let wo = frame.to_local(-unit_vector(ray_in.direction));
+        // a tilted shading normal can face away from the ray; nudge it back
+        let wo = unit_vector(V3::new(wo.x, wo.y, f64::max(wo.z, 1e-4)));
+
+        let dielectric_f0 = 0.08 * self.specular;
+        let f0 = (1.0 - metallic) * Colour::new(dielectric_f0, dielectric_f0, dielectric_f0)
+            + metallic * base_colour;
+        let diffuse_colour = (1.0 - metallic) * base_colour;
+
+        // choose a lobe in rough proportion to how much light each returns
+        let specular_weight = average(fresnel_schlick(f0, wo.z));
+        let diffuse_weight = average(diffuse_colour) * (1.0 - specular_weight);
+        let specular_probability = if specular_weight + diffuse_weight > 0.0 {
+            specular_weight / (specular_weight + diffuse_weight)
+        } else {
+            1.0
+        };
+        return PrincipledLobes {
+            frame,
+            wo,
+            alpha,
+            f0,
+            diffuse_colour,
+            specular_probability,
+        };
+    }
+
+    /// The pdf of sampling the world direction `wi`, and the BSDF times
+    /// the cosine for it.
+    fn evaluate(lobes: &PrincipledLobes, hit_record: &HitRecord, wi: V3) -> (f64, Colour) {
+        let (wo, alpha) = (lobes.wo, lobes.alpha);
+        let local = lobes.frame.to_local(wi);
+        if local.z <= 0.0 || V3::dot(wi, hit_record.normal) <= 0.0 {
+            return (0.0, Colour::new(0.0, 0.0, 0.0));
+        }
+        let m = unit_vector(wo + local);
+        let fresnel = fresnel_schlick(lobes.f0, V3::dot(wo, m));
+        let specular =
+            (ggx_d(m, alpha) * ggx_g2(wo, local, alpha) / (4.0 * wo.z * local.z)) * fresnel;
+        // the diffuse base only gets the light the specular layer didn't
+        // reflect, judged from the view so the two never add up to more than
+        // came in
+        let reflected = fresnel_schlick(lobes.f0, wo.z);
+        let transmitted = Colour::new(
+            1.0 - reflected.red,
+            1.0 - reflected.green,
+            1.0 - reflected.blue,
+        );
+        let diffuse = (1.0 / PI) * transmitted * lobes.diffuse_colour;
+        let pdf = lobes.specular_probability * ggx_reflection_pdf(wo, m, alpha)
+            + (1.0 - lobes.specular_probability) * local.z / PI;
+        return (pdf, local.z * (specular + diffuse));
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        let sample = self.sample(ray_in, hit_record)?;
+        return Some(sample.scattered(ray_in));
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let lobes = self.lobes(ray_in, hit_record);
+        let wi = if rand::random::<f64>() < lobes.specular_probability {
+            reflect(-lobes.wo, sample_ggx_visible_normal(lobes.wo, lobes.alpha))
+        } else {
+            random_cosine_direction()
+        };
+        let direction = lobes.frame.to_world(wi);
+
+        // weigh wi by the chance of either lobe picking it
+        let (pdf, value) = Principled::evaluate(&lobes, hit_record, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+        return Some(BsdfSample {
+            origin: hit_record.point,
+            direction,
+            weight: value / pdf,
+            pdf,
+            is_delta: false,
+        });
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> Colour {
+        let lobes = self.lobes(ray_in, hit_record);
+        return Principled::evaluate(&lobes, hit_record, wi).1;
+    }
+
+    fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> f64 {
+        let lobes = self.lobes(ray_in, hit_record);
+        return Principled::evaluate(&lobes, hit_record, wi).0;
+    }
+}
+
+/// Glass with a GGX rough surface, which can also absorb light as it passes
+/// through. Solid glass tints light by how far it travels inside, measured
+/// between hits on the inside of its surface, so the object should be closed
+/// and not overlap others. Thin walled glass is instead treated as a sheet
+/// with no inside, for window panes made of a single quad.
+pub struct RoughDielectric {
+    index_of_refraction: f64,
+    roughness: f64,
+    /// The colour of white light after travelling `distance` through the
+    /// glass, or through the sheet if it is thin walled
+    colour: Colour,
+    distance: f64,
+    thin_walled: bool,
+}
+
+impl RoughDielectric {
+    pub fn new(index_of_refraction: f64, roughness: f64) -> Self {
+        return RoughDielectric::new_coloured(
+            index_of_refraction,
+            roughness,
+            Colour::new(1.0, 1.0, 1.0),
+            1.0,
+        );
+    }
+
+    /// Glass which tints white light to `colour` after it has travelled
+    /// `distance` through it.
+    pub fn new_coloured(
+        index_of_refraction: f64,
+        roughness: f64,
+        colour: Colour,
+        distance: f64,
+    ) -> Self {
+        return RoughDielectric {
+            index_of_refraction,
+            roughness,
+            colour,
+            distance,
+            thin_walled: false,
+        };
+    }
+
+    /// A thin sheet of glass which tints the light passing through it to
+    /// `colour`, and lets it out in the direction it came in.
+    pub fn new_thin_walled(index_of_refraction: f64, roughness: f64, colour: Colour) -> Self {
+        return RoughDielectric {
+            index_of_refraction,
+            roughness,
+            colour,
+            distance: 1.0,
+            thin_walled: true,
+        };
+    }
+
+    /// How much light is left after travelling `distance` through the glass
+    /// (Beer-Lambert).
+    fn transmittance(&self, distance: f64) -> Colour {
+        let proportion = distance / self.distance;
+        return Colour::new(
+            f64::powf(self.colour.red, proportion),
+            f64::powf(self.colour.green, proportion),
+            f64::powf(self.colour.blue, proportion),
+        );
+    }
+}
+
+/// The surface of a `RoughDielectric` at a hit, seen along the incoming ray.
+struct Interface {
+    frame: Frame,
+    /// The direction back along the ray, in `frame`
+    wo: V3,
+    alpha: f64,
+    /// The index of refraction on the side of `wo` over that of the other
+    refraction_ratio: f64,
+}
+
+impl RoughDielectric {
+    fn interface(&self, ray_in: &Ray, hit_record: &HitRecord) -> Interface {
+        let alpha = f64::max(self.roughness * self.roughness, MIN_ALPHA);
+        let refraction_ratio = if hit_record.front_face || self.thin_walled {
+            1.0 / self.index_of_refraction
+        } else {
+            self.index_of_refraction
+        };
+        let frame = Frame::new(hit_record.shading_normal);
+        let wo = frame.to_local(-unit_vector(ray_in.direction));
+        let wo = unit_vector(V3::new(wo.x, wo.y, f64::max(wo.z, 1e-4)));
+        return Interface {
+            frame,
+            wo,
+            alpha,
+            refraction_ratio,
+        };
+    }
+
+    /// The chance of reflecting off a microfacet facing along `m`.
+    fn reflect_probability(&self, interface: &Interface, m: V3) -> f64 {
+        let cos_theta = f64::clamp(V3::dot(interface.wo, m), 0.0, 1.0);
+        let sin_theta = f64::sqrt(1.0 - cos_theta * cos_theta);
+        if !self.thin_walled && interface.refraction_ratio * sin_theta > 1.0 {
+            return 1.0;
+        }
+        let reflected = reflectance(cos_theta, interface.refraction_ratio);
+        if self.thin_walled {
+            // light bounces back and forth inside the sheet, and some of
+            // that comes back out of the front
+            return 2.0 * reflected / (1.0 + reflected);
+        }
+        return reflected;
+    }
+
+    /// The pdf of sampling the world direction `wi`, and the BSDF times
+    /// the cosine for it.
+    fn evaluate(
+        &self,
+        interface: &Interface,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        wi: V3,
+    ) -> (f64, Colour) {
+        let (wo, alpha) = (interface.wo, interface.alpha);
+        let local = interface.frame.to_local(wi);
+        // a tilted shading normal can put wi on the wrong side of the surface
+        if local.z == 0.0 || (V3::dot(wi, hit_record.normal) > 0.0) != (local.z > 0.0) {
+            return (0.0, Colour::new(0.0, 0.0, 0.0));
+        }
+
+        let (pdf, value) = if local.z > 0.0 || self.thin_walled {
+            // reflections, and light through a sheet, which leaves as the
+            // mirror image of a reflection
+            let reflected = V3::new(local.x, local.y, f64::abs(local.z));
+            let m = unit_vector(wo + reflected);
+            let reflect_probability = self.reflect_probability(interface, m);
+            let (probability, colour) = if local.z > 0.0 {
+                (reflect_probability, Colour::new(1.0, 1.0, 1.0))
+            } else {
+                (1.0 - reflect_probability, self.colour)
+            };
+            let pdf = probability * ggx_reflection_pdf(wo, m, alpha);
+            (
+                pdf,
+                (pdf * ggx_g2(wo, local, alpha) / ggx_g1(wo, alpha)) * colour,
+            )
+        } else {
+            // the microfacet which refracts wo into wi lies between them,
+            // weighted by the indices of refraction
+            let eta = 1.0 / interface.refraction_ratio;
+            let mut m = unit_vector(-(wo + eta * local));
+            if m.z < 0.0 {
+                m = -m;
+            }
+            let (wo_m, wi_m) = (V3::dot(wo, m), V3::dot(local, m));
+            if wo_m <= 0.0 || wi_m >= 0.0 {
+                return (0.0, Colour::new(0.0, 0.0, 0.0));
+            }
+            let probability = 1.0 - self.reflect_probability(interface, m);
+            let normal_pdf = ggx_g1(wo, alpha) * wo_m * ggx_d(m, alpha) / wo.z;
+            let denominator = wo_m + eta * wi_m;
+            let jacobian = eta * eta * f64::abs(wi_m) / (denominator * denominator);
+            let pdf = probability * normal_pdf * jacobian;
+            let value = pdf * ggx_g2(wo, local, alpha) / ggx_g1(wo, alpha);
+            (pdf, Colour::new(value, value, value))
+        };
+
+        // a hit on the inside ends a stretch of travel through the glass
+        if !hit_record.front_face && !self.thin_walled {
+            let distance = hit_record.time * ray_in.direction.length();
+            return (pdf, value * self.transmittance(distance));
+        }
+        return (pdf, value);
+    }
+}
+
+impl Material for RoughDielectric {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        let sample = self.sample(ray_in, hit_record)?;
+        return Some(sample.scattered(ray_in));
+    }
+
+    fn sample(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<BsdfSample> {
+        let interface = self.interface(ray_in, hit_record);
+        let wo = interface.wo;
+        let m = sample_ggx_visible_normal(wo, interface.alpha);
+
+        // pick reflection or transmission by the Fresnel term, which then
+        // cancels out of the weight, leaving the shadowing
+        let reflected = reflect(-wo, m);
+        let reflects = self.reflect_probability(&interface, m) > rand::random::<f64>();
+        if (reflects || self.thin_walled) && reflected.z <= 0.0 {
+            return None;
+        }
+        let wi = if reflects {
+            reflected
+        } else if self.thin_walled {
+            // through the sheet and out of the other side unbent
+            V3::new(reflected.x, reflected.y, -reflected.z)
+        } else {
+            let refracted = refract(-wo, m, interface.refraction_ratio);
+            if refracted.z >= 0.0 {
+                return None;
+            }
+            refracted
+        };
+        let direction = interface.frame.to_world(wi);
+
+        let (pdf, value) = self.evaluate(&interface, ray_in, hit_record, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+        return Some(BsdfSample {
+            origin: hit_record.point,
+            direction,
+            weight: value / pdf,
+            pdf,
+            is_delta: false,
+        });
+    }
+
+    fn eval(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> Colour {
+        let interface = self.interface(ray_in, hit_record);
+        return self.evaluate(&interface, ray_in, hit_record, wi).1;
+    }
+
+    fn pdf(&self, ray_in: &Ray, hit_record: &HitRecord, wi: V3) -> f64 {
+        let interface = self.interface(ray_in, hit_record);
+        return self.evaluate(&interface, ray_in, hit_record, wi).0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::tests::{check_sampling, integrate_sphere};
+
+    fn assert_close(a: f64, b: f64, tolerance: f64) {
+        assert!(f64::abs(a - b) < tolerance, "expected {b}, got {a}");
+    }
+
+    fn hit_from(direction: V3, material: Arc<dyn Material + Send + Sync>) -> HitRecord {
+        let point = V3::new(0.0, 0.0, 0.0);
+        return HitRecord::new(
+            1.0,
+            0.5,
+            0.5,
+            point,
+            V3::new(0.0, 0.0, 1.0),
+            direction,
+            material,
+        );
+    }
+
+    #[test]
+    fn ggx_projected_area_is_one() {
+        for alpha in [0.3, 0.6, 1.0] {
+            let area = integrate_sphere(|m| ggx_d(m, alpha) * f64::max(m.z, 0.0));
+            assert_close(area, 1.0, 1e-2);
+        }
+    }
+
+    #[test]
+    fn visible_normal_reflection_pdf_integrates_to_one() {
+        let alpha = 0.5;
+        for wo in [V3::new(0.0, 0.0, 1.0), unit_vector(V3::new(0.7, 0.2, 0.5))] {
+            let total = integrate_sphere(|wi| {
+                let m = unit_vector(wo + wi);
+                if V3::dot(wo, m) <= 0.0 {
+                    return 0.0;
+                }
+                return ggx_reflection_pdf(wo, m, alpha);
+            });
+            assert_close(total, 1.0, 1e-2);
+        }
+    }
+
+    #[test]
+    fn visible_normals_face_the_viewer() {
+        let wo = unit_vector(V3::new(0.9, 0.0, 0.2));
+        for _ in 0..1000 {
+            let m = sample_ggx_visible_normal(wo, 0.8);
+            assert!(m.z > 0.0);
+            assert!(V3::dot(wo, m) > -1e-9);
+            assert_close(m.length(), 1.0, 1e-9);
+        }
+    }
+
+    #[test]
+    fn fresnel_goes_from_f0_to_white() {
+        let f0 = Colour::new(0.04, 0.5, 1.0);
+        assert_close(fresnel_schlick(f0, 1.0).red, 0.04, 1e-12);
+        assert_close(fresnel_schlick(f0, 0.0).green, 1.0, 1e-12);
+    }
+
+    #[test]
+    fn smooth_metal_is_a_mirror() {
+        let material: Arc<dyn Material + Send + Sync> =
+            Arc::new(Principled::new(Colour::new(0.9, 0.6, 0.3), 1.0, 0.0, 0.5));
+        let direction = unit_vector(V3::new(1.0, 0.0, -1.0));
+        let ray = Ray::new(V3::new(-1.0, 0.0, 1.0), direction, 0.0);
+        let hit_record = hit_from(direction, material.clone());
+        let mirrored = unit_vector(V3::new(1.0, 0.0, 1.0));
+        let mut close = 0;
+        for _ in 0..1000 {
+            let (scattered, attenuation) = material.scatter(&ray, &hit_record).unwrap();
+            if (unit_vector(scattered.direction) - mirrored).length() < 1e-2 {
+                close += 1;
+            }
+            assert!(attenuation.red > attenuation.blue);
+        }
+        // GGX has long tails, so even a very smooth surface spreads a few
+        // rays widely
+        assert!(close > 900, "only {close} mirrored");
+    }
+
+    #[test]
+    fn principled_does_not_add_energy() {
+        let direction = unit_vector(V3::new(0.3, 0.0, -1.0));
+        let ray = Ray::new(V3::new(0.0, 0.0, 1.0), direction, 0.0);
+        for (metallic, roughness) in [(0.0, 0.2), (0.0, 0.8), (1.0, 0.5), (0.5, 1.0)] {
+            let material: Arc<dyn Material + Send + Sync> = Arc::new(Principled::new(
+                Colour::new(1.0, 1.0, 1.0),
+                metallic,
+                roughness,
+                0.5,
+            ));
+            let hit_record = hit_from(direction, material.clone());
+            let samples = 20000;
+            let mut total = 0.0;
+            for _ in 0..samples {
+                if let Some((_, attenuation)) = material.scatter(&ray, &hit_record) {
+                    total += average(attenuation);
+                }
+            }
+            let albedo = total / samples as f64;
+            assert!(albedo < 1.02, "albedo {albedo} for {metallic}, {roughness}");
+            assert!(albedo > 0.3, "albedo {albedo} for {metallic}, {roughness}");
+        }
+    }
+
+    /// The directions `material` scatters a ray arriving along `direction`
+    /// into, and their weights.
+    fn scatter_many(
+        material: Arc<dyn Material + Send + Sync>,
+        direction: V3,
+        time: f64,
+    ) -> Vec<(V3, Colour)> {
+        let ray = Ray::new(-time * direction, direction, 0.0);
+        let mut hit_record = hit_from(direction, material.clone());
+        hit_record.time = time;
+        return (0..1000)
+            .filter_map(|_| material.scatter(&ray, &hit_record))
+            .map(|(scattered, attenuation)| (unit_vector(scattered.direction), attenuation))
+            .collect();
+    }
+
+    #[test]
+    fn smooth_rough_dielectric_refracts_by_snells_law() {
+        let material = Arc::new(RoughDielectric::new(1.5, 0.0));
+        let direction = unit_vector(V3::new(1.0, 0.0, -1.0));
+        let samples = scatter_many(material, direction, 1.0);
+        let sin_refracted = f64::sin(PI / 4.0) / 1.5;
+        let refracted = V3::new(sin_refracted, 0.0, -f64::sqrt(1.0 - sin_refracted.powi(2)));
+        let reflected = unit_vector(V3::new(1.0, 0.0, 1.0));
+        let near = |a: V3, b: V3| (a - b).length() < 1e-2;
+        let refractions = samples.iter().filter(|(d, _)| near(*d, refracted)).count();
+        let reflections = samples.iter().filter(|(d, _)| near(*d, reflected)).count();
+        // about 5% is reflected at 45 degrees
+        assert!(refractions > 850, "only {refractions} refracted");
+        assert!(
+            reflections > 10 && reflections < 120,
+            "{reflections} reflected"
+        );
+        for (_, attenuation) in &samples {
+            assert!(attenuation.red <= 1.0 + 1e-9);
+        }
+    }
+
+    #[test]
+    fn thin_walled_glass_lets_light_through_unbent_and_tinted() {
+        let tint = Colour::new(0.2, 0.9, 0.5);
+        let material = Arc::new(RoughDielectric::new_thin_walled(1.5, 0.0, tint));
+        let direction = unit_vector(V3::new(1.0, 0.0, -1.0));
+        let samples = scatter_many(material, direction, 1.0);
+        let through: Vec<_> = samples
+            .iter()
+            .filter(|(d, _)| (*d - direction).length() < 1e-2)
+            .collect();
+        assert!(through.len() > 800, "only {} through", through.len());
+        for (_, attenuation) in through {
+            assert_close(attenuation.red / attenuation.green, 0.2 / 0.9, 1e-6);
+        }
+    }
+
+    #[test]
+    fn coloured_glass_absorbs_by_distance_travelled_inside() {
+        let colour = Colour::new(0.5, 1.0, 1.0);
+        let material = Arc::new(RoughDielectric::new_coloured(1.5, 0.0, colour, 1.0));
+        // leaving through the surface after two units inside
+        let samples = scatter_many(material.clone(), V3::new(0.0, 0.0, 1.0), 2.0);
+        let mut absorbed = 0;
+        for (_, attenuation) in &samples {
+            assert!(attenuation.red <= 0.25 + 1e-9);
+            if attenuation.green > 0.99 {
+                assert_close(attenuation.red, 0.25, 1e-2);
+                absorbed += 1;
+            }
+        }
+        assert!(absorbed > 900);
+        // nothing is absorbed entering the glass
+        let samples = scatter_many(material, V3::new(0.0, 0.0, -1.0), 2.0);
+        for (_, attenuation) in &samples {
+            assert_close(attenuation.red, attenuation.green, 1e-9);
+        }
+    }
+
+    #[test]
+    fn principled_samples_consistently() {
+        let direction = unit_vector(V3::new(1.0, 0.0, -1.0));
+        for (metallic, roughness) in [(0.0, 0.5), (0.5, 0.7), (1.0, 0.4)] {
+            let material = Principled::new(Colour::new(0.8, 0.5, 0.2), metallic, roughness, 0.5);
+            check_sampling(Arc::new(material), direction);
+        }
+    }
+
+    #[test]
+    fn rough_dielectric_samples_consistently() {
+        let tint = Colour::new(0.9, 0.6, 0.3);
+        let into = unit_vector(V3::new(1.0, 0.0, -1.0));
+        let out_of = unit_vector(V3::new(0.3, 0.0, 1.0));
+        check_sampling(Arc::new(RoughDielectric::new(1.5, 0.5)), into);
+        check_sampling(
+            Arc::new(RoughDielectric::new_coloured(1.5, 0.5, tint, 2.0)),
+            out_of,
+        );
+        check_sampling(
+            Arc::new(RoughDielectric::new_thin_walled(1.5, 0.5, tint)),
+            into,
+        );
+    }
+}
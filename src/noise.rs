@@ -0,0 +1,133 @@
+use rand::{prelude::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::v3::V3;
+
+const POINT_COUNT: usize = 256;
+
+/// Gradient noise, as described by Ken Perlin. Smoothly varying values in
+/// roughly -1 to 1, repeating every 256 units.
+pub struct Perlin {
+    gradients: Vec<V3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| random_unit_vector_from(&mut rng))
+            .collect();
+        let mut permutation = || {
+            let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+            p.shuffle(&mut rng);
+            return p;
+        };
+        let perm_x = permutation();
+        let perm_y = permutation();
+        let perm_z = permutation();
+        return Perlin {
+            gradients,
+            perm_x,
+            perm_y,
+            perm_z,
+        };
+    }
+
+    pub fn noise(&self, p: V3) -> f64 {
+        let u = p.x - p.x.floor();
+        let v = p.y - p.y.floor();
+        let w = p.z - p.z.floor();
+        let i = p.x.floor() as i64;
+        let j = p.y.floor() as i64;
+        let k = p.z.floor() as i64;
+
+        let mut accumulated = 0.0;
+        // hermite smoothing of the interpolation weights
+        let uu = u * u * (3.0 - 2.0 * u);
+        let vv = v * v * (3.0 - 2.0 * v);
+        let ww = w * w * (3.0 - 2.0 * w);
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let index = self.perm_x[((i + di) & 255) as usize]
+                        ^ self.perm_y[((j + dj) & 255) as usize]
+                        ^ self.perm_z[((k + dk) & 255) as usize];
+                    let gradient = self.gradients[index];
+                    let (fi, fj, fk) = (di as f64, dj as f64, dk as f64);
+                    let weight = V3::new(u - fi, v - fj, w - fk);
+                    accumulated += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * V3::dot(gradient, weight);
+                }
+            }
+        }
+        return accumulated;
+    }
+
+    /// Fractal Brownian motion: the sum of `octaves` octaves of noise, each
+    /// at double the frequency and half the amplitude of the previous one.
+    pub fn fbm(&self, p: V3, octaves: u32) -> f64 {
+        let mut accumulated = 0.0;
+        let mut p = p;
+        let mut weight = 1.0;
+        for _ in 0..octaves {
+            accumulated += weight * self.noise(p);
+            weight *= 0.5;
+            p = 2.0 * p;
+        }
+        return accumulated;
+    }
+
+    /// The size of `depth` octaves of fractal noise, from 0 up.
+    pub fn turbulence(&self, p: V3, depth: u32) -> f64 {
+        return f64::abs(self.fbm(p, depth));
+    }
+}
+
+fn random_unit_vector_from(rng: &mut StdRng) -> V3 {
+    // the global random_unit_vector isn't seedable, so draw from our own rng
+    loop {
+        let v = V3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        );
+        let length_squared = v.length_squared();
+        if length_squared > 1e-6 && length_squared < 1.0 {
+            return v / f64::sqrt(length_squared);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_zero_on_the_lattice_and_repeats() {
+        let perlin = Perlin::new(7);
+        assert_eq!(perlin.noise(V3::new(3.0, -2.0, 5.0)), 0.0);
+        let p = V3::new(0.3, 1.7, -2.2);
+        let repeated = p + V3::new(256.0, 256.0, -256.0);
+        assert!(f64::abs(perlin.noise(p) - perlin.noise(repeated)) < 1e-9);
+    }
+
+    #[test]
+    fn noise_is_seeded_and_bounded() {
+        let a = Perlin::new(1);
+        let b = Perlin::new(1);
+        let c = Perlin::new(2);
+        let mut differs = false;
+        for i in 0..100 {
+            let p = V3::new(0.37 * i as f64, 0.11 * i as f64, -0.23 * i as f64);
+            assert_eq!(a.noise(p), b.noise(p));
+            differs |= a.noise(p) != c.noise(p);
+            assert!(f64::abs(a.noise(p)) <= 1.0);
+            assert!(a.turbulence(p, 5) >= 0.0);
+        }
+        assert!(differs);
+    }
+}
@@ -0,0 +1,402 @@
+use std::{collections::HashMap, fs, io, path::Path, sync::Arc, time::Instant};
+
+use crate::{
+    aabb::AABB,
+    colour::Colour,
+    hittable::{HitRecord, Hittable},
+    material::{Lambertian, Material},
+    ray::Ray,
+    v3::{unit_vector, V3},
+};
+
+#[derive(Clone, Copy)]
+pub enum PointShape {
+    Sphere,
+    /// A flat disk, facing along the point's normal, or towards the ray if
+    /// the cloud has no normals
+    Disk,
+}
+
+/// The most points kept in a leaf of the tree.
+const LEAF_SIZE: usize = 4;
+
+/// A node of the tree over the points, stored flat with the first child of
+/// an interior node straight after it.
+struct PointNode {
+    bounds: AABB,
+    /// The first point of a leaf, or the second child of an interior node
+    start: usize,
+    /// Zero for an interior node
+    count: usize,
+}
+
+/// Many small coloured points, such as a lidar scan, each drawn as a sphere or
+/// disk of the same radius with a diffuse material of its own colour. The
+/// points are kept in flat arrays rather than as separate hittables, with a
+/// tree of their own to find them.
+pub struct PointCloud {
+    positions: Vec<V3>,
+    /// Which of `materials` each point uses
+    material_indices: Vec<u32>,
+    /// One for each distinct colour, shared by all the points of that colour
+    materials: Vec<Arc<dyn Material + Send + Sync>>,
+    /// Either empty or one for every point
+    normals: Vec<V3>,
+    radius: f64,
+    shape: PointShape,
+    nodes: Vec<PointNode>,
+}
+
+impl PointCloud {
+    pub fn new(
+        positions: Vec<V3>,
+        colours: Vec<Colour>,
+        normals: Option<Vec<V3>>,
+        radius: f64,
+        shape: PointShape,
+    ) -> Self {
+        assert_eq!(positions.len(), colours.len(), "every point needs a colour");
+        let normals = normals.unwrap_or_default();
+        assert!(
+            normals.is_empty() || normals.len() == positions.len(),
+            "every point needs a normal, or none do"
+        );
+        let start = Instant::now();
+
+        let mut order: Vec<usize> = (0..positions.len()).collect();
+        let mut nodes = vec![];
+        if !positions.is_empty() {
+            build_node(&positions, radius, &mut order, 0, &mut nodes);
+        }
+
+        // store the points in the order the leaves use them. Colours are
+        // rounded to bytes so that scans with many points of few colours
+        // share a material between them.
+        let to_byte = |c: f64| (f64::clamp(c, 0.0, 1.0) * 255.0).round() as u8;
+        let mut materials: Vec<Arc<dyn Material + Send + Sync>> = vec![];
+        let mut material_for_colour: HashMap<[u8; 3], u32> = HashMap::new();
+        let material_indices = order
+            .iter()
+            .map(|&i| {
+                let c = colours[i];
+                let bytes = [to_byte(c.red), to_byte(c.green), to_byte(c.blue)];
+                return *material_for_colour.entry(bytes).or_insert_with(|| {
+                    let [red, green, blue] = bytes.map(|b| b as f64 / 255.0);
+                    materials.push(Arc::new(Lambertian::new(Colour::new(red, green, blue))));
+                    return (materials.len() - 1) as u32;
+                });
+            })
+            .collect();
+        let normals = if normals.is_empty() {
+            normals
+        } else {
+            order.iter().map(|&i| unit_vector(normals[i])).collect()
+        };
+        let positions = order.iter().map(|&i| positions[i]).collect();
+
+        eprintln!(
+            "Built point cloud of {} points in {:.2?}",
+            order.len(),
+            start.elapsed()
+        );
+        return PointCloud {
+            positions,
+            material_indices,
+            materials,
+            normals,
+            radius,
+            shape,
+            nodes,
+        };
+    }
+
+    /// Load a text file of points, one per line, as `x y z`, `x y z r g b`
+    /// or `x y z r g b nx ny nz`, separated by spaces or commas. Colours may
+    /// be given from 0 to 1 or, if any are above 1, from 0 to 255. Points
+    /// without colours are white.
+    pub fn load_xyz<P: AsRef<Path>>(path: P, radius: f64, shape: PointShape) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let invalid = |line: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid point line: {line}"),
+            )
+        };
+
+        let mut positions = vec![];
+        let mut colours = vec![];
+        let mut normals = vec![];
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+                continue;
+            }
+            let values: Vec<f64> = line
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|part| !part.is_empty())
+                .map(|part| part.parse().map_err(|_| invalid(line)))
+                .collect::<io::Result<_>>()?;
+            if values.len() < 3 {
+                return Err(invalid(line));
+            }
+            positions.push(V3::new(values[0], values[1], values[2]));
+            if values.len() >= 6 {
+                colours.push(Colour::new(values[3], values[4], values[5]));
+            } else {
+                colours.push(Colour::new(1.0, 1.0, 1.0));
+            }
+            if values.len() >= 9 {
+                normals.push(V3::new(values[6], values[7], values[8]));
+            }
+        }
+        if !normals.is_empty() && normals.len() != positions.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "only some points have normals",
+            ));
+        }
+
+        let brightest = colours.iter().fold(0.0, |brightest: f64, c| {
+            return brightest.max(c.red).max(c.green).max(c.blue);
+        });
+        if brightest > 1.0 {
+            colours = colours.into_iter().map(|c| c / 255.0).collect();
+        }
+        let normals = if normals.is_empty() {
+            None
+        } else {
+            Some(normals)
+        };
+        return Ok(PointCloud::new(positions, colours, normals, radius, shape));
+    }
+
+    /// The distance along the ray to the point, if it is hit, and the
+    /// outward normal there.
+    fn hit_point(&self, index: usize, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, V3)> {
+        let centre = self.positions[index];
+        match self.shape {
+            PointShape::Sphere => {
+                let oc = ray.origin - centre;
+                let a = ray.direction.length_squared();
+                let half_b = V3::dot(oc, ray.direction);
+                let c = oc.length_squared() - self.radius * self.radius;
+                let discriminant = half_b * half_b - a * c;
+                if discriminant < 0.0 {
+                    return None;
+                }
+                let sqrtd = f64::sqrt(discriminant);
+                let mut t = (-half_b - sqrtd) / a;
+                if t < t_min || t_max < t {
+                    t = (-half_b + sqrtd) / a;
+                    if t < t_min || t_max < t {
+                        return None;
+                    }
+                }
+                return Some((t, (ray.at(t) - centre) / self.radius));
+            }
+            PointShape::Disk => {
+                let normal = if self.normals.is_empty() {
+                    -unit_vector(ray.direction)
+                } else {
+                    self.normals[index]
+                };
+                let denominator = V3::dot(normal, ray.direction);
+                if f64::abs(denominator) < 1e-12 {
+                    return None;
+                }
+                let t = V3::dot(centre - ray.origin, normal) / denominator;
+                if t < t_min || t_max < t {
+                    return None;
+                }
+                if (ray.at(t) - centre).length_squared() > self.radius * self.radius {
+                    return None;
+                }
+                return Some((t, normal));
+            }
+        }
+    }
+
+    /// The nearest point the ray hits: the distance along the ray, the
+    /// outward normal there, and the point's index.
+    fn closest_point(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, V3, usize)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let mut closest: Option<(f64, V3, usize)> = None;
+        let mut closest_so_far = t_max;
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !node.bounds.does_hit(ray, t_min, closest_so_far) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.start);
+                stack.push(node_index + 1);
+                continue;
+            }
+            for index in node.start..node.start + node.count {
+                if let Some((t, normal)) = self.hit_point(index, ray, t_min, closest_so_far) {
+                    closest_so_far = t;
+                    closest = Some((t, normal, index));
+                }
+            }
+        }
+        return closest;
+    }
+}
+
+impl Hittable for PointCloud {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // the points' materials are opaque, so the closest point is the hit
+        let (t, outward_normal, index) = self.closest_point(ray, t_min, t_max)?;
+        let material = self.materials[self.material_indices[index] as usize].clone();
+        return Some(HitRecord::new(
+            t,
+            0.0,
+            0.0,
+            ray.at(t),
+            outward_normal,
+            ray.direction,
+            material,
+        ));
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        return self.nodes.first().map(|node| node.bounds);
+    }
+}
+
+fn point_bounds(centre: V3, radius: f64) -> AABB {
+    let half_size = V3::new(radius, radius, radius);
+    return AABB::new(centre - half_size, centre + half_size);
+}
+
+/// Add the node for the points `order[offset..offset + order.len()]`, and
+/// everything under it, sorting `order` so each leaf's points are together.
+fn build_node(
+    positions: &[V3],
+    radius: f64,
+    order: &mut [usize],
+    offset: usize,
+    nodes: &mut Vec<PointNode>,
+) {
+    let bounds = order
+        .iter()
+        .map(|&i| point_bounds(positions[i], radius))
+        .reduce(|a, b| a + b)
+        .unwrap();
+    let node_index = nodes.len();
+    if order.len() <= LEAF_SIZE {
+        nodes.push(PointNode {
+            bounds,
+            start: offset,
+            count: order.len(),
+        });
+        return;
+    }
+    nodes.push(PointNode {
+        bounds,
+        start: 0,
+        count: 0,
+    });
+
+    // split at the median along the longest side
+    let extent = bounds.maximum - bounds.minimum;
+    let axis = if extent.x > extent.y && extent.x > extent.z {
+        0
+    } else if extent.y > extent.z {
+        1
+    } else {
+        2
+    };
+    let middle = order.len() / 2;
+    order.select_nth_unstable_by(middle, |&a, &b| {
+        return positions[a]
+            .get_by_index(axis)
+            .total_cmp(&positions[b].get_by_index(axis));
+    });
+    let (first, second) = order.split_at_mut(middle);
+    build_node(positions, radius, first, offset, nodes);
+    nodes[node_index].start = nodes.len();
+    build_node(positions, radius, second, offset + middle, nodes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn along_z() -> Ray {
+        return Ray::new(V3::new(0.0, 0.0, -10.0), V3::new(0.0, 0.0, 1.0), 0.0);
+    }
+
+    /// A line of points along z, plus a few scattered off to the side.
+    fn line(shape: PointShape) -> PointCloud {
+        let mut positions = vec![];
+        let mut colours = vec![];
+        for i in 0..50 {
+            positions.push(V3::new(0.0, 0.0, 49.0 - i as f64));
+            colours.push(Colour::new(i as f64 / 49.0, 0.0, 0.0));
+            positions.push(V3::new(5.0, i as f64, 0.0));
+            colours.push(Colour::new(0.0, 1.0, 0.0));
+        }
+        return PointCloud::new(positions, colours, None, 0.25, shape);
+    }
+
+    #[test]
+    fn nearest_point_is_found_through_the_tree() {
+        let cloud = line(PointShape::Sphere);
+        let hit = cloud.hit(&along_z(), 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 9.75) < 1e-9);
+        assert!((hit.normal - V3::new(0.0, 0.0, -1.0)).length() < 1e-9);
+        let hit = cloud.hit(&along_z(), 15.5, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 15.75) < 1e-9);
+        let miss = Ray::new(V3::new(2.0, 0.0, -10.0), V3::new(0.0, 0.0, 1.0), 0.0);
+        assert!(cloud.hit(&miss, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn disks_without_normals_face_the_ray() {
+        let cloud = line(PointShape::Disk);
+        let hit = cloud.hit(&along_z(), 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 10.0) < 1e-9);
+        assert!(hit.front_face);
+    }
+
+    #[test]
+    fn xyz_colours_out_of_255_are_scaled() {
+        let path = std::env::temp_dir().join("rt_points.xyz");
+        fs::write(&path, "# points\n0 0 0 255 0 0\n1,0,0,0,255,0\n\n").unwrap();
+        let cloud = PointCloud::load_xyz(&path, 0.1, PointShape::Sphere).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(cloud.positions.len(), 2);
+        let colour_at = |x: f64| {
+            let ray = Ray::new(V3::new(x, 0.0, -10.0), V3::new(0.0, 0.0, 1.0), 0.0);
+            let hit = cloud.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            return hit.material.eval(&ray, &hit, V3::new(0.0, 0.0, -1.0));
+        };
+        let (red, green) = (colour_at(0.0), colour_at(1.0));
+        assert!(red.red > 0.0 && red.green == 0.0);
+        assert!(green.green > 0.0 && green.red == 0.0);
+        assert!(f64::abs(red.red - green.green) < 1e-9);
+    }
+
+    #[test]
+    fn points_of_the_same_colour_share_a_material() {
+        let cloud = line(PointShape::Sphere);
+        // fifty shades of red, and one green
+        assert_eq!(cloud.materials.len(), 51);
+        assert_eq!(cloud.material_indices.len(), 100);
+    }
+
+    #[test]
+    fn xyz_with_some_normals_is_invalid() {
+        let path = std::env::temp_dir().join("rt_points_normals.xyz");
+        fs::write(&path, "0 0 0 1 1 1 0 1 0\n1 0 0 1 1 1\n").unwrap();
+        let error = PointCloud::load_xyz(&path, 0.1, PointShape::Disk)
+            .err()
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -3,11 +3,61 @@ use crate::v3::V3;
 pub struct Ray {
     pub origin: V3,
     pub direction: V3,
+    /// The moment within the camera's shutter interval that the ray was cast
+    pub time: f64,
+    /// Rays through the neighbouring pixels, for working out how much of a
+    /// surface a pixel covers. Only camera rays have them.
+    pub differentials: Option<RayDifferentials>,
+    /// The single wavelength in nanometres that the ray carries when
+    /// rendering spectrally, or none when it carries red, green and blue
+    pub wavelength: Option<f64>,
+}
+
+/// The rays one pixel across (`x`) and one pixel up (`y`) from a camera ray.
+#[derive(Clone, Copy)]
+pub struct RayDifferentials {
+    pub x_origin: V3,
+    pub x_direction: V3,
+    pub y_origin: V3,
+    pub y_direction: V3,
 }
 
 impl Ray {
-    pub fn new(origin: V3, direction: V3) -> Ray {
-        return Ray { origin, direction };
+    pub fn new(origin: V3, direction: V3, time: f64) -> Ray {
+        return Ray {
+            origin,
+            direction,
+            time,
+            differentials: None,
+            wavelength: None,
+        };
+    }
+
+    pub fn new_with_differentials(
+        origin: V3,
+        direction: V3,
+        time: f64,
+        differentials: RayDifferentials,
+    ) -> Ray {
+        return Ray {
+            origin,
+            direction,
+            time,
+            differentials: Some(differentials),
+            wavelength: None,
+        };
+    }
+
+    /// The same ray (time, differentials and wavelength) but from another
+    /// origin in another direction.
+    pub fn with_origin_direction(&self, origin: V3, direction: V3) -> Ray {
+        return Ray {
+            origin,
+            direction,
+            time: self.time,
+            differentials: self.differentials,
+            wavelength: self.wavelength,
+        };
     }
 
     pub fn at(&self, t: f64) -> V3 {
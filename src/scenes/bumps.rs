@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use crate::{
+    bump::{BumpMap, NormalMap},
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, Plane, Sphere},
+    material::{Dielectric, DiffuseLight, Lambertian, Metal},
+    texture::{Addressing, ColourRamp, ImageTexture, Noise, UvTransform},
+    v3::{unit_vector, V3},
+};
+
+use super::Scene;
+
+/// A tangent space normal map to tile over the metal ball, stored as plain
+/// values rather than sRGB colours.
+const BALL_NORMALS: &str = "ball_normals.png";
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 4.0, 13.0);
+    let look_at = V3::new(0.0, 1.2, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 13.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+/// A tile which is raised everywhere except its edges.
+fn tile_heights() -> ImageTexture {
+    let size = 16;
+    let image = (0..size)
+        .map(|j| {
+            return (0..size)
+                .map(|i| {
+                    if i < 2 || j < 2 {
+                        return Colour::new(0.0, 0.0, 0.0);
+                    }
+                    return Colour::new(1.0, 1.0, 1.0);
+                })
+                .collect();
+        })
+        .collect();
+    return ImageTexture::new(image, Addressing::Wrap);
+}
+
+/// A normal map of a round dimple, like on a golf ball.
+fn dimple_normals() -> ImageTexture {
+    let size = 32;
+    let image = (0..size)
+        .map(|j| {
+            return (0..size)
+                .map(|i| {
+                    // from -1 to 1 across the tile
+                    let x = 2.0 * (i as f64 + 0.5) / size as f64 - 1.0;
+                    let y = 2.0 * (j as f64 + 0.5) / size as f64 - 1.0;
+                    let distance_squared = x * x + y * y;
+                    // the inside of a sphere tilts towards the middle
+                    let normal = if distance_squared < 0.8 {
+                        unit_vector(V3::new(-x, -y, 1.5))
+                    } else {
+                        V3::new(0.0, 0.0, 1.0)
+                    };
+                    return Colour::new(
+                        0.5 + 0.5 * normal.x,
+                        0.5 + 0.5 * normal.y,
+                        0.5 + 0.5 * normal.z,
+                    );
+                })
+                .collect();
+        })
+        .collect();
+    return ImageTexture::new(image, Addressing::Wrap);
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    // tiles, with the grout sunk between them
+    let tiles = UvTransform::new(tile_heights(), (0.5, 0.5), 0.0, (0.0, 0.0));
+    let ground_material = BumpMap::new(
+        Arc::new(Lambertian::new(Colour::new(0.6, 0.6, 0.55))),
+        tiles,
+        0.1,
+    );
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        Arc::new(ground_material),
+    );
+    hittables.push(Arc::new(ground));
+
+    // lumpy clay
+    let lumps = Noise::new(
+        1,
+        4.0,
+        4,
+        ColourRamp::new_from_colours(Colour::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0)),
+    );
+    let clay = BumpMap::new(
+        Arc::new(Lambertian::new(Colour::new(0.8, 0.4, 0.2))),
+        lumps,
+        0.1,
+    );
+    let clay_sphere = Sphere::new(V3::new(-3.0, 1.2, 0.0), 1.2, Arc::new(clay));
+    hittables.push(Arc::new(clay_sphere));
+
+    // a dimpled metal ball
+    let normals = match ImageTexture::load_linear(BALL_NORMALS, Addressing::Wrap) {
+        Ok(normals) => normals,
+        Err(error) => {
+            eprintln!("Couldn't load {BALL_NORMALS} ({error}), using dimples instead");
+            dimple_normals()
+        }
+    };
+    let dimples = UvTransform::new(normals, (16.0, 8.0), 0.0, (0.0, 0.0));
+    let golf = NormalMap::new(
+        Arc::new(Metal::new(Colour::new(0.9, 0.9, 0.9), 0.05)),
+        dimples,
+        1.0,
+    );
+    let golf_sphere = Sphere::new(V3::new(0.0, 1.2, 0.0), 1.2, Arc::new(golf));
+    hittables.push(Arc::new(golf_sphere));
+
+    // rippled glass
+    let ripples = Noise::new(
+        2,
+        2.0,
+        2,
+        ColourRamp::new_from_colours(Colour::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0)),
+    );
+    let glass = BumpMap::new(Arc::new(Dielectric::new(1.5)), ripples, 0.2);
+    let glass_sphere = Sphere::new(V3::new(3.0, 1.2, 0.0), 1.2, Arc::new(glass));
+    hittables.push(Arc::new(glass_sphere));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(6.0, 6.0, 6.0)));
+    let light = Sphere::new(V3::new(-4.0, 12.0, 8.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
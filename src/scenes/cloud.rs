@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    camera::Camera,
+    colour::Colour,
+    hittable::{HittableList, Sphere},
+    material::{DiffuseLight, Lambertian},
+    medium::{DensityGrid, GridMedium},
+    v3::V3,
+};
+
+use super::Scene;
+
+/// A density grid for the cloud, in the raw format `DensityGrid::load_raw`
+/// reads.
+const CLOUD_DENSITY: &str = "cloud.raw";
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 3.0, 20.0);
+    let look_at = V3::new(0.0, 2.5, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 20.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        30.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let ground_material = Arc::new(Lambertian::new(Colour::new(0.3, 0.5, 0.2)));
+    let ground_sphere = Sphere::new(V3::new(0.0, -1000.0, 0.0), 1000.0, ground_material);
+    world.add(Arc::new(ground_sphere));
+
+    let bounds = AABB::new(V3::new(-4.0, 0.5, -3.0), V3::new(4.0, 4.5, 3.0));
+    let grid = match DensityGrid::load_raw(CLOUD_DENSITY) {
+        Ok(grid) => grid,
+        Err(error) => {
+            eprintln!("Couldn't load {CLOUD_DENSITY} ({error}), using noise instead");
+            DensityGrid::from_noise(64, 11, 4.0)
+        }
+    };
+    let cloud = GridMedium::new(bounds, grid, 0.05, 2.0, Colour::new(1.0, 1.0, 1.0), 0.6);
+    world.add(Arc::new(cloud));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(15.0, 14.0, 12.0)));
+    let light = Sphere::new(V3::new(-10.0, 30.0, -20.0), 8.0, light_material);
+    world.add(Arc::new(light));
+
+    return world;
+}
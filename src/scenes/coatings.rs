@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, Plane, Sphere, Torus},
+    layered::Layered,
+    material::{DiffuseLight, Lambertian},
+    microfacet::Principled,
+    texture::Checkers,
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 4.0, 13.0);
+    let look_at = V3::new(0.0, 1.0, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 13.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    let checks =
+        Checkers::new_from_colours(1.0, Colour::new(0.7, 0.7, 0.7), Colour::new(0.2, 0.2, 0.2));
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new_from_texture(checks)),
+    );
+    hittables.push(Arc::new(ground));
+
+    // car paint: rough metallic flake, bare and then under a glossy coat
+    let paint = Arc::new(Principled::new(
+        Colour::new(0.6, 0.05, 0.05),
+        0.6,
+        0.45,
+        0.5,
+    ));
+    let bare = Sphere::new(V3::new(-4.5, 1.0, 0.0), 1.0, paint.clone());
+    hittables.push(Arc::new(bare));
+    let coated_paint = Layered::new_clearcoat(paint, 1.0, 0.03);
+    let coated = Sphere::new(V3::new(-2.0, 1.0, 0.0), 1.0, Arc::new(coated_paint));
+    hittables.push(Arc::new(coated));
+
+    // velvet: a dark cloth with a pale sheen at grazing angles
+    let cloth = Arc::new(Lambertian::new(Colour::new(0.05, 0.05, 0.25)));
+    let velvet = Layered::new_sheen(cloth, Colour::new(0.8, 0.8, 1.0), 0.3);
+    let velvet_sphere = Sphere::new(V3::new(0.5, 1.0, 0.0), 1.0, Arc::new(velvet));
+    hittables.push(Arc::new(velvet_sphere));
+
+    // lacquered wood: both at once
+    let wood = Arc::new(Lambertian::new(Colour::new(0.45, 0.25, 0.1)));
+    let lacquer = Layered::new(wood, 0.8, 0.1, Colour::new(0.3, 0.2, 0.1), 0.5);
+    let ring = Torus::new(V3::new(3.8, 0.5, 0.0), 1.1, 0.5, Arc::new(lacquer));
+    hittables.push(Arc::new(ring));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(8.0, 8.0, 7.0)));
+    let light = Sphere::new(V3::new(-4.0, 14.0, 8.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+    let sky_material = Arc::new(DiffuseLight::new(Colour::new(0.3, 0.35, 0.45)));
+    let sky = Sphere::new(V3::new(0.0, 0.0, 0.0), 100.0, sky_material);
+    hittables.push(Arc::new(sky));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
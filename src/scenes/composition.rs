@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Cuboid, Hittable, Plane, Sphere},
+    material::{DiffuseLight, Lambertian},
+    texture::{
+        Add, Addressing, ColourRamp, ImageTexture, Marble, Mix, Multiply, Noise, Ramp, SolidColour,
+        Triplanar, UvTransform, Wood,
+    },
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 4.0, 13.0);
+    let look_at = V3::new(0.0, 1.2, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 13.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+/// A small tile: light with dark grout around the edge.
+fn tile_image() -> ImageTexture {
+    let size = 16;
+    let image = (0..size)
+        .map(|j| {
+            return (0..size)
+                .map(|i| {
+                    if i == 0 || j == 0 {
+                        return Colour::new(0.1, 0.1, 0.1);
+                    }
+                    return Colour::new(0.8, 0.75, 0.7);
+                })
+                .collect();
+        })
+        .collect();
+    return ImageTexture::new(image, Addressing::Wrap);
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    // large tiles, turned to run diagonally, darkened by grime
+    let tiles = UvTransform::new(tile_image(), (0.5, 0.5), 45.0, (0.0, 0.0));
+    let grime = Noise::new(
+        1,
+        1.5,
+        4,
+        ColourRamp::new_from_colours(Colour::new(0.4, 0.4, 0.4), Colour::new(1.0, 1.0, 1.0)),
+    );
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new_from_texture(Multiply::new(tiles, grime))),
+    );
+    hittables.push(Arc::new(ground));
+
+    // marble and wood, mixed by a mask of noise
+    let white = Colour::new(0.9, 0.9, 0.9);
+    let dark = Colour::new(0.2, 0.2, 0.25);
+    let marble = Marble::new(2, 4.0, 6, ColourRamp::new_from_colours(white, dark));
+    let wood = Wood::new(
+        3,
+        5.0,
+        4,
+        ColourRamp::new_from_colours(Colour::new(0.75, 0.5, 0.25), Colour::new(0.35, 0.18, 0.07)),
+    );
+    // bleached a little
+    let wood = Mix::new(wood, SolidColour::new(white), 0.2);
+    let mask = Noise::new(
+        4,
+        1.5,
+        3,
+        ColourRamp::new(vec![
+            (0.45, Colour::new(0.0, 0.0, 0.0)),
+            (0.55, Colour::new(1.0, 1.0, 1.0)),
+        ]),
+    );
+    let mixed = Mix::new_with_mask(marble, wood, mask);
+    let mixed_sphere = Sphere::new(
+        V3::new(-3.0, 1.2, 0.0),
+        1.2,
+        Arc::new(Lambertian::new_from_texture(mixed)),
+    );
+    hittables.push(Arc::new(mixed_sphere));
+
+    // grey noise recoloured as a heat map, with a faint blue added
+    let heat = Ramp::new(
+        Noise::new(
+            5,
+            3.0,
+            5,
+            ColourRamp::new_from_colours(Colour::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0)),
+        ),
+        ColourRamp::new(vec![
+            (0.3, Colour::new(0.1, 0.0, 0.2)),
+            (0.5, Colour::new(0.8, 0.1, 0.1)),
+            (0.7, Colour::new(1.0, 0.9, 0.2)),
+        ]),
+    );
+    let tinted = Add::new(heat, SolidColour::new(Colour::new(0.0, 0.0, 0.1)));
+    let cube = Cuboid::new(
+        V3::new(-1.0, 0.0, -1.0),
+        V3::new(1.0, 2.0, 1.0),
+        Arc::new(Lambertian::new_from_texture(tinted)),
+    );
+    hittables.push(Arc::new(cube));
+
+    // tiles projected from three sides onto a sphere
+    let centre = V3::new(3.0, 1.2, 0.0);
+    let projected = Triplanar::new(tile_image(), centre, 2.0, 4.0);
+    let projected_sphere = Sphere::new(
+        centre,
+        1.2,
+        Arc::new(Lambertian::new_from_texture(projected)),
+    );
+    hittables.push(Arc::new(projected_sphere));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(6.0, 6.0, 6.0)));
+    let light = Sphere::new(V3::new(-4.0, 12.0, 8.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Cuboid, Hittable, XYRect, XZRect, YZRect},
+    material::{DiffuseLight, Lambertian},
+    medium::ConstantMedium,
+    transform::Transformed,
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 1.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(278.0, 278.0, -800.0);
+    let look_at = V3::new(278.0, 278.0, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 10.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        40.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    let red = Arc::new(Lambertian::new(Colour::new(0.65, 0.05, 0.05)));
+    let white = Arc::new(Lambertian::new(Colour::new(0.73, 0.73, 0.73)));
+    let green = Arc::new(Lambertian::new(Colour::new(0.12, 0.45, 0.15)));
+    let light = Arc::new(DiffuseLight::new(Colour::new(7.0, 7.0, 7.0)));
+
+    // walls
+    hittables.push(Arc::new(YZRect::new(0.0, 555.0, 0.0, 555.0, 555.0, green)));
+    hittables.push(Arc::new(YZRect::new(0.0, 555.0, 0.0, 555.0, 0.0, red)));
+    hittables.push(Arc::new(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        0.0,
+        white.clone(),
+    )));
+    hittables.push(Arc::new(XZRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+    hittables.push(Arc::new(XYRect::new(
+        0.0,
+        555.0,
+        0.0,
+        555.0,
+        555.0,
+        white.clone(),
+    )));
+
+    // lights
+    hittables.push(Arc::new(XZRect::new(
+        113.0, 443.0, 127.0, 432.0, 554.0, light,
+    )));
+
+    // smoke
+    let tall_box = Arc::new(Cuboid::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(165.0, 330.0, 165.0),
+        white.clone(),
+    ));
+    let tall_box = Arc::new(Transformed::rotate_y(tall_box, 15.0));
+    let tall_box = Arc::new(Transformed::translate(tall_box, V3::new(265.0, 0.0, 295.0)));
+    hittables.push(Arc::new(ConstantMedium::new(
+        tall_box,
+        0.01,
+        Colour::new(0.0, 0.0, 0.0),
+    )));
+
+    let short_box = Arc::new(Cuboid::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(165.0, 165.0, 165.0),
+        white,
+    ));
+    let short_box = Arc::new(Transformed::rotate_y(short_box, -18.0));
+    let short_box = Arc::new(Transformed::translate(short_box, V3::new(130.0, 0.0, 65.0)));
+    hittables.push(Arc::new(ConstantMedium::new(
+        short_box,
+        0.01,
+        Colour::new(1.0, 1.0, 1.0),
+    )));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
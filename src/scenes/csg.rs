@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    csg::Csg,
+    hittable::{Cuboid, Cylinder, Hittable, Plane, Sphere},
+    material::{DiffuseLight, Lambertian, Metal},
+    transform::Transformed,
+    v3::{M4, V3},
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 6.0, 14.0);
+    let look_at = V3::new(0.0, 2.0, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 14.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        30.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    let ground_material = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        ground_material,
+    );
+    hittables.push(Arc::new(ground));
+
+    // the classic rounded cube, drilled through along each axis
+    let red = Arc::new(Lambertian::new(Colour::new(0.7, 0.1, 0.1)));
+    let steel = Arc::new(Metal::new(Colour::new(0.8, 0.8, 0.9), 0.2));
+    let centre = V3::new(0.0, 2.0, 0.0);
+    let cube = Arc::new(Cuboid::new(
+        centre - V3::new(1.5, 1.5, 1.5),
+        centre + V3::new(1.5, 1.5, 1.5),
+        red,
+    ));
+    let sphere = Arc::new(Sphere::new(centre, 2.0, steel));
+    let rounded_cube: Arc<dyn Hittable + Send + Sync> = Arc::new(Csg::intersection(cube, sphere));
+
+    let blue = Arc::new(Lambertian::new(Colour::new(0.1, 0.2, 0.7)));
+    let drill: Arc<dyn Hittable + Send + Sync> =
+        Arc::new(Cylinder::new(V3::new(0.0, -2.0, 0.0), 0.8, 4.0, blue));
+    let mut drilled = rounded_cube;
+    for rotation in [M4::identity(), M4::rotation_x(90.0), M4::rotation_z(90.0)] {
+        let hole = Arc::new(Transformed::new(
+            drill.clone(),
+            M4::translation(centre) * rotation,
+        ));
+        drilled = Arc::new(Csg::difference(drilled, hole));
+    }
+    let placed = Transformed::new(
+        drilled,
+        M4::translation(centre) * M4::rotation_y(30.0) * M4::translation(-centre),
+    );
+    hittables.push(Arc::new(placed));
+
+    // a capsule beside it: a cylinder with a ball on each end
+    let gold = Arc::new(Metal::new(Colour::new(0.8, 0.6, 0.2), 0.1));
+    let capsule_base = V3::new(4.0, 0.5, 0.0);
+    let shaft = Arc::new(Cylinder::new(capsule_base, 0.5, 2.0, gold.clone()));
+    let bottom = Arc::new(Sphere::new(capsule_base, 0.5, gold.clone()));
+    let top = Arc::new(Sphere::new(
+        capsule_base + V3::new(0.0, 2.0, 0.0),
+        0.5,
+        gold,
+    ));
+    let ends = Arc::new(Csg::union(bottom, top));
+    hittables.push(Arc::new(Csg::union(shaft, ends)));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(6.0, 6.0, 6.0)));
+    let light = Sphere::new(V3::new(0.0, 12.0, 4.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
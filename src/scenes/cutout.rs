@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use rand::{prelude::StdRng, Rng, SeedableRng};
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Cylinder, Hittable, Plane, Sphere, XYRect},
+    material::{AlphaMask, AlphaMode, DiffuseLight, Lambertian},
+    mesh::Mesh,
+    texture::{Addressing, ImageTexture, SolidColour, UvTransform},
+    v3::{unit_vector, V3},
+};
+
+use super::Scene;
+
+/// A picture of a leaf on a transparent background, whose alpha cuts the
+/// leaves out of their cards.
+const LEAF: &str = "leaf.png";
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(1.5, 2.5, 12.0);
+    let look_at = V3::new(0.0, 1.8, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 12.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+/// The opacity of one picket of a fence, with a pointed top and a gap to
+/// either side.
+fn picket_opacity() -> ImageTexture {
+    let size = 32;
+    let image = (0..size)
+        .map(|j| {
+            return (0..size)
+                .map(|i| {
+                    let x = (i as f64 + 0.5) / size as f64;
+                    let y = (j as f64 + 0.5) / size as f64;
+                    let from_middle = f64::abs(x - 0.5);
+                    let inside = from_middle < 0.35 && y < 1.0 - from_middle;
+                    if inside {
+                        return Colour::new(1.0, 1.0, 1.0);
+                    }
+                    return Colour::new(0.0, 0.0, 0.0);
+                })
+                .collect();
+        })
+        .collect();
+    return ImageTexture::new(image, Addressing::Wrap);
+}
+
+/// The opacity of a leaf, pointed at both ends, filling the unit square.
+fn leaf_opacity() -> ImageTexture {
+    let size = 32;
+    let image = (0..size)
+        .map(|j| {
+            return (0..size)
+                .map(|i| {
+                    let x = 2.0 * (i as f64 + 0.5) / size as f64 - 1.0;
+                    let y = 2.0 * (j as f64 + 0.5) / size as f64 - 1.0;
+                    // the width of the leaf narrows towards its tips
+                    let width = 0.6 * (1.0 - y * y);
+                    if f64::abs(x) < width {
+                        return Colour::new(1.0, 1.0, 1.0);
+                    }
+                    return Colour::new(0.0, 0.0, 0.0);
+                })
+                .collect();
+        })
+        .collect();
+    return ImageTexture::new(image, Addressing::Clamp);
+}
+
+/// A cloud of square cards scattered through a ball, each with uv from 0 to
+/// 1 across it.
+fn leaf_cards(rng: &mut StdRng, centre: V3, radius: f64, count: usize, size: f64) -> Mesh {
+    let mut positions = vec![];
+    let mut uvs = vec![];
+    let mut faces = vec![];
+    for _ in 0..count {
+        let offset = loop {
+            let p = V3::new(
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+                rng.gen_range(-1.0..1.0),
+            );
+            if p.length_squared() < 1.0 {
+                break p;
+            }
+        };
+        let middle = centre + radius * offset;
+        // a random orientation, from two random directions
+        let along = unit_vector(V3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        ));
+        let random = V3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        );
+        let across = unit_vector(V3::cross(along, random));
+        let (along, across) = (0.5 * size * along, 0.5 * size * across);
+        let first = positions.len();
+        positions.extend([
+            middle - across - along,
+            middle + across - along,
+            middle + across + along,
+            middle - across + along,
+        ]);
+        uvs.extend([(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+        faces.push([first, first + 1, first + 2]);
+        faces.push([first, first + 2, first + 3]);
+    }
+    return Mesh::new(positions, uvs, faces);
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+    let mut rng = StdRng::seed_from_u64(43);
+
+    let ground_material = Arc::new(Lambertian::new(Colour::new(0.3, 0.45, 0.2)));
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        ground_material,
+    );
+    hittables.push(Arc::new(ground));
+
+    // a picket fence across the front, one quad with the gaps cut out
+    let pickets = UvTransform::new(picket_opacity(), (12.0, 1.0), 0.0, (0.0, 0.0));
+    let fence_material = AlphaMask::new(
+        Arc::new(Lambertian::new(Colour::new(0.85, 0.85, 0.8))),
+        pickets,
+        AlphaMode::Threshold(0.5),
+    );
+    let fence = XYRect::new(-6.0, 6.0, 0.0, 1.5, 2.5, Arc::new(fence_material));
+    hittables.push(Arc::new(fence));
+
+    // a tree behind it, with leaves cut out of square cards
+    let trunk_material = Arc::new(Lambertian::new(Colour::new(0.35, 0.22, 0.12)));
+    let trunk = Cylinder::new(V3::new(-2.0, 0.0, -2.0), 0.25, 3.0, trunk_material);
+    hittables.push(Arc::new(trunk));
+    let leaf_opacity = match ImageTexture::load_alpha(LEAF, Addressing::Clamp) {
+        Ok(opacity) => opacity,
+        Err(error) => {
+            eprintln!("Couldn't load {LEAF} ({error}), using a drawn leaf instead");
+            leaf_opacity()
+        }
+    };
+    let leaf_material = AlphaMask::new(
+        Arc::new(Lambertian::new(Colour::new(0.2, 0.5, 0.1))),
+        leaf_opacity,
+        AlphaMode::Threshold(0.5),
+    );
+    let leaves = leaf_cards(&mut rng, V3::new(-2.0, 3.6, -2.0), 1.6, 1500, 0.35);
+    hittables.push(Arc::new(leaves.build(Arc::new(leaf_material))));
+
+    // a ball which lets half the light through
+    let ghost_material = AlphaMask::new(
+        Arc::new(Lambertian::new(Colour::new(0.8, 0.2, 0.2))),
+        SolidColour::new(Colour::new(0.5, 0.5, 0.5)),
+        AlphaMode::Stochastic,
+    );
+    let ghost = Sphere::new(V3::new(2.5, 1.2, -1.0), 1.2, Arc::new(ghost_material));
+    hittables.push(Arc::new(ghost));
+
+    // lights, and a dim sky to see through the holes to
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(8.0, 8.0, 7.0)));
+    let light = Sphere::new(V3::new(6.0, 14.0, 8.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+    let sky_material = Arc::new(DiffuseLight::new(Colour::new(0.3, 0.4, 0.6)));
+    let sky = Sphere::new(V3::new(0.0, 0.0, 0.0), 100.0, sky_material);
+    hittables.push(Arc::new(sky));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
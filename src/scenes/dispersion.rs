@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, Plane, Sphere, XYRect},
+    material::{Dielectric, DiffuseLight, IndexOfRefraction, Lambertian},
+    mesh::Mesh,
+    v3::V3,
+};
+
+use super::Scene;
+
+/// Best rendered with `ColourMode::Spectral`; in RGB the glass is clear.
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 2.0, 12.0);
+    let look_at = V3::new(0.0, 1.2, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 12.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+/// A triangular prism lying along x, with its faces kept flat by giving each
+/// its own vertices.
+fn prism(centre: V3, size: f64, length: f64) -> Mesh {
+    let corners: Vec<V3> = (0..3)
+        .map(|i| {
+            let angle = std::f64::consts::FRAC_PI_2 + 2.0 * std::f64::consts::PI * i as f64 / 3.0;
+            return centre + size * V3::new(0.0, f64::sin(angle), f64::cos(angle));
+        })
+        .collect();
+    let back = V3::new(-0.5 * length, 0.0, 0.0);
+    let front = V3::new(0.5 * length, 0.0, 0.0);
+    let mut quads = vec![];
+    for i in 0..3 {
+        let (a, b) = (corners[i], corners[(i + 1) % 3]);
+        quads.push(vec![a + back, b + back, b + front, a + front]);
+    }
+    let ends = vec![
+        corners.iter().map(|&c| c + back).collect(),
+        corners.iter().map(|&c| c + front).collect(),
+    ];
+
+    let mut positions = vec![];
+    let mut faces = vec![];
+    for polygon in quads.into_iter().chain(ends) {
+        // wind each face so its normal points out of the prism
+        let middle = polygon
+            .iter()
+            .fold(V3::new(0.0, 0.0, 0.0), |sum, &p| sum + p)
+            / polygon.len() as f64;
+        let normal = V3::cross(polygon[1] - polygon[0], polygon[2] - polygon[0]);
+        let outward = V3::dot(normal, middle - centre) > 0.0;
+        let first = positions.len();
+        positions.extend(polygon.iter().cloned());
+        for i in 1..polygon.len() - 1 {
+            if outward {
+                faces.push([first, first + i, first + i + 1]);
+            } else {
+                faces.push([first, first + i + 1, first + i]);
+            }
+        }
+    }
+    let uvs = vec![(0.0, 0.0); positions.len()];
+    return Mesh::new(positions, uvs, faces);
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    let floor_material = Arc::new(Lambertian::new(Colour::new(0.3, 0.3, 0.3)));
+    let floor = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        floor_material,
+    );
+    hittables.push(Arc::new(floor));
+
+    // bright stripes on a dark wall, whose sharp edges show the colours
+    let wall_material = Arc::new(Lambertian::new(Colour::new(0.05, 0.05, 0.05)));
+    let wall = Plane::new(
+        V3::new(0.0, 0.0, -4.0),
+        V3::new(0.0, 0.0, 1.0),
+        wall_material,
+    );
+    hittables.push(Arc::new(wall));
+    let stripe_material = Arc::new(DiffuseLight::new(Colour::new(4.0, 4.0, 4.0)));
+    for i in 0..12 {
+        let x = -7.0 + 1.2 * i as f64;
+        let stripe = XYRect::new(x, x + 0.25, 0.0, 6.0, -3.9, stripe_material.clone());
+        hittables.push(Arc::new(stripe));
+    }
+
+    // crown glass, diamond and a glass with exaggerated dispersion
+    let crown = Dielectric::new_dispersive(IndexOfRefraction::crown_glass());
+    hittables.push(Arc::new(Sphere::new(
+        V3::new(-4.5, 1.0, 0.0),
+        1.0,
+        Arc::new(crown),
+    )));
+    let diamond = Dielectric::new_dispersive(IndexOfRefraction::diamond());
+    hittables.push(Arc::new(Sphere::new(
+        V3::new(-2.0, 1.0, 0.0),
+        1.0,
+        Arc::new(diamond),
+    )));
+    let dense = Dielectric::new_dispersive(IndexOfRefraction::Cauchy { a: 1.5, b: 0.06 });
+    hittables.push(Arc::new(Sphere::new(
+        V3::new(0.5, 1.0, 0.0),
+        1.0,
+        Arc::new(dense),
+    )));
+    let prism_material = Dielectric::new_dispersive(IndexOfRefraction::Cauchy { a: 1.5, b: 0.06 });
+    let prism = prism(V3::new(3.8, 1.0, 0.0), 1.0, 2.4);
+    hittables.push(Arc::new(prism.build(Arc::new(prism_material))));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(3.0, 3.0, 3.0)));
+    let light = Sphere::new(V3::new(0.0, 14.0, 8.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, Plane, Sphere},
+    material::{DiffuseLight, Lambertian, Material},
+    texture::{Addressing, Checkers, ImageTexture},
+    v3::V3,
+};
+
+use super::Scene;
+
+/// An equirectangular map of the earth, such as NASA's Blue Marble.
+const EARTH_MAP: &str = "earthmap.png";
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 3.0, 12.0);
+    let look_at = V3::new(0.0, 2.0, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 12.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        30.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+/// A material showing the earth map, or checks if it can't be loaded.
+fn earth_material(addressing: Addressing) -> Arc<dyn Material + Send + Sync> {
+    match ImageTexture::load(EARTH_MAP, addressing) {
+        Ok(texture) => return Arc::new(Lambertian::new_from_texture(texture)),
+        Err(error) => {
+            eprintln!("Couldn't load {EARTH_MAP} ({error}), using checks instead");
+            let checks = Checkers::new_from_colours(
+                10.0,
+                Colour::new(0.1, 0.3, 0.7),
+                Colour::new(0.2, 0.6, 0.2),
+            );
+            return Arc::new(Lambertian::new_from_texture(checks));
+        }
+    }
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    // the map tiled across the ground, flipped on every other tile
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        earth_material(Addressing::Mirror),
+    );
+    hittables.push(Arc::new(ground));
+
+    let globe = Sphere::new(
+        V3::new(0.0, 2.0, 0.0),
+        2.0,
+        earth_material(Addressing::Wrap),
+    );
+    hittables.push(Arc::new(globe));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(6.0, 6.0, 6.0)));
+    let light = Sphere::new(V3::new(-6.0, 12.0, 10.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -39,6 +39,8 @@ fn get_camera(aspect_ratio: f64) -> Camera {
         aspect_ratio,
         aperture,
         dist_to_focus,
+        0.0,
+        1.0,
     );
     return camera;
 }
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, Sphere, XZRect},
+    material::{DiffuseLight, Lambertian},
+    texture::{Addressing, Checkers, ImageTexture, UvTransform},
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 1.5, 10.0);
+    let look_at = V3::new(0.0, 0.5, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 10.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        40.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+/// Black and white stripes, one pixel wide: the worst case for aliasing.
+fn stripes() -> ImageTexture {
+    let size = 64;
+    let image = (0..size)
+        .map(|_| {
+            return (0..size)
+                .map(|i| {
+                    let shade = (i % 2) as f64;
+                    return Colour::new(shade, shade, shade);
+                })
+                .collect();
+        })
+        .collect();
+    return ImageTexture::new(image, Addressing::Wrap);
+}
+
+/// Fine patterns stretching away to the horizon, which alias into moiré
+/// without filtering.
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    // the ground is checked, with stripes laid over its left half
+    let checks =
+        Checkers::new_from_colours(6.0, Colour::new(0.9, 0.9, 0.9), Colour::new(0.1, 0.1, 0.1));
+    let ground = Sphere::new(
+        V3::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::new_from_texture(checks)),
+    );
+    hittables.push(Arc::new(ground));
+
+    let striped = UvTransform::new(stripes(), (50.0, 50.0), 30.0, (0.0, 0.0));
+    let stripes = XZRect::new(
+        -200.0,
+        0.0,
+        -200.0,
+        10.0,
+        0.01,
+        Arc::new(Lambertian::new_from_texture(striped)),
+    );
+    hittables.push(Arc::new(stripes));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(4.0, 4.0, 4.0)));
+    let light = Sphere::new(V3::new(0.0, 30.0, 10.0), 20.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -0,0 +1,113 @@
+use std::sync::Arc;
+
+use rand::{prelude::StdRng, Rng, SeedableRng};
+
+use crate::{
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, HittableList, Sphere},
+    instance::{build_instance_bvh, Geometry, Instance},
+    material::{DiffuseLight, Lambertian},
+    utils::scale,
+    v3::{M4, V3},
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world(7, 3000);
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 6.0, 40.0);
+    let look_at = V3::new(0.0, 0.0, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 40.0;
+    let aperture = 0.1;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        30.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_tree() -> Geometry {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    let bark = Arc::new(Lambertian::new(Colour::new(0.3, 0.2, 0.1)));
+    for i in 0..6 {
+        let trunk = Sphere::new(V3::new(0.0, i as f64 * 0.2, 0.0), 0.12, bark.clone());
+        hittables.push(Arc::new(trunk));
+    }
+
+    let leaves = Arc::new(Lambertian::new(Colour::new(0.1, 0.5, 0.1)));
+    let canopy = [
+        (V3::new(0.0, 1.6, 0.0), 0.6),
+        (V3::new(0.35, 1.3, 0.1), 0.45),
+        (V3::new(-0.3, 1.35, -0.15), 0.45),
+        (V3::new(0.0, 2.1, 0.05), 0.4),
+    ];
+    for (centre, radius) in canopy {
+        hittables.push(Arc::new(Sphere::new(centre, radius, leaves.clone())));
+    }
+
+    return Geometry::new(hittables, 0.0, 1.0);
+}
+
+fn make_world(seed: u64, number_of_trees: usize) -> HittableList {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut world = HittableList::new();
+
+    let ground_material = Arc::new(Lambertian::new(Colour::new(0.4, 0.35, 0.2)));
+    let ground_sphere = Sphere::new(V3::new(0.0, -1000.0, 0.0), 1000.0, ground_material);
+    world.add(Arc::new(ground_sphere));
+
+    // every tree shares the same geometry, placed with its own transform
+    let tree = make_tree();
+    let autumn = Arc::new(Lambertian::new(Colour::new(0.8, 0.35, 0.05)));
+    let mut instances = vec![];
+    for _ in 0..number_of_trees {
+        let position = V3::new(
+            scale(rng.gen(), -40.0, 40.0),
+            0.0,
+            scale(rng.gen(), -60.0, 20.0),
+        );
+        let size = scale(rng.gen(), 0.6, 1.6);
+        let transform = M4::translation(position)
+            * M4::rotation_y(scale(rng.gen(), 0.0, 360.0))
+            * M4::scaling(V3::new(size, size, size));
+        let instance = if rng.gen::<f64>() < 0.2 {
+            Instance::new_with_material(&tree, transform, autumn.clone())
+        } else {
+            Instance::new(&tree, transform)
+        };
+        instances.push(instance);
+    }
+    eprintln!("Making instance BVH");
+    world.add(Arc::new(build_instance_bvh(instances, 0.0, 1.0)));
+    eprintln!("Finished making instance BVH");
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(8.0, 8.0, 8.0)));
+    let light = Sphere::new(V3::new(0.0, 60.0, 0.0), 20.0, light_material);
+    world.add(Arc::new(light));
+
+    return world;
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Cuboid, Hittable, Plane, Sphere, XYRect},
+    material::{DiffuseLight, Lambertian},
+    microfacet::RoughDielectric,
+    texture::Checkers,
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 4.0, 14.0);
+    let look_at = V3::new(0.0, 1.0, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 14.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    let checks =
+        Checkers::new_from_colours(1.0, Colour::new(0.8, 0.8, 0.8), Colour::new(0.1, 0.1, 0.1));
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new_from_texture(checks)),
+    );
+    hittables.push(Arc::new(ground));
+
+    // clear glass, getting rougher from left to right
+    for (i, roughness) in [0.0, 0.15, 0.4].into_iter().enumerate() {
+        let glass = RoughDielectric::new(1.5, roughness);
+        let centre = V3::new(-5.0 + 2.5 * i as f64, 1.0, 0.0);
+        hittables.push(Arc::new(Sphere::new(centre, 1.0, Arc::new(glass))));
+    }
+
+    // thick green glass, darker where the light goes further through it
+    let green = RoughDielectric::new_coloured(1.5, 0.0, Colour::new(0.3, 0.8, 0.4), 1.0);
+    let green_sphere = Sphere::new(V3::new(2.5, 1.0, 0.0), 1.0, Arc::new(green));
+    hittables.push(Arc::new(green_sphere));
+    let amber = RoughDielectric::new_coloured(1.5, 0.1, Colour::new(0.9, 0.5, 0.1), 1.0);
+    let amber_block = Cuboid::new(
+        V3::new(4.2, 0.0, -0.8),
+        V3::new(5.8, 2.0, 0.8),
+        Arc::new(amber),
+    );
+    hittables.push(Arc::new(amber_block));
+
+    // a frosted blue window in front of the spheres
+    let window = RoughDielectric::new_thin_walled(1.5, 0.2, Colour::new(0.6, 0.8, 1.0));
+    let pane = XYRect::new(-6.2, -3.3, 0.0, 1.4, 2.0, Arc::new(window));
+    hittables.push(Arc::new(pane));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(8.0, 8.0, 7.0)));
+    let light = Sphere::new(V3::new(-4.0, 14.0, 8.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+    let sky_material = Arc::new(DiffuseLight::new(Colour::new(0.3, 0.35, 0.45)));
+    let sky = Sphere::new(V3::new(0.0, 0.0, 0.0), 100.0, sky_material);
+    hittables.push(Arc::new(sky));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use rand::{prelude::StdRng, Rng, SeedableRng};
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    curve::{Curve, CurveShape, RootToTip},
+    hittable::{Hittable, Plane, Sphere},
+    material::{DiffuseLight, Lambertian},
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 2.5, 9.0);
+    let look_at = V3::new(0.0, 0.6, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 9.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+    let mut rng = StdRng::seed_from_u64(36);
+
+    let ground_material = Arc::new(Lambertian::new(Colour::new(0.25, 0.18, 0.1)));
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        ground_material,
+    );
+    hittables.push(Arc::new(ground));
+
+    // blades of grass, dark at the root and pale at the tip, bending over
+    // in random directions
+    let grass_texture =
+        RootToTip::new_from_colours(Colour::new(0.05, 0.2, 0.02), Colour::new(0.6, 0.75, 0.3));
+    let grass_material = Arc::new(Lambertian::new_from_texture(grass_texture));
+    for _ in 0..4000 {
+        let root = V3::new(rng.gen_range(-5.0..5.0), 0.0, rng.gen_range(-4.0..3.0));
+        let height = rng.gen_range(0.5..1.2);
+        let angle: f64 = rng.gen_range(0.0..2.0 * std::f64::consts::PI);
+        let lean = rng.gen_range(0.0..0.5) * height;
+        let direction = V3::new(f64::cos(angle), 0.0, f64::sin(angle));
+        let control_points = [
+            root,
+            root + V3::new(0.0, 0.4 * height, 0.0),
+            root + V3::new(0.0, 0.8 * height, 0.0) + 0.5 * lean * direction,
+            root + V3::new(0.0, height, 0.0) + lean * direction,
+        ];
+        let blade = Curve::new(
+            control_points,
+            0.04,
+            0.0,
+            CurveShape::Ribbon,
+            grass_material.clone(),
+        );
+        hittables.push(Arc::new(blade));
+    }
+
+    // a thick stalk arching over the grass
+    let stalk_material = Arc::new(Lambertian::new(Colour::new(0.6, 0.3, 0.1)));
+    let stalk = Curve::new(
+        [
+            V3::new(-2.0, 0.0, 1.0),
+            V3::new(-1.5, 2.5, 1.0),
+            V3::new(1.5, 2.5, 1.0),
+            V3::new(2.0, 0.0, 1.0),
+        ],
+        0.2,
+        0.05,
+        CurveShape::Cylinder,
+        stalk_material,
+    );
+    hittables.push(Arc::new(stalk));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(8.0, 8.0, 7.0)));
+    let light = Sphere::new(V3::new(-4.0, 12.0, 6.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, Plane, Sphere},
+    material::{DiffuseLight, Lambertian, Metal},
+    mesh::Mesh,
+    texture::Checkers,
+    transform::Transformed,
+    v3::V3,
+};
+
+use super::Scene;
+
+/// A model to use instead of the octahedron, if there is one. It should fit
+/// in a sphere of radius 1.2 around the origin.
+const MODEL: &str = "model.obj";
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 4.0, 14.0);
+    let look_at = V3::new(0.0, 1.2, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 14.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        30.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+/// A low poly octahedron, centred on `centre`, with spherical uvs.
+fn octahedron(centre: V3, radius: f64) -> Mesh {
+    let directions = [
+        V3::new(1.0, 0.0, 0.0),
+        V3::new(-1.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        V3::new(0.0, -1.0, 0.0),
+        V3::new(0.0, 0.0, 1.0),
+        V3::new(0.0, 0.0, -1.0),
+    ];
+    let positions = directions.iter().map(|&d| centre + radius * d).collect();
+    let uvs = directions
+        .iter()
+        .map(|d| {
+            let u = (f64::atan2(-d.z, d.x) + std::f64::consts::PI) / (2.0 * std::f64::consts::PI);
+            let v = f64::acos(-d.y) / std::f64::consts::PI;
+            return (u, v);
+        })
+        .collect();
+    let faces = vec![
+        [0, 2, 4],
+        [4, 2, 1],
+        [1, 2, 5],
+        [5, 2, 0],
+        [4, 3, 0],
+        [1, 3, 4],
+        [5, 3, 1],
+        [0, 3, 5],
+    ];
+    return Mesh::new(positions, uvs, faces);
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    let ground_material = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        ground_material,
+    );
+    hittables.push(Arc::new(ground));
+
+    // the same mesh, as it was modelled, smoothed, and smoothed with bumps
+    let model = match Mesh::load_obj(MODEL) {
+        Ok(mesh) => mesh,
+        Err(error) => {
+            eprintln!("Couldn't load {MODEL} ({error}), using an octahedron instead");
+            octahedron(V3::new(0.0, 0.0, 0.0), 1.2)
+        }
+    };
+    let material = Arc::new(Lambertian::new(Colour::new(0.7, 0.3, 0.1)));
+    let faceted = Arc::new(model.clone().build(material.clone()));
+    hittables.push(Arc::new(Transformed::translate(
+        faceted,
+        V3::new(-3.0, 1.2, 0.0),
+    )));
+
+    let smooth = Arc::new(model.subdivide(4).build(material));
+    hittables.push(Arc::new(Transformed::translate(
+        smooth,
+        V3::new(0.0, 1.2, 0.0),
+    )));
+
+    let bumps =
+        Checkers::new_from_colours(8.0, Colour::new(1.0, 1.0, 1.0), Colour::new(0.0, 0.0, 0.0));
+    let bumpy = model.subdivide(5).displace(&bumps, 0.1);
+    let metal = Arc::new(Metal::new(Colour::new(0.8, 0.8, 0.8), 0.2));
+    hittables.push(Arc::new(Transformed::translate(
+        Arc::new(bumpy.build(metal)),
+        V3::new(3.0, 1.2, 0.0),
+    )));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(6.0, 6.0, 6.0)));
+    let light = Sphere::new(V3::new(0.0, 12.0, 4.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, Plane, Sphere},
+    material::{Dielectric, DiffuseLight, Lambertian, Metal, MixMaterial},
+    microfacet::Principled,
+    texture::{Checkers, ColourRamp, Noise},
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 4.0, 13.0);
+    let look_at = V3::new(0.0, 1.2, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 13.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    // polished tiles alternating with matte ones
+    let polished = Arc::new(Principled::new(Colour::new(0.2, 0.2, 0.25), 0.0, 0.05, 0.5));
+    let matte = Arc::new(Lambertian::new(Colour::new(0.7, 0.7, 0.65)));
+    let tiles =
+        Checkers::new_from_colours(1.0, Colour::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+    let floor_material = MixMaterial::new_with_mask(polished, matte, tiles);
+    let floor = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        Arc::new(floor_material),
+    );
+    hittables.push(Arc::new(floor));
+
+    // rust eating into steel, with a sharp edge between them
+    let steel = Arc::new(Metal::new(Colour::new(0.8, 0.8, 0.85), 0.05));
+    let rust = Arc::new(Lambertian::new(Colour::new(0.45, 0.2, 0.08)));
+    let rust_patches = Noise::new(
+        7,
+        1.5,
+        5,
+        ColourRamp::new(vec![
+            (0.0, Colour::new(0.0, 0.0, 0.0)),
+            (0.45, Colour::new(0.0, 0.0, 0.0)),
+            (0.55, Colour::new(1.0, 1.0, 1.0)),
+            (1.0, Colour::new(1.0, 1.0, 1.0)),
+        ]),
+    );
+    let rusty_steel = MixMaterial::new_with_mask(steel, rust, rust_patches);
+    let rusty_sphere = Sphere::new(V3::new(-2.5, 1.2, 0.0), 1.2, Arc::new(rusty_steel));
+    hittables.push(Arc::new(rusty_sphere));
+
+    // half glass and half white paint everywhere, like frosted glass
+    let glass = Arc::new(Dielectric::new(1.5));
+    let paint = Arc::new(Lambertian::new(Colour::new(0.9, 0.9, 0.9)));
+    let milky = MixMaterial::new(glass, paint, 0.3);
+    let milky_sphere = Sphere::new(V3::new(0.5, 1.2, 0.0), 1.2, Arc::new(milky));
+    hittables.push(Arc::new(milky_sphere));
+
+    // a lamp whose shade only glows between stripes of metal
+    let glow = Arc::new(DiffuseLight::new(Colour::new(3.0, 2.0, 1.0)));
+    let brass = Arc::new(Metal::new(Colour::new(0.8, 0.6, 0.3), 0.2));
+    let stripes =
+        Checkers::new_from_colours(4.0, Colour::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0));
+    let lamp_material = MixMaterial::new_with_mask(glow, brass, stripes);
+    let lamp = Sphere::new(V3::new(3.5, 1.2, 0.0), 1.2, Arc::new(lamp_material));
+    hittables.push(Arc::new(lamp));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(8.0, 8.0, 7.0)));
+    let light = Sphere::new(V3::new(-4.0, 14.0, 8.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+    let sky_material = Arc::new(DiffuseLight::new(Colour::new(0.3, 0.35, 0.45)));
+    let sky = Sphere::new(V3::new(0.0, 0.0, 0.0), 100.0, sky_material);
+    hittables.push(Arc::new(sky));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -1,15 +1,98 @@
 use crate::{camera::Camera, hittable::Hittable};
 
+pub mod bouncing;
+pub mod bumps;
+pub mod cloud;
+pub mod coatings;
+pub mod composition;
+pub mod cornell_smoke;
+pub mod csg;
+pub mod cutout;
+pub mod dispersion;
+pub mod earth;
 pub mod example;
 pub mod example_bvh;
+pub mod filtering;
+pub mod forest;
+pub mod glass;
+pub mod grass;
 pub mod light;
+pub mod mesh;
+pub mod mixed;
 pub mod nts;
+pub mod point_cloud;
+pub mod primitives;
+pub mod principled;
+pub mod procedural;
+pub mod sdf;
+pub mod subsurface;
+pub mod terrain;
 
 pub enum SceneConfig {
     Example,
     ExampleBVH,
     NTS,
     Light,
+    Forest,
+    Bouncing,
+    CornellSmoke,
+    Cloud,
+    Primitives,
+    Csg,
+    Sdf,
+    Terrain,
+    Mesh,
+    Grass,
+    PointCloud,
+    Earth,
+    Procedural,
+    Composition,
+    Filtering,
+    Bumps,
+    Cutout,
+    Principled,
+    Glass,
+    Dispersion,
+    Coatings,
+    Subsurface,
+    Mixed,
+}
+
+impl SceneConfig {
+    /// The scene with the same name as its module, such as `cornell_smoke`.
+    pub fn from_name(name: &str) -> Option<SceneConfig> {
+        let scene = match name {
+            "example" => SceneConfig::Example,
+            "example_bvh" => SceneConfig::ExampleBVH,
+            "nts" => SceneConfig::NTS,
+            "light" => SceneConfig::Light,
+            "forest" => SceneConfig::Forest,
+            "bouncing" => SceneConfig::Bouncing,
+            "cornell_smoke" => SceneConfig::CornellSmoke,
+            "cloud" => SceneConfig::Cloud,
+            "primitives" => SceneConfig::Primitives,
+            "csg" => SceneConfig::Csg,
+            "sdf" => SceneConfig::Sdf,
+            "terrain" => SceneConfig::Terrain,
+            "mesh" => SceneConfig::Mesh,
+            "grass" => SceneConfig::Grass,
+            "point_cloud" => SceneConfig::PointCloud,
+            "earth" => SceneConfig::Earth,
+            "procedural" => SceneConfig::Procedural,
+            "composition" => SceneConfig::Composition,
+            "filtering" => SceneConfig::Filtering,
+            "bumps" => SceneConfig::Bumps,
+            "cutout" => SceneConfig::Cutout,
+            "principled" => SceneConfig::Principled,
+            "glass" => SceneConfig::Glass,
+            "dispersion" => SceneConfig::Dispersion,
+            "coatings" => SceneConfig::Coatings,
+            "subsurface" => SceneConfig::Subsurface,
+            "mixed" => SceneConfig::Mixed,
+            _ => return None,
+        };
+        return Some(scene);
+    }
 }
 
 pub struct Scene {
@@ -24,5 +107,46 @@ pub fn get_scene(scene_config: SceneConfig) -> Scene {
         SceneConfig::ExampleBVH => example_bvh::scene(),
         SceneConfig::NTS => nts::scene(),
         SceneConfig::Light => light::scene(),
+        SceneConfig::Forest => forest::scene(),
+        SceneConfig::Bouncing => bouncing::scene(),
+        SceneConfig::CornellSmoke => cornell_smoke::scene(),
+        SceneConfig::Cloud => cloud::scene(),
+        SceneConfig::Primitives => primitives::scene(),
+        SceneConfig::Csg => csg::scene(),
+        SceneConfig::Sdf => sdf::scene(),
+        SceneConfig::Terrain => terrain::scene(),
+        SceneConfig::Mesh => mesh::scene(),
+        SceneConfig::Grass => grass::scene(),
+        SceneConfig::PointCloud => point_cloud::scene(),
+        SceneConfig::Earth => earth::scene(),
+        SceneConfig::Procedural => procedural::scene(),
+        SceneConfig::Composition => composition::scene(),
+        SceneConfig::Filtering => filtering::scene(),
+        SceneConfig::Bumps => bumps::scene(),
+        SceneConfig::Cutout => cutout::scene(),
+        SceneConfig::Principled => principled::scene(),
+        SceneConfig::Glass => glass::scene(),
+        SceneConfig::Dispersion => dispersion::scene(),
+        SceneConfig::Coatings => coatings::scene(),
+        SceneConfig::Subsurface => subsurface::scene(),
+        SceneConfig::Mixed => mixed::scene(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scenes_are_named_after_their_modules() {
+        assert!(matches!(
+            SceneConfig::from_name("cornell_smoke"),
+            Some(SceneConfig::CornellSmoke)
+        ));
+        assert!(matches!(
+            SceneConfig::from_name("nts"),
+            Some(SceneConfig::NTS)
+        ));
+        assert!(SceneConfig::from_name("Cornell Smoke").is_none());
     }
 }
@@ -5,9 +5,8 @@ use rand::{prelude::StdRng, Rng, SeedableRng};
 use crate::{
     camera::Camera,
     colour::Colour,
-    hittable::{HittableList, Sphere, XYRect},
+    hittable::{HittableList, Sphere},
     material::{Dielectric, DiffuseLight, Lambertian, Material, Metal},
-    texture::Checkers,
     utils::scale,
     v3::V3,
 };
@@ -16,7 +15,7 @@ use super::Scene;
 
 pub fn scene() -> Scene {
     let world = make_world(3, 2.0, true);
-    let aspect_ratio = 16.0 / 16.0;
+    let aspect_ratio = 1.0;
     let camera = get_camera(aspect_ratio);
     return Scene {
         aspect_ratio,
@@ -41,11 +40,13 @@ fn get_camera(aspect_ratio: f64) -> Camera {
         aspect_ratio,
         aperture,
         dist_to_focus,
+        0.0,
+        1.0,
     );
     return camera;
 }
 
-fn make_world(seed: u64, checkers_scale: f64, include_small_spheres: bool) -> HittableList {
+fn make_world(seed: u64, _checkers_scale: f64, include_small_spheres: bool) -> HittableList {
     let mut rng = StdRng::seed_from_u64(seed);
 
     let mut world = HittableList::new();
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use rand::{prelude::StdRng, Rng, SeedableRng};
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, Sphere},
+    material::DiffuseLight,
+    noise::Perlin,
+    point_cloud::{PointCloud, PointShape},
+    v3::{unit_vector, V3},
+};
+
+use super::Scene;
+
+/// A scan to add to the scene, in the format `PointCloud::load_xyz` reads,
+/// if there is one.
+const SCAN: &str = "scan.xyz";
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 6.0, 14.0);
+    let look_at = V3::new(0.0, 0.5, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 14.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+    let mut rng = StdRng::seed_from_u64(37);
+
+    // a scan of rolling ground, coloured by height, drawn as splats
+    let perlin = Perlin::new(37);
+    let mut positions = vec![];
+    let mut colours = vec![];
+    for _ in 0..400_000 {
+        let x = rng.gen_range(-8.0..8.0);
+        let z = rng.gen_range(-6.0..4.0);
+        let height = 1.5 * perlin.turbulence(V3::new(0.3 * x, 0.5, 0.3 * z), 4);
+        let blend = f64::clamp(height / 1.2, 0.0, 1.0);
+        positions.push(V3::new(x, height, z));
+        colours.push(Colour::new(
+            0.2 + 0.6 * blend,
+            0.5 - 0.1 * blend,
+            0.2 + 0.1 * blend,
+        ));
+    }
+    let ground = PointCloud::new(positions, colours, None, 0.03, PointShape::Disk);
+    hittables.push(Arc::new(ground));
+
+    // a ball of points with normals, drawn as oriented disks
+    let centre = V3::new(-2.0, 2.2, 0.0);
+    let mut positions = vec![];
+    let mut colours = vec![];
+    let mut normals = vec![];
+    for _ in 0..60_000 {
+        let normal = unit_vector(V3::new(
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        ));
+        positions.push(centre + 1.2 * normal);
+        colours.push(Colour::new(
+            0.5 + 0.5 * normal.x,
+            0.5 + 0.5 * normal.y,
+            0.5 + 0.5 * normal.z,
+        ));
+        normals.push(normal);
+    }
+    let ball = PointCloud::new(positions, colours, Some(normals), 0.02, PointShape::Disk);
+    hittables.push(Arc::new(ball));
+
+    // a sparse helix of spheres
+    let mut positions = vec![];
+    let mut colours = vec![];
+    for i in 0..400 {
+        let angle = i as f64 * 0.1;
+        positions.push(V3::new(
+            2.5 + f64::cos(angle),
+            1.0 + 0.008 * i as f64,
+            f64::sin(angle),
+        ));
+        colours.push(Colour::new(0.9, 0.8, 0.2));
+    }
+    let helix = PointCloud::new(positions, colours, None, 0.08, PointShape::Sphere);
+    hittables.push(Arc::new(helix));
+
+    match PointCloud::load_xyz(SCAN, 0.02, PointShape::Disk) {
+        Ok(scan) => hittables.push(Arc::new(scan)),
+        Err(error) => eprintln!("Couldn't load {SCAN} ({error}), leaving it out"),
+    }
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(8.0, 8.0, 8.0)));
+    let light = Sphere::new(V3::new(0.0, 14.0, 6.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Cone, Cylinder, Disk, Hittable, Plane, Sphere, Torus},
+    material::{Dielectric, DiffuseLight, Lambertian, Metal},
+    texture::Checkers,
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 4.0, 18.0);
+    let look_at = V3::new(0.0, 1.0, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 18.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        30.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    // the ground plane has no bounding box, so the BVH keeps it to one side
+    let ground_material = Arc::new(Lambertian::new_from_texture(Checkers::new_from_colours(
+        3.0,
+        Colour::new(0.2, 0.3, 0.1),
+        Colour::new(0.9, 0.9, 0.9),
+    )));
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        ground_material,
+    );
+    hittables.push(Arc::new(ground));
+
+    let red = Arc::new(Lambertian::new(Colour::new(0.7, 0.1, 0.1)));
+    let cylinder = Cylinder::new(V3::new(-4.5, 0.0, 0.0), 1.0, 2.5, red);
+    hittables.push(Arc::new(cylinder));
+
+    let gold = Arc::new(Metal::new(Colour::new(0.8, 0.6, 0.2), 0.1));
+    let cone = Cone::new(V3::new(-1.5, 0.0, 0.0), 1.0, 2.5, gold);
+    hittables.push(Arc::new(cone));
+
+    let glass = Arc::new(Dielectric::new(1.5));
+    let torus = Torus::new(V3::new(1.5, 0.5, 0.0), 1.0, 0.5, glass);
+    hittables.push(Arc::new(torus));
+
+    let blue = Arc::new(Lambertian::new(Colour::new(0.1, 0.2, 0.7)));
+    let disk = Disk::new(V3::new(4.5, 1.2, 0.0), V3::new(0.3, 0.2, 1.0), 1.2, blue);
+    hittables.push(Arc::new(disk));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(6.0, 6.0, 6.0)));
+    let light = Sphere::new(V3::new(0.0, 12.0, 4.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
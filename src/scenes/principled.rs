@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, Plane, Sphere},
+    material::DiffuseLight,
+    microfacet::Principled,
+    texture::{Addressing, Checkers, ImageTexture, SolidColour},
+    v3::V3,
+};
+
+use super::Scene;
+
+/// A base colour texture for the floor tiles, as a glTF asset would give it.
+const FLOOR_BASE_COLOUR: &str = "floor_base_colour.png";
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 5.0, 14.0);
+    let look_at = V3::new(0.0, 1.0, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 14.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    // a floor of alternating polished and rough tiles
+    let floor_roughness = Checkers::new(
+        1.0,
+        SolidColour::new(Colour::new(0.0, 0.1, 0.0)),
+        SolidColour::new(Colour::new(0.0, 0.7, 0.0)),
+    );
+    let white = Colour::new(1.0, 1.0, 1.0);
+    let floor_base_colour = match ImageTexture::load(FLOOR_BASE_COLOUR, Addressing::Wrap) {
+        Ok(texture) => texture,
+        Err(error) => {
+            eprintln!("Couldn't load {FLOOR_BASE_COLOUR} ({error}), using plain grey instead");
+            ImageTexture::new(vec![vec![white]], Addressing::Wrap)
+        }
+    };
+    let floor_material = Principled::new_gltf(
+        Colour::new(0.3, 0.3, 0.35),
+        floor_base_colour,
+        0.0,
+        1.0,
+        floor_roughness,
+    );
+    let floor = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        Arc::new(floor_material),
+    );
+    hittables.push(Arc::new(floor));
+
+    // gold at the back and red plastic at the front, each getting rougher
+    // from left to right
+    for i in 0..5 {
+        let roughness = i as f64 / 4.0;
+        let x = 2.4 * (i as f64 - 2.0);
+        let gold = Principled::new(Colour::new(1.0, 0.78, 0.34), 1.0, roughness, 0.5);
+        let gold_sphere = Sphere::new(V3::new(x, 1.0, -1.5), 1.0, Arc::new(gold));
+        hittables.push(Arc::new(gold_sphere));
+        let plastic = Principled::new(Colour::new(0.7, 0.05, 0.05), 0.0, roughness, 0.5);
+        let plastic_sphere = Sphere::new(V3::new(x, 1.0, 1.5), 1.0, Arc::new(plastic));
+        hittables.push(Arc::new(plastic_sphere));
+    }
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(8.0, 8.0, 7.0)));
+    let light = Sphere::new(V3::new(-6.0, 14.0, 8.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+    let sky_material = Arc::new(DiffuseLight::new(Colour::new(0.2, 0.25, 0.35)));
+    let sky = Sphere::new(V3::new(0.0, 0.0, 0.0), 100.0, sky_material);
+    hittables.push(Arc::new(sky));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Cylinder, Hittable, Plane, Sphere},
+    material::{DiffuseLight, Lambertian},
+    texture::{ColourRamp, Marble, Noise, Wood},
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 3.0, 12.0);
+    let look_at = V3::new(0.0, 1.2, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 12.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    let ground_ramp =
+        ColourRamp::new_from_colours(Colour::new(0.15, 0.15, 0.12), Colour::new(0.6, 0.55, 0.45));
+    let ground_texture = Noise::new(1, 2.0, 6, ground_ramp);
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        Arc::new(Lambertian::new_from_texture(ground_texture)),
+    );
+    hittables.push(Arc::new(ground));
+
+    let marble_ramp = ColourRamp::new(vec![
+        (0.0, Colour::new(0.9, 0.9, 0.88)),
+        (0.7, Colour::new(0.8, 0.8, 0.78)),
+        (1.0, Colour::new(0.2, 0.25, 0.3)),
+    ]);
+    let marble = Marble::new(2, 4.0, 7, marble_ramp);
+    let marble_sphere = Sphere::new(
+        V3::new(-3.0, 1.2, 0.0),
+        1.2,
+        Arc::new(Lambertian::new_from_texture(marble)),
+    );
+    hittables.push(Arc::new(marble_sphere));
+
+    let wood_ramp =
+        ColourRamp::new_from_colours(Colour::new(0.75, 0.5, 0.25), Colour::new(0.35, 0.18, 0.07));
+    let wood = Wood::new(3, 6.0, 4, wood_ramp);
+    let log = Cylinder::new(
+        V3::new(0.0, 0.0, 0.0),
+        1.0,
+        2.0,
+        Arc::new(Lambertian::new_from_texture(wood)),
+    );
+    hittables.push(Arc::new(log));
+
+    // glowing lava, lit by its own texture
+    let lava_ramp = ColourRamp::new(vec![
+        (0.3, Colour::new(0.0, 0.0, 0.0)),
+        (0.5, Colour::new(2.0, 0.3, 0.0)),
+        (0.7, Colour::new(6.0, 4.0, 0.5)),
+    ]);
+    let lava = Noise::new(4, 3.0, 5, lava_ramp);
+    let lava_sphere = Sphere::new(
+        V3::new(3.0, 1.2, 0.0),
+        1.2,
+        Arc::new(DiffuseLight::new_from_texture(lava)),
+    );
+    hittables.push(Arc::new(lava_sphere));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(6.0, 6.0, 6.0)));
+    let light = Sphere::new(V3::new(-4.0, 12.0, 8.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
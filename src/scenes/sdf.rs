@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Hittable, Plane, Sphere},
+    material::{Dielectric, DiffuseLight, Lambertian, Metal},
+    sdf::{Repeat, Sdf, SdfBox, SdfHittable, SdfSphere, SmoothUnion},
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 6.0, 14.0);
+    let look_at = V3::new(0.0, 2.0, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 14.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        30.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    let ground_material = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        ground_material,
+    );
+    hittables.push(Arc::new(ground));
+
+    // two spheres melted together
+    let blob = SmoothUnion::new(
+        SdfSphere::new(V3::new(-0.6, 1.2, 0.0), 1.0),
+        SdfSphere::new(V3::new(0.8, 1.6, 0.3), 0.7),
+        0.6,
+    );
+    let blob_bounds = AABB::new(V3::new(-1.8, 0.0, -1.2), V3::new(1.8, 2.6, 1.3));
+    let blob_material = Arc::new(Metal::new(Colour::new(0.8, 0.6, 0.2), 0.1));
+    hittables.push(Arc::new(SdfHittable::new(blob, blob_bounds, blob_material)));
+
+    // a row of rounded boxes from a single repeated one, cut off by the bounds
+    let boxes = Repeat::new(
+        SdfBox::new_rounded(V3::new(0.0, 0.0, 0.0), V3::new(0.4, 0.4, 0.4), 0.1),
+        V3::new(1.2, 100.0, 100.0),
+    );
+    let boxes_bounds = AABB::new(V3::new(-4.2, 0.0, -3.4), V3::new(4.2, 0.8, -2.6));
+    let boxes_material = Arc::new(Lambertian::new(Colour::new(0.1, 0.3, 0.7)));
+    let boxes = move |p: V3| boxes.distance(p - V3::new(0.0, 0.4, -3.0));
+    hittables.push(Arc::new(SdfHittable::new(
+        boxes,
+        boxes_bounds,
+        boxes_material,
+    )));
+
+    // any closure can be used as a distance function
+    let torus = |p: V3| {
+        let ring = f64::sqrt(p.x * p.x + p.z * p.z) - 0.8;
+        return f64::sqrt(ring * ring + p.y * p.y) - 0.3;
+    };
+    let torus = move |p: V3| torus(p - V3::new(3.0, 0.3, 1.0));
+    let torus_bounds = AABB::new(V3::new(1.9, 0.0, -0.1), V3::new(4.1, 0.6, 2.1));
+    let glass = Arc::new(Dielectric::new(1.5));
+    hittables.push(Arc::new(SdfHittable::new(torus, torus_bounds, glass)));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(6.0, 6.0, 6.0)));
+    let light = Sphere::new(V3::new(0.0, 12.0, 4.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
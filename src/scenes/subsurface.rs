@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use crate::{
+    bvh::BVHNode,
+    camera::Camera,
+    colour::Colour,
+    hittable::{Cuboid, Cylinder, Hittable, Plane, Sphere},
+    material::{DiffuseLight, Lambertian},
+    subsurface::Subsurface,
+    v3::V3,
+};
+
+use super::Scene;
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 3.0, 13.0);
+    let look_at = V3::new(0.0, 1.0, 0.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 13.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        35.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> BVHNode {
+    let mut hittables: Vec<Arc<dyn Hittable + Send + Sync>> = vec![];
+
+    let ground_material = Arc::new(Lambertian::new(Colour::new(0.4, 0.4, 0.4)));
+    let ground = Plane::new(
+        V3::new(0.0, 0.0, 0.0),
+        V3::new(0.0, 1.0, 0.0),
+        ground_material,
+    );
+    hittables.push(Arc::new(ground));
+
+    // a plain diffuse ball to compare against
+    let plaster = Arc::new(Lambertian::new(Colour::new(0.9, 0.6, 0.5)));
+    hittables.push(Arc::new(Sphere::new(V3::new(-4.5, 1.0, 0.0), 1.0, plaster)));
+
+    // skin, where red light travels furthest
+    let skin = Subsurface::new(Colour::new(0.9, 0.6, 0.5), Colour::new(0.5, 0.35, 0.3), 1.4);
+    hittables.push(Arc::new(Sphere::new(
+        V3::new(-2.0, 1.0, 0.0),
+        1.0,
+        Arc::new(skin),
+    )));
+
+    // a wax candle
+    let wax = Subsurface::new(
+        Colour::new(0.95, 0.85, 0.6),
+        Colour::new(0.5, 0.45, 0.4),
+        1.45,
+    );
+    let candle = Cylinder::new(V3::new(0.5, 0.0, 0.0), 0.6, 2.2, Arc::new(wax));
+    hittables.push(Arc::new(candle));
+
+    // jade and marble
+    let jade = Subsurface::new(Colour::new(0.3, 0.8, 0.5), Colour::new(0.3, 0.6, 0.4), 1.6);
+    hittables.push(Arc::new(Sphere::new(
+        V3::new(3.0, 1.0, 0.0),
+        1.0,
+        Arc::new(jade),
+    )));
+    let marble = Subsurface::new(Colour::new(0.9, 0.9, 0.88), Colour::new(0.2, 0.2, 0.2), 1.5);
+    let block = Cuboid::new(
+        V3::new(4.6, 0.0, -0.8),
+        V3::new(6.0, 1.4, 0.6),
+        Arc::new(marble),
+    );
+    hittables.push(Arc::new(block));
+
+    // a light behind, to shine through the thin parts
+    let back_light_material = Arc::new(DiffuseLight::new(Colour::new(6.0, 6.0, 6.0)));
+    let back_light = Sphere::new(V3::new(0.0, 1.5, -6.0), 1.0, back_light_material);
+    hittables.push(Arc::new(back_light));
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(5.0, 5.0, 4.5)));
+    let light = Sphere::new(V3::new(-4.0, 14.0, 8.0), 5.0, light_material);
+    hittables.push(Arc::new(light));
+
+    return BVHNode::new(hittables, 0.0, 1.0);
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use crate::{
+    camera::Camera,
+    colour::Colour,
+    heightfield::{HeightGrid, HeightTexture, Heightfield, SlopeTexture},
+    hittable::{HittableList, Sphere},
+    material::{DiffuseLight, Lambertian},
+    texture::SolidColour,
+    v3::V3,
+};
+
+use super::Scene;
+
+/// A greyscale height map to use instead of the noise, if there is one.
+const HEIGHT_MAP: &str = "heightmap.png";
+
+pub fn scene() -> Scene {
+    let world = make_world();
+    let aspect_ratio = 16.0 / 9.0;
+    let camera = get_camera(aspect_ratio);
+    return Scene {
+        aspect_ratio,
+        world: Box::new(world),
+        camera,
+    };
+}
+
+fn get_camera(aspect_ratio: f64) -> Camera {
+    // camera
+    let look_from = V3::new(0.0, 12.0, 22.0);
+    let look_at = V3::new(0.0, 2.0, -8.0);
+    let view_up = V3::new(0.0, 1.0, 0.0);
+    let dist_to_focus = 30.0;
+    let aperture = 0.0;
+
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        view_up,
+        45.0,
+        aspect_ratio,
+        aperture,
+        dist_to_focus,
+        0.0,
+        1.0,
+    );
+    return camera;
+}
+
+fn make_world() -> HittableList {
+    let mut world = HittableList::new();
+
+    let grid = match HeightGrid::load_image(HEIGHT_MAP) {
+        Ok(grid) => grid,
+        Err(error) => {
+            eprintln!("Couldn't load {HEIGHT_MAP} ({error}), using noise instead");
+            HeightGrid::from_noise(257, 257, 5, 4.0)
+        }
+    };
+    let grid = Arc::new(grid);
+    let minimum = V3::new(-20.0, 0.0, -30.0);
+    let size = V3::new(40.0, 8.0, 40.0);
+
+    // grass fading to snow with height, with bare rock on the steep parts
+    let ground = HeightTexture::new_from_colours(
+        3.0,
+        5.0,
+        Colour::new(0.2, 0.45, 0.1),
+        Colour::new(0.9, 0.9, 0.95),
+    );
+    let rock = SolidColour::new(Colour::new(0.35, 0.3, 0.25));
+    let texture = SlopeTexture::new(grid.clone(), size, ground, rock, 0.5, 0.7);
+    let terrain_material = Arc::new(Lambertian::new_from_texture(texture));
+    let terrain = Heightfield::new(grid, minimum, size, terrain_material);
+    world.add(Arc::new(terrain));
+
+    // lights
+    let light_material = Arc::new(DiffuseLight::new(Colour::new(15.0, 14.0, 12.0)));
+    let light = Sphere::new(V3::new(-20.0, 40.0, -10.0), 12.0, light_material);
+    world.add(Arc::new(light));
+
+    return world;
+}
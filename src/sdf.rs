@@ -0,0 +1,321 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    hittable::{HitRecord, Hittable},
+    material::Material,
+    ray::Ray,
+    v3::{unit_vector, V3},
+};
+
+/// A signed distance function: the distance from `p` to the nearest point of
+/// a surface, negative inside it. It may underestimate the distance, but
+/// must never overestimate it.
+pub trait Sdf {
+    fn distance(&self, p: V3) -> f64;
+}
+
+impl<F> Sdf for F
+where
+    F: Fn(V3) -> f64,
+{
+    fn distance(&self, p: V3) -> f64 {
+        return self(p);
+    }
+}
+
+/// A surface given by a signed distance function, found by sphere tracing:
+/// stepping along the ray by the distance to the nearest surface until it is
+/// reached.
+pub struct SdfHittable {
+    sdf: Arc<dyn Sdf + Send + Sync>,
+    /// Must contain the whole surface, as rays are only traced within it
+    bounds: AABB,
+    material: Arc<dyn Material + Send + Sync>,
+}
+
+const MAX_STEPS: usize = 512;
+const EPSILON: f64 = 1e-5;
+
+impl SdfHittable {
+    pub fn new<S: Sdf + Send + Sync + 'static>(
+        sdf: S,
+        bounds: AABB,
+        material: Arc<dyn Material + Send + Sync>,
+    ) -> Self {
+        return SdfHittable {
+            sdf: Arc::new(sdf),
+            bounds,
+            material,
+        };
+    }
+
+    /// The outward normal, from the gradient of the distance function.
+    fn normal(&self, p: V3) -> V3 {
+        let h = 1e-5;
+        let d = |offset: V3| self.sdf.distance(p + offset) - self.sdf.distance(p - offset);
+        return unit_vector(V3::new(
+            d(V3::new(h, 0.0, 0.0)),
+            d(V3::new(0.0, h, 0.0)),
+            d(V3::new(0.0, 0.0, h)),
+        ));
+    }
+}
+
+impl Hittable for SdfHittable {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t_start, t_end) = self.bounds.hit_interval(ray, t_min, t_max)?;
+        let ray_length = ray.direction.length();
+
+        let mut t = t_start;
+        // a ray leaving the surface starts right on it; move off the surface
+        // before looking for the next one. The bounds may fit the surface
+        // tightly, so a surface where the ray enters them is a real hit.
+        let mut leaving_surface = f64::abs(self.sdf.distance(ray.at(t_min))) < EPSILON;
+        for _ in 0..MAX_STEPS {
+            if t > t_end {
+                return None;
+            }
+            let distance = f64::abs(self.sdf.distance(ray.at(t)));
+            if distance < EPSILON {
+                if !leaving_surface {
+                    let point = ray.at(t);
+                    let outward_normal = self.normal(point);
+                    let hit_record = HitRecord::new(
+                        t,
+                        0.0,
+                        0.0,
+                        point,
+                        outward_normal,
+                        ray.direction,
+                        self.material.clone(),
+                    );
+                    if !hit_record.is_cut_out() {
+                        return Some(hit_record);
+                    }
+                    // carry on through the hole to the surface beyond
+                    leaving_surface = true;
+                }
+            } else {
+                leaving_surface = false;
+            }
+            t += f64::max(distance, EPSILON) / ray_length;
+        }
+        return None;
+    }
+
+    fn bounding_box(&self, _t_0: f64, _t_1: f64) -> Option<AABB> {
+        return Some(self.bounds);
+    }
+}
+
+pub struct SdfSphere {
+    centre: V3,
+    radius: f64,
+}
+
+impl SdfSphere {
+    pub fn new(centre: V3, radius: f64) -> Self {
+        return SdfSphere { centre, radius };
+    }
+}
+
+impl Sdf for SdfSphere {
+    fn distance(&self, p: V3) -> f64 {
+        return (p - self.centre).length() - self.radius;
+    }
+}
+
+/// An axis aligned box, with its edges rounded off by `radius`. A radius of
+/// zero gives a sharp box.
+pub struct SdfBox {
+    centre: V3,
+    half_size: V3,
+    radius: f64,
+}
+
+impl SdfBox {
+    pub fn new_rounded(centre: V3, half_size: V3, radius: f64) -> Self {
+        let inner = half_size - V3::new(radius, radius, radius);
+        return SdfBox {
+            centre,
+            half_size: inner,
+            radius,
+        };
+    }
+}
+
+impl Sdf for SdfBox {
+    fn distance(&self, p: V3) -> f64 {
+        let p = p - self.centre;
+        let q = V3::new(
+            f64::abs(p.x) - self.half_size.x,
+            f64::abs(p.y) - self.half_size.y,
+            f64::abs(p.z) - self.half_size.z,
+        );
+        let outside = V3::new(f64::max(q.x, 0.0), f64::max(q.y, 0.0), f64::max(q.z, 0.0));
+        let inside = f64::min(f64::max(q.x, f64::max(q.y, q.z)), 0.0);
+        return outside.length() + inside - self.radius;
+    }
+}
+
+/// The union of two surfaces, blended together where they are within `k`
+/// of each other.
+pub struct SmoothUnion {
+    a: Arc<dyn Sdf + Send + Sync>,
+    b: Arc<dyn Sdf + Send + Sync>,
+    k: f64,
+}
+
+impl SmoothUnion {
+    pub fn new<S: Sdf + Send + Sync + 'static, T: Sdf + Send + Sync + 'static>(
+        a: S,
+        b: T,
+        k: f64,
+    ) -> Self {
+        return SmoothUnion {
+            a: Arc::new(a),
+            b: Arc::new(b),
+            k,
+        };
+    }
+}
+
+impl Sdf for SmoothUnion {
+    fn distance(&self, p: V3) -> f64 {
+        // polynomial smooth minimum
+        let d_a = self.a.distance(p);
+        let d_b = self.b.distance(p);
+        let h = f64::clamp(0.5 + 0.5 * (d_b - d_a) / self.k, 0.0, 1.0);
+        return d_b + h * (d_a - d_b) - self.k * h * (1.0 - h);
+    }
+}
+
+/// Infinitely many copies of a surface, one in each cell of a grid with the
+/// given spacing. The surface should fit within a cell centred on the origin.
+pub struct Repeat {
+    sdf: Arc<dyn Sdf + Send + Sync>,
+    spacing: V3,
+}
+
+impl Repeat {
+    pub fn new<S: Sdf + Send + Sync + 'static>(sdf: S, spacing: V3) -> Self {
+        return Repeat {
+            sdf: Arc::new(sdf),
+            spacing,
+        };
+    }
+}
+
+impl Sdf for Repeat {
+    fn distance(&self, p: V3) -> f64 {
+        let wrap = |x: f64, s: f64| x - s * f64::round(x / s);
+        let local = V3::new(
+            wrap(p.x, self.spacing.x),
+            wrap(p.y, self.spacing.y),
+            wrap(p.z, self.spacing.z),
+        );
+        return self.sdf.distance(local);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        colour::Colour,
+        material::{AlphaMask, AlphaMode, Lambertian},
+        texture::Texture,
+    };
+
+    fn material() -> Arc<dyn Material + Send + Sync> {
+        return Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+    }
+
+    fn unit_box(bounds_margin: f64) -> SdfHittable {
+        let sdf = SdfBox::new_rounded(V3::new(0.0, 0.0, 0.0), V3::new(1.0, 1.0, 1.0), 0.0);
+        let extent = 1.0 + bounds_margin;
+        let bounds = AABB::new(
+            V3::new(-extent, -extent, -extent),
+            V3::new(extent, extent, extent),
+        );
+        return SdfHittable::new(sdf, bounds, material());
+    }
+
+    #[test]
+    fn box_hit_on_its_front_face_with_tight_bounds() {
+        for margin in [0.0, 0.5] {
+            let sdf_box = unit_box(margin);
+            let ray = Ray::new(V3::new(0.0, 5.0, 0.0), V3::new(0.0, -1.0, 0.0), 0.0);
+            let hit = sdf_box.hit(&ray, 0.001, f64::INFINITY).unwrap();
+            assert!(f64::abs(hit.time - 4.0) < 1e-4, "got t = {}", hit.time);
+            assert!(hit.front_face);
+            assert!((hit.normal - V3::new(0.0, 1.0, 0.0)).length() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn ray_leaving_the_surface_finds_the_far_side() {
+        let sdf_box = unit_box(0.0);
+        // starting on the top face, heading down through the box
+        let ray = Ray::new(V3::new(0.0, 1.0, 0.0), V3::new(0.0, -1.0, 0.0), 0.0);
+        let hit = sdf_box.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 2.0) < 1e-4, "got t = {}", hit.time);
+        assert!(!hit.front_face);
+    }
+
+    #[test]
+    fn sphere_missed_and_hit() {
+        let sphere = SdfHittable::new(
+            SdfSphere::new(V3::new(0.0, 0.0, 0.0), 1.0),
+            AABB::new(V3::new(-1.0, -1.0, -1.0), V3::new(1.0, 1.0, 1.0)),
+            material(),
+        );
+        let hit_ray = Ray::new(V3::new(-5.0, 0.0, 0.0), V3::new(2.0, 0.0, 0.0), 0.0);
+        let hit = sphere.hit(&hit_ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 2.0) < 1e-4);
+        let miss_ray = Ray::new(V3::new(-5.0, 0.9, 0.9), V3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(sphere.hit(&miss_ray, 0.001, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn smooth_union_fills_in_between_and_repeat_wraps() {
+        let a = SdfSphere::new(V3::new(-1.0, 0.0, 0.0), 0.8);
+        let b = SdfSphere::new(V3::new(1.0, 0.0, 0.0), 0.8);
+        let union = SmoothUnion::new(a, b, 0.5);
+        // the hard union has the origin 0.2 outside; the blend reaches it
+        assert!(union.distance(V3::new(0.0, 0.0, 0.0)) < 0.2);
+        let repeated = Repeat::new(
+            SdfSphere::new(V3::new(0.0, 0.0, 0.0), 0.5),
+            V3::new(4.0, 4.0, 4.0),
+        );
+        let d = repeated.distance(V3::new(8.0, 4.0, -4.0));
+        assert!(f64::abs(d + 0.5) < 1e-9);
+    }
+
+    /// Clear in front of the xy plane, opaque behind it.
+    struct OpaqueBehind;
+
+    impl Texture for OpaqueBehind {
+        fn colour(&self, _u: f64, _v: f64, p: V3) -> Colour {
+            if p.z > 0.0 {
+                return Colour::new(0.0, 0.0, 0.0);
+            }
+            return Colour::new(1.0, 1.0, 1.0);
+        }
+    }
+
+    #[test]
+    fn rays_pass_through_cut_outs_to_the_surface_beyond() {
+        let material = AlphaMask::new(material(), OpaqueBehind, AlphaMode::Threshold(0.5));
+        let sphere = SdfHittable::new(
+            SdfSphere::new(V3::new(0.0, 0.0, 0.0), 1.0),
+            AABB::new(V3::new(-1.0, -1.0, -1.0), V3::new(1.0, 1.0, 1.0)),
+            Arc::new(material),
+        );
+        let ray = Ray::new(V3::new(0.0, 0.0, 5.0), V3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = sphere.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 6.0) < 1e-4, "got t = {}", hit.time);
+        assert!(!hit.front_face);
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::OnceLock;
+
+use crate::colour::Colour;
+
+/// The range of wavelengths sampled when rendering spectrally, in
+/// nanometres.
+pub const WAVELENGTH_MIN: f64 = 380.0;
+pub const WAVELENGTH_MAX: f64 = 780.0;
+
+/// The wavelength used for a single index of refraction when rendering in
+/// RGB: the helium d line that glass is usually specified at. (The sodium D
+/// line, at 589.3 nm, is sometimes used instead.)
+pub const WAVELENGTH_D_LINE: f64 = 587.6;
+
+pub fn random_wavelength() -> f64 {
+    return WAVELENGTH_MIN + (WAVELENGTH_MAX - WAVELENGTH_MIN) * rand::random::<f64>();
+}
+
+/// A Gaussian with different widths either side of its peak.
+fn piecewise_gaussian(x: f64, mean: f64, sigma_below: f64, sigma_above: f64) -> f64 {
+    let sigma = if x < mean { sigma_below } else { sigma_above };
+    let t = (x - mean) / sigma;
+    return f64::exp(-0.5 * t * t);
+}
+
+/// The CIE 1931 colour matching functions at a wavelength, using the
+/// multi-lobe fit of Wyman, Sloan and Shirley, "Simple Analytic
+/// Approximations to the CIE XYZ Color Matching Functions".
+pub fn cie_xyz(wavelength: f64) -> (f64, f64, f64) {
+    let x = 1.056 * piecewise_gaussian(wavelength, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(wavelength, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(wavelength, 501.1, 20.4, 26.2);
+    let y = 0.821 * piecewise_gaussian(wavelength, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(wavelength, 530.9, 16.3, 31.1);
+    let z = 1.217 * piecewise_gaussian(wavelength, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(wavelength, 459.0, 26.0, 13.8);
+    return (x, y, z);
+}
+
+/// Convert CIE XYZ to linear sRGB. Saturated colours outside sRGB come out
+/// with negative parts.
+pub fn xyz_to_rgb(x: f64, y: f64, z: f64) -> Colour {
+    return Colour::new(
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    );
+}
+
+/// What a path carrying one wavelength adds to the red, green and blue of a
+/// pixel, for each unit of light it carries. Averaged over the sampled
+/// wavelengths this is white, so a scene without dispersion renders the same
+/// as it does in RGB.
+pub fn wavelength_weight(wavelength: f64) -> Colour {
+    static WHITE: OnceLock<Colour> = OnceLock::new();
+    let white = WHITE.get_or_init(|| {
+        let steps = 4000;
+        let step = (WAVELENGTH_MAX - WAVELENGTH_MIN) / steps as f64;
+        let mut total = Colour::new(0.0, 0.0, 0.0);
+        for i in 0..steps {
+            let (x, y, z) = cie_xyz(WAVELENGTH_MIN + (i as f64 + 0.5) * step);
+            total = total + xyz_to_rgb(x, y, z);
+        }
+        return total / steps as f64;
+    });
+    let (x, y, z) = cie_xyz(wavelength);
+    let rgb = xyz_to_rgb(x, y, z);
+    return Colour::new(
+        rgb.red / white.red,
+        rgb.green / white.green,
+        rgb.blue / white.blue,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wavelengths_are_sampled_in_the_visible_range() {
+        for _ in 0..1000 {
+            let wavelength = random_wavelength();
+            assert!((WAVELENGTH_MIN..WAVELENGTH_MAX).contains(&wavelength));
+        }
+    }
+
+    #[test]
+    fn luminance_peaks_in_the_green() {
+        let (_, y_green, _) = cie_xyz(555.0);
+        let (_, y_blue, _) = cie_xyz(450.0);
+        let (_, y_red, _) = cie_xyz(650.0);
+        assert!(y_green > 0.95 && y_green < 1.05);
+        assert!(y_green > y_blue && y_green > y_red);
+    }
+
+    #[test]
+    fn wavelength_weights_average_to_white() {
+        let steps = 4000;
+        let step = (WAVELENGTH_MAX - WAVELENGTH_MIN) / steps as f64;
+        let mut total = Colour::new(0.0, 0.0, 0.0);
+        for i in 0..steps {
+            total = total + wavelength_weight(WAVELENGTH_MIN + (i as f64 + 0.5) * step);
+        }
+        let average = total / steps as f64;
+        for channel in [average.red, average.green, average.blue] {
+            assert!(f64::abs(channel - 1.0) < 1e-9);
+        }
+        // and the ends of the spectrum are red and blue
+        let red = wavelength_weight(650.0);
+        let blue = wavelength_weight(450.0);
+        assert!(red.red > red.blue && blue.blue > blue.red);
+    }
+
+    #[test]
+    fn spectral_pixels_are_white_and_never_negative() {
+        let steps = 400;
+        let step = (WAVELENGTH_MAX - WAVELENGTH_MIN) / steps as f64;
+        let mut total = Colour::new(0.0, 0.0, 0.0);
+        for i in 0..steps {
+            let weight = wavelength_weight(WAVELENGTH_MIN + (i as f64 + 0.5) * step);
+            total = total + weight;
+            // a pixel that saw only this wavelength still has a colour
+            let pixel = weight.clamp_non_negative().gamma_correct(1.0);
+            for channel in [pixel.red, pixel.green, pixel.blue] {
+                assert!(channel.is_finite() && channel >= 0.0);
+            }
+        }
+        // while one that saw them all is white, with nothing clamped away
+        let pixel = total.clamp_non_negative().gamma_correct(1.0 / steps as f64);
+        for channel in [pixel.red, pixel.green, pixel.blue] {
+            assert!(f64::abs(channel - 1.0) < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{
+    colour::Colour,
+    hittable::HitRecord,
+    material::{reflect_or_refract, Material},
+    ray::Ray,
+    v3::{random_unit_vector, unit_vector},
+};
+
+/// A translucent material like wax, skin, jade or marble, where light
+/// enters through a smooth dielectric surface, bounces around inside, and
+/// comes out somewhere else. The walk inside is followed with the same rays
+/// as the rest of the path: each hit on the inside of the surface ends a
+/// stretch of travel, which may have been cut short by scattering. The
+/// object must be closed, and each scattering inside uses up a bounce of the
+/// path's depth, so mean free paths much smaller than the object need a
+/// deeper path to avoid darkening. Each stretch is sampled for one colour
+/// channel, so mean free paths which differ a lot between channels give
+/// coloured noise.
+pub struct Subsurface {
+    /// The chance of light scattering rather than being absorbed at each
+    /// event inside, for red, green and blue
+    single_scattering_albedo: [f64; 3],
+    /// How often light meets something inside, as one over the mean free
+    /// path, for red, green and blue
+    extinction: [f64; 3],
+    index_of_refraction: f64,
+}
+
+impl Subsurface {
+    /// `albedo` is the colour a thick piece of the material appears, and
+    /// `mean_free_path` how far each of red, green and blue light travels
+    /// between events inside it on average.
+    pub fn new(albedo: Colour, mean_free_path: Colour, index_of_refraction: f64) -> Self {
+        let albedo = [albedo.red, albedo.green, albedo.blue];
+        let mean_free_path = [
+            mean_free_path.red,
+            mean_free_path.green,
+            mean_free_path.blue,
+        ];
+        return Subsurface {
+            single_scattering_albedo: albedo.map(single_scattering_albedo),
+            extinction: mean_free_path.map(|distance| 1.0 / f64::max(distance, 1e-6)),
+            index_of_refraction,
+        };
+    }
+}
+
+/// The albedo of each event inside which gives a thick piece of material
+/// the overall albedo `albedo` (Chiang, Kutz and Burley, "Practical and
+/// Controllable Subsurface Scattering for Production Path Tracing").
+fn single_scattering_albedo(albedo: f64) -> f64 {
+    let a = f64::clamp(albedo, 0.0, 1.0);
+    let root = 4.09712 + 4.20863 * a - f64::sqrt(9.59217 + 41.6808 * a + 17.7126 * a * a);
+    return f64::clamp(1.0 - root * root, 0.0, 1.0);
+}
+
+impl Material for Subsurface {
+    fn scatter(&self, ray_in: &Ray, hit_record: &HitRecord) -> Option<(Ray, Colour)> {
+        let unit_direction = unit_vector(ray_in.direction);
+        if hit_record.front_face {
+            // light arriving from outside reflects off the surface or goes in
+            let direction = reflect_or_refract(
+                unit_direction,
+                hit_record.shading_normal,
+                1.0 / self.index_of_refraction,
+            );
+            let scattered = Ray::new(hit_record.point, direction, ray_in.time);
+            return Some((scattered, Colour::new(1.0, 1.0, 1.0)));
+        }
+
+        // the ray has crossed the inside; decide how far it really got,
+        // using the extinction of one channel picked at random and weighting
+        // by the chance of any of the channels picking that distance
+        let distance = hit_record.time * ray_in.direction.length();
+        let channel = rand::random::<usize>() % 3;
+        let free_path = -f64::ln(1.0 - rand::random::<f64>()) / self.extinction[channel];
+        let transmittance = self.extinction.map(|extinction| {
+            return f64::exp(-extinction * f64::min(free_path, distance));
+        });
+
+        if free_path < distance {
+            // scattered inside, in any direction
+            let pdf = (0..3)
+                .map(|i| self.extinction[i] * transmittance[i])
+                .sum::<f64>()
+                / 3.0;
+            let weight = |i: usize| {
+                return self.single_scattering_albedo[i] * self.extinction[i] * transmittance[i]
+                    / pdf;
+            };
+            let origin = ray_in.origin + free_path * unit_direction;
+            let scattered = Ray::new(origin, random_unit_vector(), ray_in.time);
+            return Some((scattered, Colour::new(weight(0), weight(1), weight(2))));
+        }
+
+        // reached the surface, to leave or be reflected back in
+        let probability = transmittance.iter().sum::<f64>() / 3.0;
+        let weight = |i: usize| transmittance[i] / probability;
+        let direction = reflect_or_refract(
+            unit_direction,
+            hit_record.shading_normal,
+            self.index_of_refraction,
+        );
+        let scattered = Ray::new(hit_record.point, direction, ray_in.time);
+        return Some((scattered, Colour::new(weight(0), weight(1), weight(2))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::v3::V3;
+
+    /// A hit on the xy plane, whose outside is up z, by a ray from `origin`
+    /// that has travelled `distance` to reach it.
+    fn hit(material: &Arc<Subsurface>, origin: V3, distance: f64) -> (Ray, HitRecord) {
+        let direction = unit_vector(-origin);
+        let ray = Ray::new(origin, direction, 0.0);
+        let hit_record = HitRecord::new(
+            distance,
+            0.0,
+            0.0,
+            V3::new(0.0, 0.0, 0.0),
+            V3::new(0.0, 0.0, 1.0),
+            direction,
+            material.clone(),
+        );
+        return (ray, hit_record);
+    }
+
+    #[test]
+    fn single_scattering_albedo_spans_zero_to_one() {
+        assert!(single_scattering_albedo(0.0) < 1e-4);
+        assert!(single_scattering_albedo(1.0) > 1.0 - 1e-4);
+        let mut last = 0.0;
+        for i in 1..=10 {
+            let albedo = single_scattering_albedo(i as f64 / 10.0);
+            assert!(albedo > last);
+            // many events inside darken a piece, so each must be brighter
+            assert!(albedo > i as f64 / 10.0 - 1e-4);
+            last = albedo;
+        }
+    }
+
+    #[test]
+    fn light_enters_through_the_surface_unweighted() {
+        let material = Arc::new(Subsurface::new(
+            Colour::new(0.8, 0.8, 0.8),
+            Colour::new(1.0, 1.0, 1.0),
+            1.5,
+        ));
+        let (ray, hit_record) = hit(&material, V3::new(0.0, 0.0, 1.0), 1.0);
+        let mut inside = 0;
+        for _ in 0..1000 {
+            let (scattered, attenuation) = material.scatter(&ray, &hit_record).unwrap();
+            assert_eq!(attenuation.red, 1.0);
+            if scattered.direction.z < 0.0 {
+                inside += 1;
+            }
+        }
+        // all but the 4% reflected head on
+        assert!(inside > 930, "only {inside} went in");
+    }
+
+    #[test]
+    fn scattering_inside_happens_before_the_surface() {
+        let material = Arc::new(Subsurface::new(
+            Colour::new(0.8, 0.8, 0.8),
+            Colour::new(0.01, 0.01, 0.01),
+            1.5,
+        ));
+        // from inside, two units below the surface
+        let (ray, hit_record) = hit(&material, V3::new(0.0, 0.0, -2.0), 2.0);
+        for _ in 0..100 {
+            let (scattered, _) = material.scatter(&ray, &hit_record).unwrap();
+            assert!(scattered.origin.z < 0.0 && scattered.origin.z > -2.0);
+            // and the sample leaves from there too, rather than the hit
+            let sample = material.sample(&ray, &hit_record).unwrap();
+            assert!(sample.origin.z < 0.0 && sample.origin.z > -2.0);
+            assert_eq!(sample.scattered(&ray).0.origin.z, sample.origin.z);
+        }
+    }
+
+    #[test]
+    fn light_reaching_the_surface_is_weighted_by_each_channels_transmittance() {
+        let material = Arc::new(Subsurface::new(
+            Colour::new(0.8, 0.8, 0.8),
+            Colour::new(1.0, 0.5, 0.25),
+            1.5,
+        ));
+        let distance = 0.5;
+        let (ray, hit_record) = hit(&material, V3::new(0.0, 0.0, -distance), distance);
+        let samples = 100000;
+        let mut total = Colour::new(0.0, 0.0, 0.0);
+        for _ in 0..samples {
+            let (scattered, attenuation) = material.scatter(&ray, &hit_record).unwrap();
+            // scattering inside moves the origin off the surface
+            if scattered.origin.z == 0.0 {
+                total = total + attenuation;
+            }
+        }
+        let average = total / samples as f64;
+        let expected = [1.0, 2.0, 4.0].map(|extinction: f64| f64::exp(-extinction * distance));
+        assert!(f64::abs(average.red - expected[0]) < 0.02);
+        assert!(f64::abs(average.green - expected[1]) < 0.02);
+        assert!(f64::abs(average.blue - expected[2]) < 0.02);
+    }
+}
@@ -1,9 +1,32 @@
-use std::sync::Arc;
+use std::{io, path::Path, sync::Arc};
 
-use crate::{colour::Colour, v3::V3};
+use crate::{
+    colour::Colour,
+    image::{read_image, read_png_alpha},
+    noise::Perlin,
+    v3::V3,
+};
 
 pub trait Texture {
     fn colour(&self, u: f64, v: f64, p: V3) -> Colour;
+
+    /// The average colour over the area of the surface one pixel covers,
+    /// to avoid aliasing. By default just the colour at the centre.
+    fn colour_filtered(&self, u: f64, v: f64, p: V3, _footprint: &Footprint) -> Colour {
+        return self.colour(u, v, p);
+    }
+}
+
+/// How far a surface point and its uvs move from one pixel to the next,
+/// across (`x`) and up (`y`) the image.
+#[derive(Clone, Copy)]
+pub struct Footprint {
+    pub dpdx: V3,
+    pub dpdy: V3,
+    pub dudx: f64,
+    pub dvdx: f64,
+    pub dudy: f64,
+    pub dvdy: f64,
 }
 
 pub struct SolidColour {
@@ -59,4 +82,834 @@ impl Texture for Checkers {
             return self.even.colour(u, v, p);
         }
     }
+
+    /// The checks averaged over a box around `p` as wide as the footprint,
+    /// so distant checks fade to a blend of the two textures rather than
+    /// aliasing.
+    fn colour_filtered(&self, u: f64, v: f64, p: V3, footprint: &Footprint) -> Colour {
+        // the sign of each sine is a square wave, whose average over a span
+        // comes from its integral, a triangle wave
+        let half_period = std::f64::consts::PI / self.scale;
+        let integral = |x: f64| {
+            let m = x.rem_euclid(2.0 * half_period);
+            if m < half_period {
+                return m;
+            } else {
+                return 2.0 * half_period - m;
+            }
+        };
+        let average = |x: f64, dx: f64, dy: f64| {
+            let width = f64::max(f64::abs(dx), f64::abs(dy));
+            if width < 1e-6 * half_period {
+                return f64::signum(f64::sin(self.scale * x));
+            }
+            return (integral(x + 0.5 * width) - integral(x - 0.5 * width)) / width;
+        };
+        let signs = average(p.x, footprint.dpdx.x, footprint.dpdy.x)
+            * average(p.y, footprint.dpdx.y, footprint.dpdy.y)
+            * average(p.z, footprint.dpdx.z, footprint.dpdy.z);
+        let even = 0.5 * (1.0 + signs);
+        return even * self.even.colour_filtered(u, v, p, footprint)
+            + (1.0 - even) * self.odd.colour_filtered(u, v, p, footprint);
+    }
+}
+
+/// How an image is sampled outside of 0 to 1.
+#[derive(Clone, Copy)]
+pub enum Addressing {
+    /// Repeat the image
+    Wrap,
+    /// Stretch the edge pixels outwards
+    Clamp,
+    /// Repeat the image, flipping every other copy
+    Mirror,
+}
+
+impl Addressing {
+    fn pixel_index(&self, i: i64, size: usize) -> usize {
+        let size = size as i64;
+        let index = match self {
+            Addressing::Wrap => i.rem_euclid(size),
+            Addressing::Clamp => i.clamp(0, size - 1),
+            Addressing::Mirror => {
+                let i = i.rem_euclid(2 * size);
+                if i < size {
+                    i
+                } else {
+                    2 * size - 1 - i
+                }
+            }
+        };
+        return index as usize;
+    }
+}
+
+/// One level of an image's mip pyramid.
+struct MipLevel {
+    width: usize,
+    height: usize,
+    /// Linear colours, from the bottom row up
+    pixels: Vec<Colour>,
+}
+
+impl MipLevel {
+    fn pixel(&self, i: i64, j: i64, addressing: Addressing) -> Colour {
+        let i = addressing.pixel_index(i, self.width);
+        let j = addressing.pixel_index(j, self.height);
+        return self.pixels[j * self.width + i];
+    }
+
+    /// The pixels blended bilinearly at `u`, `v`.
+    fn sample(&self, u: f64, v: f64, addressing: Addressing) -> Colour {
+        // pixel centres are at the halves
+        let x = u * self.width as f64 - 0.5;
+        let y = v * self.height as f64 - 0.5;
+        let (i, j) = (x.floor(), y.floor());
+        let (s, t) = (x - i, y - j);
+        let (i, j) = (i as i64, j as i64);
+        let bottom =
+            (1.0 - s) * self.pixel(i, j, addressing) + s * self.pixel(i + 1, j, addressing);
+        let top =
+            (1.0 - s) * self.pixel(i, j + 1, addressing) + s * self.pixel(i + 1, j + 1, addressing);
+        return (1.0 - t) * bottom + t * top;
+    }
+
+    /// The level below: half the size, each pixel the average of the (up to)
+    /// four it covers.
+    fn downsample(&self) -> MipLevel {
+        let width = usize::max(self.width / 2, 1);
+        let height = usize::max(self.height / 2, 1);
+        let mut pixels = Vec::with_capacity(width * height);
+        for j in 0..height {
+            for i in 0..width {
+                let mut total = Colour::new(0.0, 0.0, 0.0);
+                for (di, dj) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let x = usize::min(2 * i + di, self.width - 1);
+                    let y = usize::min(2 * j + dj, self.height - 1);
+                    total = total + self.pixels[y * self.width + x];
+                }
+                pixels.push(total / 4.0);
+            }
+        }
+        return MipLevel {
+            width,
+            height,
+            pixels,
+        };
+    }
+}
+
+/// An image mapped onto a surface by its uvs, with (0, 0) at the bottom left
+/// of the image. Pixels are blended bilinearly, and when a pixel of the
+/// render covers many pixels of the image, they are averaged using
+/// successively halved copies of the image (mipmaps).
+pub struct ImageTexture {
+    /// From the full size image down to a single pixel
+    levels: Vec<MipLevel>,
+    addressing: Addressing,
+}
+
+impl ImageTexture {
+    /// An image of linear colours, with rows from the bottom up as returned
+    /// by `image::read_image`.
+    pub fn new(image: Vec<Vec<Colour>>, addressing: Addressing) -> Self {
+        let height = image.len();
+        let width = image.first().map_or(0, |row| row.len());
+        assert!(width > 0 && height > 0, "an image texture can't be empty");
+        let pixels: Vec<Colour> = image.into_iter().flatten().collect();
+        assert_eq!(
+            pixels.len(),
+            width * height,
+            "image rows must be the same length"
+        );
+        let mut levels = vec![MipLevel {
+            width,
+            height,
+            pixels,
+        }];
+        while let Some(last) = levels.last().filter(|l| l.width > 1 || l.height > 1) {
+            levels.push(last.downsample());
+        }
+        return ImageTexture { levels, addressing };
+    }
+
+    /// Load a PNG, PPM or PGM image, taking its colours to be sRGB.
+    pub fn load<P: AsRef<Path>>(path: P, addressing: Addressing) -> io::Result<Self> {
+        let image = read_image(path)?
+            .into_iter()
+            .map(|row| row.into_iter().map(srgb_to_linear).collect())
+            .collect();
+        return Ok(ImageTexture::new(image, addressing));
+    }
+
+    /// Load the alpha channel of a PNG image, as a greyscale texture from 0
+    /// for transparent to 1 for opaque.
+    pub fn load_alpha<P: AsRef<Path>>(path: P, addressing: Addressing) -> io::Result<Self> {
+        return Ok(ImageTexture::new(read_png_alpha(path)?, addressing));
+    }
+
+    /// Load a PNG, PPM or PGM image of data rather than colours, such as a
+    /// normal map, using its values as they are.
+    pub fn load_linear<P: AsRef<Path>>(path: P, addressing: Addressing) -> io::Result<Self> {
+        return Ok(ImageTexture::new(read_image(path)?, addressing));
+    }
+}
+
+impl Texture for ImageTexture {
+    fn colour(&self, u: f64, v: f64, _p: V3) -> Colour {
+        return self.levels[0].sample(u, v, self.addressing);
+    }
+
+    fn colour_filtered(&self, u: f64, v: f64, _p: V3, footprint: &Footprint) -> Colour {
+        // how many full size pixels the longer side of the footprint spans
+        let full = &self.levels[0];
+        let (width, height) = (full.width as f64, full.height as f64);
+        let across = f64::hypot(footprint.dudx * width, footprint.dvdx * height);
+        let up = f64::hypot(footprint.dudy * width, footprint.dvdy * height);
+        let level = f64::log2(f64::max(across, up));
+        if level.is_nan() || level <= 0.0 {
+            return full.sample(u, v, self.addressing);
+        }
+        let last = (self.levels.len() - 1) as f64;
+        if level >= last {
+            return self.levels.last().unwrap().sample(u, v, self.addressing);
+        }
+        // blend between the two nearest levels
+        let lower = level.floor();
+        let blend = level - lower;
+        let lower = lower as usize;
+        return (1.0 - blend) * self.levels[lower].sample(u, v, self.addressing)
+            + blend * self.levels[lower + 1].sample(u, v, self.addressing);
+    }
+}
+
+fn srgb_to_linear(colour: Colour) -> Colour {
+    let convert = |c: f64| {
+        if c <= 0.04045 {
+            return c / 12.92;
+        } else {
+            return f64::powf((c + 0.055) / 1.055, 2.4);
+        }
+    };
+    return Colour::new(
+        convert(colour.red),
+        convert(colour.green),
+        convert(colour.blue),
+    );
+}
+
+/// Colours at points along a line, blended linearly between them, for
+/// colouring values such as noise.
+#[derive(Clone)]
+pub struct ColourRamp {
+    /// Sorted by position
+    stops: Vec<(f64, Colour)>,
+}
+
+impl ColourRamp {
+    pub fn new(stops: Vec<(f64, Colour)>) -> Self {
+        assert!(!stops.is_empty(), "a colour ramp needs at least one stop");
+        let mut stops = stops;
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        return ColourRamp { stops };
+    }
+
+    /// A ramp from `start` at 0 to `end` at 1.
+    pub fn new_from_colours(start: Colour, end: Colour) -> Self {
+        return ColourRamp::new(vec![(0.0, start), (1.0, end)]);
+    }
+
+    /// The colour at `x`, holding the end colours beyond the first and last
+    /// stops.
+    pub fn colour_at(&self, x: f64) -> Colour {
+        let after = self.stops.partition_point(|&(position, _)| position <= x);
+        if after == 0 {
+            return self.stops[0].1;
+        }
+        if after == self.stops.len() {
+            return self.stops[after - 1].1;
+        }
+        let (p_0, c_0) = self.stops[after - 1];
+        let (p_1, c_1) = self.stops[after];
+        let blend = (x - p_0) / (p_1 - p_0);
+        return (1.0 - blend) * c_0 + blend * c_1;
+    }
+}
+
+/// Fractal Perlin noise, coloured by a ramp from 0 to 1.
+pub struct Noise {
+    perlin: Perlin,
+    scale: f64,
+    octaves: u32,
+    ramp: ColourRamp,
+}
+
+impl Noise {
+    pub fn new(seed: u64, scale: f64, octaves: u32, ramp: ColourRamp) -> Self {
+        return Noise {
+            perlin: Perlin::new(seed),
+            scale,
+            octaves,
+            ramp,
+        };
+    }
+}
+
+impl Texture for Noise {
+    fn colour(&self, _u: f64, _v: f64, p: V3) -> Colour {
+        let value = 0.5 * (1.0 + self.perlin.fbm(self.scale * p, self.octaves));
+        return self.ramp.colour_at(value);
+    }
+}
+
+/// How far turbulence pushes the veins of marble and the rings of wood.
+const MARBLE_DISTORTION: f64 = 10.0;
+const WOOD_DISTORTION: f64 = 0.5;
+
+/// Veins running across z, bent by turbulence, coloured by a ramp from 0
+/// (between veins) to 1 (in a vein).
+pub struct Marble {
+    perlin: Perlin,
+    scale: f64,
+    octaves: u32,
+    ramp: ColourRamp,
+}
+
+impl Marble {
+    pub fn new(seed: u64, scale: f64, octaves: u32, ramp: ColourRamp) -> Self {
+        return Marble {
+            perlin: Perlin::new(seed),
+            scale,
+            octaves,
+            ramp,
+        };
+    }
+}
+
+impl Texture for Marble {
+    fn colour(&self, _u: f64, _v: f64, p: V3) -> Colour {
+        let p = self.scale * p;
+        let phase = p.z + MARBLE_DISTORTION * self.perlin.turbulence(p, self.octaves);
+        let value = 0.5 * (1.0 + f64::sin(phase));
+        return self.ramp.colour_at(value);
+    }
+}
+
+/// Growth rings around the y axis, wobbled by noise, coloured by a ramp
+/// across each ring from 0 (early wood) to 1 (late wood).
+pub struct Wood {
+    perlin: Perlin,
+    scale: f64,
+    octaves: u32,
+    ramp: ColourRamp,
+}
+
+impl Wood {
+    pub fn new(seed: u64, scale: f64, octaves: u32, ramp: ColourRamp) -> Self {
+        return Wood {
+            perlin: Perlin::new(seed),
+            scale,
+            octaves,
+            ramp,
+        };
+    }
+}
+
+impl Texture for Wood {
+    fn colour(&self, _u: f64, _v: f64, p: V3) -> Colour {
+        let p = self.scale * p;
+        // stretch the noise along the grain
+        let wobble = self.perlin.fbm(V3::new(p.x, 0.1 * p.y, p.z), self.octaves);
+        let radius = f64::hypot(p.x, p.z) + WOOD_DISTORTION * wobble;
+        let value = radius - radius.floor();
+        // late wood forms quickly at the end of each ring
+        return self.ramp.colour_at(value * value);
+    }
+}
+
+/// The mean of a colour's channels, for using a texture as a single value.
+pub fn brightness(colour: Colour) -> f64 {
+    return (colour.red + colour.green + colour.blue) / 3.0;
+}
+
+/// A blend of two textures, by a fixed amount or by the brightness of a
+/// mask texture, from all of `a` at 0 to all of `b` at 1.
+pub struct Mix {
+    a: Arc<dyn Texture + Send + Sync>,
+    b: Arc<dyn Texture + Send + Sync>,
+    mask: Arc<dyn Texture + Send + Sync>,
+}
+
+impl Mix {
+    pub fn new<S: Texture + Send + Sync + 'static, T: Texture + Send + Sync + 'static>(
+        a: S,
+        b: T,
+        factor: f64,
+    ) -> Self {
+        return Mix::new_with_mask(a, b, SolidColour::new(Colour::new(factor, factor, factor)));
+    }
+
+    pub fn new_with_mask<
+        S: Texture + Send + Sync + 'static,
+        T: Texture + Send + Sync + 'static,
+        M: Texture + Send + Sync + 'static,
+    >(
+        a: S,
+        b: T,
+        mask: M,
+    ) -> Self {
+        return Mix {
+            a: Arc::new(a),
+            b: Arc::new(b),
+            mask: Arc::new(mask),
+        };
+    }
+}
+
+impl Texture for Mix {
+    fn colour(&self, u: f64, v: f64, p: V3) -> Colour {
+        let factor = brightness(self.mask.colour(u, v, p));
+        return (1.0 - factor) * self.a.colour(u, v, p) + factor * self.b.colour(u, v, p);
+    }
+
+    fn colour_filtered(&self, u: f64, v: f64, p: V3, footprint: &Footprint) -> Colour {
+        let factor = brightness(self.mask.colour_filtered(u, v, p, footprint));
+        return (1.0 - factor) * self.a.colour_filtered(u, v, p, footprint)
+            + factor * self.b.colour_filtered(u, v, p, footprint);
+    }
+}
+
+/// The product of two textures, such as a colour tinted by a pattern.
+pub struct Multiply {
+    a: Arc<dyn Texture + Send + Sync>,
+    b: Arc<dyn Texture + Send + Sync>,
+}
+
+impl Multiply {
+    pub fn new<S: Texture + Send + Sync + 'static, T: Texture + Send + Sync + 'static>(
+        a: S,
+        b: T,
+    ) -> Self {
+        return Multiply {
+            a: Arc::new(a),
+            b: Arc::new(b),
+        };
+    }
+}
+
+impl Texture for Multiply {
+    fn colour(&self, u: f64, v: f64, p: V3) -> Colour {
+        return self.a.colour(u, v, p) * self.b.colour(u, v, p);
+    }
+
+    fn colour_filtered(&self, u: f64, v: f64, p: V3, footprint: &Footprint) -> Colour {
+        return self.a.colour_filtered(u, v, p, footprint)
+            * self.b.colour_filtered(u, v, p, footprint);
+    }
+}
+
+/// The sum of two textures.
+pub struct Add {
+    a: Arc<dyn Texture + Send + Sync>,
+    b: Arc<dyn Texture + Send + Sync>,
+}
+
+impl Add {
+    pub fn new<S: Texture + Send + Sync + 'static, T: Texture + Send + Sync + 'static>(
+        a: S,
+        b: T,
+    ) -> Self {
+        return Add {
+            a: Arc::new(a),
+            b: Arc::new(b),
+        };
+    }
+}
+
+impl Texture for Add {
+    fn colour(&self, u: f64, v: f64, p: V3) -> Colour {
+        return self.a.colour(u, v, p) + self.b.colour(u, v, p);
+    }
+
+    fn colour_filtered(&self, u: f64, v: f64, p: V3, footprint: &Footprint) -> Colour {
+        return self.a.colour_filtered(u, v, p, footprint)
+            + self.b.colour_filtered(u, v, p, footprint);
+    }
+}
+
+/// A texture with its uvs scaled, then rotated anticlockwise by `rotation`
+/// degrees about the origin, then offset.
+pub struct UvTransform {
+    texture: Arc<dyn Texture + Send + Sync>,
+    scale: (f64, f64),
+    sin_theta: f64,
+    cos_theta: f64,
+    offset: (f64, f64),
+}
+
+impl UvTransform {
+    pub fn new<T: Texture + Send + Sync + 'static>(
+        texture: T,
+        scale: (f64, f64),
+        rotation: f64,
+        offset: (f64, f64),
+    ) -> Self {
+        let theta = rotation.to_radians();
+        return UvTransform {
+            texture: Arc::new(texture),
+            scale,
+            sin_theta: theta.sin(),
+            cos_theta: theta.cos(),
+            offset,
+        };
+    }
+}
+
+impl UvTransform {
+    /// Scale and rotate `u`, `v`, without the offset.
+    fn scale_and_rotate(&self, u: f64, v: f64) -> (f64, f64) {
+        let (u, v) = (self.scale.0 * u, self.scale.1 * v);
+        return (
+            self.cos_theta * u - self.sin_theta * v,
+            self.sin_theta * u + self.cos_theta * v,
+        );
+    }
+}
+
+impl Texture for UvTransform {
+    fn colour(&self, u: f64, v: f64, p: V3) -> Colour {
+        let (u, v) = self.scale_and_rotate(u, v);
+        return self.texture.colour(u + self.offset.0, v + self.offset.1, p);
+    }
+
+    fn colour_filtered(&self, u: f64, v: f64, p: V3, footprint: &Footprint) -> Colour {
+        let (u, v) = self.scale_and_rotate(u, v);
+        let (dudx, dvdx) = self.scale_and_rotate(footprint.dudx, footprint.dvdx);
+        let (dudy, dvdy) = self.scale_and_rotate(footprint.dudy, footprint.dvdy);
+        let footprint = Footprint {
+            dudx,
+            dvdx,
+            dudy,
+            dvdy,
+            ..*footprint
+        };
+        return self
+            .texture
+            .colour_filtered(u + self.offset.0, v + self.offset.1, p, &footprint);
+    }
+}
+
+/// The brightness of a texture, recoloured by a ramp.
+pub struct Ramp {
+    texture: Arc<dyn Texture + Send + Sync>,
+    ramp: ColourRamp,
+}
+
+impl Ramp {
+    pub fn new<T: Texture + Send + Sync + 'static>(texture: T, ramp: ColourRamp) -> Self {
+        return Ramp {
+            texture: Arc::new(texture),
+            ramp,
+        };
+    }
+}
+
+impl Texture for Ramp {
+    fn colour(&self, u: f64, v: f64, p: V3) -> Colour {
+        return self
+            .ramp
+            .colour_at(brightness(self.texture.colour(u, v, p)));
+    }
+
+    fn colour_filtered(&self, u: f64, v: f64, p: V3, footprint: &Footprint) -> Colour {
+        let value = brightness(self.texture.colour_filtered(u, v, p, footprint));
+        return self.ramp.colour_at(value);
+    }
+}
+
+/// A uv texture projected onto a shape along each axis, for shapes without
+/// good uvs of their own. Textures aren't given the surface normal, so the
+/// direction from `centre` stands in for it: the projections are blended by
+/// how closely that direction lines up with each axis, sharper blends coming
+/// from higher `sharpness`. This suits roughly round shapes around `centre`.
+pub struct Triplanar {
+    texture: Arc<dyn Texture + Send + Sync>,
+    centre: V3,
+    scale: f64,
+    sharpness: f64,
+}
+
+impl Triplanar {
+    pub fn new<T: Texture + Send + Sync + 'static>(
+        texture: T,
+        centre: V3,
+        scale: f64,
+        sharpness: f64,
+    ) -> Self {
+        return Triplanar {
+            texture: Arc::new(texture),
+            centre,
+            scale,
+            sharpness,
+        };
+    }
+}
+
+impl Texture for Triplanar {
+    fn colour(&self, _u: f64, _v: f64, p: V3) -> Colour {
+        let direction = p - self.centre;
+        let weight = |d: f64| f64::powf(f64::abs(d), self.sharpness);
+        let (w_x, w_y, w_z) = (
+            weight(direction.x),
+            weight(direction.y),
+            weight(direction.z),
+        );
+        let total = w_x + w_y + w_z;
+        if total == 0.0 {
+            return self.texture.colour(0.0, 0.0, p);
+        }
+        let q = self.scale * p;
+        let along_x = self.texture.colour(q.z, q.y, p);
+        let along_y = self.texture.colour(q.x, q.z, p);
+        let along_z = self.texture.colour(q.x, q.y, p);
+        return (w_x * along_x + w_y * along_y + w_z * along_z) / total;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!(f64::abs(a - b) < 1e-9, "expected {b}, got {a}");
+    }
+
+    /// Black on the left, white on the right.
+    fn two_pixels(addressing: Addressing) -> ImageTexture {
+        let image = vec![vec![Colour::new(0.0, 0.0, 0.0), Colour::new(1.0, 1.0, 1.0)]];
+        return ImageTexture::new(image, addressing);
+    }
+
+    #[test]
+    fn image_pixels_are_blended_bilinearly() {
+        let texture = two_pixels(Addressing::Clamp);
+        let p = V3::new(0.0, 0.0, 0.0);
+        assert_close(texture.colour(0.25, 0.5, p).red, 0.0);
+        assert_close(texture.colour(0.5, 0.5, p).red, 0.5);
+        assert_close(texture.colour(0.75, 0.5, p).red, 1.0);
+    }
+
+    #[test]
+    fn image_addressing_outside_zero_to_one() {
+        let p = V3::new(0.0, 0.0, 0.0);
+        assert_close(two_pixels(Addressing::Clamp).colour(1.25, 0.5, p).red, 1.0);
+        assert_close(two_pixels(Addressing::Wrap).colour(1.25, 0.5, p).red, 0.0);
+        assert_close(two_pixels(Addressing::Mirror).colour(1.25, 0.5, p).red, 1.0);
+        assert_close(
+            two_pixels(Addressing::Mirror).colour(-0.25, 0.5, p).red,
+            0.0,
+        );
+    }
+
+    #[test]
+    fn srgb_is_converted_to_linear() {
+        assert_close(srgb_to_linear(Colour::new(1.0, 0.0, 0.0)).red, 1.0);
+        assert!(srgb_to_linear(Colour::new(0.5, 0.5, 0.5)).red < 0.25);
+    }
+
+    #[test]
+    fn colour_ramp_blends_between_sorted_stops() {
+        let ramp = ColourRamp::new(vec![
+            (1.0, Colour::new(1.0, 1.0, 1.0)),
+            (0.0, Colour::new(0.0, 0.0, 0.0)),
+            (0.5, Colour::new(1.0, 0.0, 0.0)),
+        ]);
+        assert_close(ramp.colour_at(-1.0).red, 0.0);
+        assert_close(ramp.colour_at(0.25).red, 0.5);
+        assert_close(ramp.colour_at(0.75).green, 0.5);
+        assert_close(ramp.colour_at(2.0).blue, 1.0);
+    }
+
+    #[test]
+    fn noise_textures_stay_within_their_ramps() {
+        let ramp =
+            ColourRamp::new_from_colours(Colour::new(0.2, 0.2, 0.2), Colour::new(0.8, 0.8, 0.8));
+        let textures: Vec<Box<dyn Texture>> = vec![
+            Box::new(Noise::new(3, 4.0, 5, ramp.clone())),
+            Box::new(Marble::new(3, 4.0, 5, ramp.clone())),
+            Box::new(Wood::new(3, 4.0, 5, ramp)),
+        ];
+        for texture in &textures {
+            for i in 0..50 {
+                let p = V3::new(0.13 * i as f64, -0.07 * i as f64, 0.29 * i as f64);
+                let red = texture.colour(0.0, 0.0, p).red;
+                assert!((0.2 - 1e-9..=0.8 + 1e-9).contains(&red));
+            }
+        }
+    }
+
+    /// Shows the uvs as red and green.
+    struct Uvs;
+
+    impl Texture for Uvs {
+        fn colour(&self, u: f64, v: f64, _p: V3) -> Colour {
+            return Colour::new(u, v, 0.0);
+        }
+    }
+
+    fn grey(value: f64) -> SolidColour {
+        return SolidColour::new(Colour::new(value, value, value));
+    }
+
+    #[test]
+    fn mix_blends_by_factor_or_mask() {
+        let p = V3::new(0.0, 0.0, 0.0);
+        let fixed = Mix::new(grey(0.2), grey(0.6), 0.25);
+        assert_close(fixed.colour(0.0, 0.0, p).red, 0.3);
+        // the mask's red channel alone is worth a third
+        let masked = Mix::new_with_mask(
+            grey(0.0),
+            grey(1.0),
+            SolidColour::new(Colour::new(1.0, 0.0, 0.0)),
+        );
+        assert_close(masked.colour(0.0, 0.0, p).green, 1.0 / 3.0);
+    }
+
+    #[test]
+    fn multiply_and_add_combine_channels() {
+        let p = V3::new(0.0, 0.0, 0.0);
+        let a = || SolidColour::new(Colour::new(0.5, 0.25, 1.0));
+        let b = || SolidColour::new(Colour::new(0.5, 2.0, 0.0));
+        let product = Multiply::new(a(), b()).colour(0.0, 0.0, p);
+        let sum = Add::new(a(), b()).colour(0.0, 0.0, p);
+        assert_close(product.red, 0.25);
+        assert_close(product.green, 0.5);
+        assert_close(product.blue, 0.0);
+        assert_close(sum.red, 1.0);
+        assert_close(sum.green, 2.25);
+        assert_close(sum.blue, 1.0);
+    }
+
+    #[test]
+    fn uv_transform_scales_then_rotates_then_offsets() {
+        let p = V3::new(0.0, 0.0, 0.0);
+        let texture = UvTransform::new(Uvs, (2.0, 3.0), 90.0, (0.5, 0.25));
+        // (1, 1) scales to (2, 3) and turns to (-3, 2)
+        let colour = texture.colour(1.0, 1.0, p);
+        assert_close(colour.red, -2.5);
+        assert_close(colour.green, 2.25);
+    }
+
+    #[test]
+    fn uv_transform_carries_the_footprint_through() {
+        /// Shows how far u changes across a pixel as red.
+        struct DuDx;
+
+        impl Texture for DuDx {
+            fn colour(&self, _u: f64, _v: f64, _p: V3) -> Colour {
+                return Colour::new(0.0, 0.0, 0.0);
+            }
+
+            fn colour_filtered(&self, _u: f64, _v: f64, _p: V3, footprint: &Footprint) -> Colour {
+                return Colour::new(footprint.dudx, footprint.dvdx, 0.0);
+            }
+        }
+
+        let zero = V3::new(0.0, 0.0, 0.0);
+        let footprint = Footprint {
+            dpdx: zero,
+            dpdy: zero,
+            dudx: 0.1,
+            dvdx: 0.0,
+            dudy: 0.0,
+            dvdy: 0.1,
+        };
+        let texture = UvTransform::new(DuDx, (2.0, 2.0), 90.0, (5.0, 5.0));
+        let colour = texture.colour_filtered(0.0, 0.0, zero, &footprint);
+        assert_close(colour.red, 0.0);
+        assert_close(colour.green, 0.2);
+    }
+
+    #[test]
+    fn ramp_recolours_by_brightness() {
+        let p = V3::new(0.0, 0.0, 0.0);
+        let ramp =
+            ColourRamp::new_from_colours(Colour::new(0.0, 0.0, 1.0), Colour::new(1.0, 0.0, 0.0));
+        let colour =
+            Ramp::new(SolidColour::new(Colour::new(0.75, 0.75, 0.0)), ramp).colour(0.0, 0.0, p);
+        assert_close(colour.red, 0.5);
+        assert_close(colour.blue, 0.5);
+    }
+
+    #[test]
+    fn triplanar_projects_along_the_nearest_axis() {
+        let centre = V3::new(0.0, 0.0, 0.0);
+        let texture = Triplanar::new(Uvs, centre, 2.0, 8.0);
+        // straight above the centre, the texture is laid along y, taking x and z
+        let above = texture.colour(0.0, 0.0, V3::new(0.0, 1.0, 0.25));
+        assert_close(above.red, 0.0);
+        assert!(f64::abs(above.green - 0.5) < 1e-3);
+        // off to the side along x, it takes z and y
+        let side = texture.colour(0.0, 0.0, V3::new(1.0, 0.125, 0.0));
+        assert!(f64::abs(side.red - 0.0) < 1e-3);
+        assert!(f64::abs(side.green - 0.25) < 1e-3);
+        // at the centre itself nothing points anywhere
+        assert_close(texture.colour(0.0, 0.0, centre).red, 0.0);
+    }
+
+    fn footprint(dudx: f64, dvdy: f64, width: f64) -> Footprint {
+        return Footprint {
+            dpdx: V3::new(width, 0.0, 0.0),
+            dpdy: V3::new(0.0, width, width),
+            dudx,
+            dvdx: 0.0,
+            dudy: 0.0,
+            dvdy,
+        };
+    }
+
+    #[test]
+    fn mip_levels_average_the_image() {
+        // alternating black and white pixels, four by four
+        let image = (0..4)
+            .map(|j| {
+                (0..4)
+                    .map(|i| {
+                        let value = ((i + j) % 2) as f64;
+                        Colour::new(value, value, value)
+                    })
+                    .collect()
+            })
+            .collect();
+        let texture = ImageTexture::new(image, Addressing::Wrap);
+        assert_eq!(texture.levels.len(), 3);
+        assert_close(texture.levels[2].pixels[0].red, 0.5);
+
+        let p = V3::new(0.0, 0.0, 0.0);
+        let (u, v) = (0.125, 0.125);
+        // less than a pixel across: the full size image
+        let sharp = texture.colour_filtered(u, v, p, &footprint(0.1, 0.1, 0.0));
+        assert_close(sharp.red, texture.colour(u, v, p).red);
+        // the whole image across: the single pixel at the bottom
+        let blurred = texture.colour_filtered(u, v, p, &footprint(1.0, 0.0, 0.0));
+        assert_close(blurred.red, 0.5);
+    }
+
+    #[test]
+    fn checkers_fade_to_grey_when_filtered_widely() {
+        let texture = Checkers::new_from_colours(
+            std::f64::consts::PI,
+            Colour::new(1.0, 1.0, 1.0),
+            Colour::new(0.0, 0.0, 0.0),
+        );
+        let p = V3::new(0.5, 0.5, 0.5);
+        let point = texture.colour_filtered(0.0, 0.0, p, &footprint(0.0, 0.0, 0.0));
+        assert_close(point.red, texture.colour(0.0, 0.0, p).red);
+        // a whole period in every direction averages to an even blend
+        let wide = texture.colour_filtered(0.0, 0.0, p, &footprint(0.0, 0.0, 2.0));
+        assert_close(wide.red, 0.5);
+        // less than a whole period only softens the check
+        let narrow = texture.colour_filtered(0.0, 0.0, p, &footprint(0.0, 0.0, 1.5));
+        assert!(narrow.red > 0.5 && narrow.red < 1.0);
+    }
 }
@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use crate::{
+    aabb::AABB,
+    hittable::{HitRecord, Hittable},
+    ray::{Ray, RayDifferentials},
+    v3::{unit_vector, M4, V3},
+};
+
+/// Places a hittable in the world with an affine transform. Rays are moved
+/// into the object's space to be intersected, and the resulting hit is moved
+/// back out into world space.
+pub struct Transformed {
+    hittable: Arc<dyn Hittable + Send + Sync>,
+    transform: M4,
+    inverse: M4,
+    motion: Option<Motion>,
+}
+
+/// The transform at the end of a motion, which is interpolated towards from
+/// the starting transform over the time range.
+struct Motion {
+    end_transform: M4,
+    time_0: f64,
+    time_1: f64,
+}
+
+impl Transformed {
+    pub fn new(hittable: Arc<dyn Hittable + Send + Sync>, transform: M4) -> Self {
+        let inverse = transform
+            .inverse()
+            .expect("transform for a hittable must be invertible");
+        return Transformed {
+            hittable,
+            transform,
+            inverse,
+            motion: None,
+        };
+    }
+
+    /// A hittable that moves from `start_transform` at `time_0` to
+    /// `end_transform` at `time_1`, interpolating the matrices linearly.
+    pub fn new_moving(
+        hittable: Arc<dyn Hittable + Send + Sync>,
+        start_transform: M4,
+        end_transform: M4,
+        time_0: f64,
+        time_1: f64,
+    ) -> Self {
+        let mut transformed = Transformed::new(hittable, start_transform);
+        transformed.motion = Some(Motion {
+            end_transform,
+            time_0,
+            time_1,
+        });
+        return transformed;
+    }
+
+    fn transform_at(&self, time: f64) -> (M4, M4) {
+        match &self.motion {
+            None => return (self.transform, self.inverse),
+            // a motion with no time to happen in stays at the start
+            Some(motion) if motion.time_1 == motion.time_0 => {
+                return (self.transform, self.inverse);
+            }
+            Some(motion) => {
+                // rays outside the time range see the object held at the
+                // nearest end, where the bounding box expects it
+                let proportion = (time - motion.time_0) / (motion.time_1 - motion.time_0);
+                let proportion = f64::clamp(proportion, 0.0, 1.0);
+                let transform = M4::lerp(self.transform, motion.end_transform, proportion);
+                let inverse = transform
+                    .inverse()
+                    .expect("transform for a hittable must be invertible");
+                return (transform, inverse);
+            }
+        }
+    }
+
+    pub fn translate(hittable: Arc<dyn Hittable + Send + Sync>, offset: V3) -> Self {
+        return Transformed::new(hittable, M4::translation(offset));
+    }
+
+    pub fn rotate_y(hittable: Arc<dyn Hittable + Send + Sync>, angle: f64) -> Self {
+        return Transformed::new(hittable, M4::rotation_y(angle));
+    }
+}
+
+impl Hittable for Transformed {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // the direction is deliberately not normalised, so that t is the same
+        // in both object and world space
+        let (transform, inverse) = self.transform_at(ray.time);
+        let object_ray = transform_ray(&inverse, ray);
+        let mut hit_record = self.hittable.hit(&object_ray, t_min, t_max)?;
+        hit_record.point = transform.transform_point(hit_record.point);
+        // the normal already faces against the object space ray, and the
+        // inverse transpose preserves which side of the surface it is on
+        hit_record.normal = unit_vector(inverse.transform_normal(hit_record.normal));
+        hit_record.shading_normal =
+            unit_vector(inverse.transform_normal(hit_record.shading_normal));
+        hit_record.dpdu = transform.transform_vector(hit_record.dpdu);
+        hit_record.dpdv = transform.transform_vector(hit_record.dpdv);
+        return Some(hit_record);
+    }
+
+    fn bounding_box(&self, t_0: f64, t_1: f64) -> Option<AABB> {
+        let object_box = self.hittable.bounding_box(t_0, t_1)?;
+        let start_box = transform_box(&self.transform, &object_box);
+        match &self.motion {
+            None => return Some(start_box),
+            // every point moves in a straight line between its start and end
+            // positions, so the union of the two boxes covers the whole path
+            Some(motion) => {
+                let end_box = transform_box(&motion.end_transform, &object_box);
+                return Some(start_box + end_box);
+            }
+        }
+    }
+}
+
+/// The ray moved by `transform`, along with its differentials, keeping its
+/// time and wavelength.
+pub fn transform_ray(transform: &M4, ray: &Ray) -> Ray {
+    let mut transformed = ray.with_origin_direction(
+        transform.transform_point(ray.origin),
+        transform.transform_vector(ray.direction),
+    );
+    transformed.differentials = ray.differentials.map(|d| RayDifferentials {
+        x_origin: transform.transform_point(d.x_origin),
+        x_direction: transform.transform_vector(d.x_direction),
+        y_origin: transform.transform_point(d.y_origin),
+        y_direction: transform.transform_vector(d.y_direction),
+    });
+    return transformed;
+}
+
+/// The world space box containing all eight transformed corners of the
+/// object space box.
+pub fn transform_box(transform: &M4, object_box: &AABB) -> AABB {
+    let corner = |i: usize| {
+        let pick = |bit: usize, axis: usize| {
+            if i & bit == 0 {
+                object_box.minimum.get_by_index(axis)
+            } else {
+                object_box.maximum.get_by_index(axis)
+            }
+        };
+        return transform.transform_point(V3::new(pick(1, 0), pick(2, 1), pick(4, 2)));
+    };
+    let first = corner(0);
+    let mut world_box = AABB::new(first, first);
+    for i in 1..8 {
+        let p = corner(i);
+        world_box = world_box + AABB::new(p, p);
+    }
+    return world_box;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{colour::Colour, hittable::Sphere, material::Lambertian};
+
+    fn assert_close_v3(a: V3, b: V3) {
+        assert!((a - b).length() < 1e-6);
+    }
+
+    fn sphere() -> Arc<dyn Hittable + Send + Sync> {
+        let material = Arc::new(Lambertian::new(Colour::new(0.5, 0.5, 0.5)));
+        return Arc::new(Sphere::new(V3::new(0.0, 0.0, 0.0), 1.0, material));
+    }
+
+    #[test]
+    fn translated_sphere_hit_in_world_space() {
+        let translated = Transformed::translate(sphere(), V3::new(0.0, 0.0, -5.0));
+        let ray = Ray::new(V3::new(0.0, 0.0, 0.0), V3::new(0.0, 0.0, -1.0), 0.0);
+        let hit = translated.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 4.0) < 1e-6);
+        assert_close_v3(hit.point, V3::new(0.0, 0.0, -4.0));
+        assert_close_v3(hit.normal, V3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn scaled_sphere_keeps_unit_normals_and_box() {
+        let scaled = Transformed::new(sphere(), M4::scaling(V3::new(2.0, 1.0, 1.0)));
+        let ray = Ray::new(V3::new(-5.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.0);
+        let hit = scaled.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 3.0) < 1e-6);
+        assert_close_v3(hit.normal, V3::new(-1.0, 0.0, 0.0));
+        let b = scaled.bounding_box(0.0, 1.0).unwrap();
+        assert_close_v3(b.maximum, V3::new(2.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn transformed_ray_keeps_wavelength_and_moves_differentials() {
+        let differentials = RayDifferentials {
+            x_origin: V3::new(1.0, 0.0, 0.0),
+            x_direction: V3::new(0.0, 0.0, -1.0),
+            y_origin: V3::new(0.0, 1.0, 0.0),
+            y_direction: V3::new(0.0, 0.0, -1.0),
+        };
+        let mut ray = Ray::new_with_differentials(
+            V3::new(0.0, 0.0, 0.0),
+            V3::new(0.0, 0.0, -1.0),
+            0.5,
+            differentials,
+        );
+        ray.wavelength = Some(500.0);
+        let moved = transform_ray(&M4::translation(V3::new(0.0, 0.0, 2.0)), &ray);
+        assert_eq!(moved.wavelength, Some(500.0));
+        assert_eq!(moved.time, 0.5);
+        let moved_differentials = moved.differentials.unwrap();
+        assert_close_v3(moved_differentials.x_origin, V3::new(1.0, 0.0, 2.0));
+        assert_close_v3(moved_differentials.y_direction, V3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn moving_transform_interpolates_with_ray_time() {
+        let end = M4::translation(V3::new(0.0, 2.0, 0.0));
+        let moving = Transformed::new_moving(sphere(), M4::identity(), end, 0.0, 1.0);
+        let ray = Ray::new(V3::new(-5.0, 1.0, 0.0), V3::new(1.0, 0.0, 0.0), 0.5);
+        let hit = moving.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 4.0) < 1e-6);
+    }
+
+    #[test]
+    fn moving_transform_with_no_shutter_time_stays_at_start() {
+        let end = M4::translation(V3::new(0.0, 2.0, 0.0));
+        let moving = Transformed::new_moving(sphere(), M4::identity(), end, 1.0, 1.0);
+        let ray = Ray::new(V3::new(-5.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), 1.0);
+        let hit = moving.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert!(f64::abs(hit.time - 4.0) < 1e-6);
+        assert_close_v3(hit.point, V3::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn moving_transform_holds_still_outside_the_shutter_interval() {
+        let end = M4::translation(V3::new(0.0, 2.0, 0.0));
+        let moving = Transformed::new_moving(sphere(), M4::identity(), end, 0.0, 1.0);
+        // long after the end, the sphere stays where it stopped
+        let ray = Ray::new(V3::new(-5.0, 2.0, 0.0), V3::new(1.0, 0.0, 0.0), 3.0);
+        let hit = moving.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_close_v3(hit.point, V3::new(-1.0, 2.0, 0.0));
+        // and before the start, where it began
+        let ray = Ray::new(V3::new(-5.0, 0.0, 0.0), V3::new(1.0, 0.0, 0.0), -2.0);
+        let hit = moving.hit(&ray, 0.001, f64::INFINITY).unwrap();
+        assert_close_v3(hit.point, V3::new(-1.0, 0.0, 0.0));
+        let ray = Ray::new(V3::new(-5.0, 6.0, 0.0), V3::new(1.0, 0.0, 0.0), 3.0);
+        assert!(moving.hit(&ray, 0.001, f64::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{ray::Ray, v3::V3};
+
+/// Intersect a ray with the triangle `p0`, `p1`, `p2` using the
+/// Möller-Trumbore algorithm. Returns the t of the hit and the barycentric
+/// weights of `p1` and `p2` there.
+pub fn hit_triangle(
+    ray: &Ray,
+    p0: V3,
+    p1: V3,
+    p2: V3,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let edge_1 = p1 - p0;
+    let edge_2 = p2 - p0;
+    let h = V3::cross(ray.direction, edge_2);
+    let determinant = V3::dot(edge_1, h);
+    if f64::abs(determinant) < 1e-12 {
+        // the ray is parallel to the triangle
+        return None;
+    }
+    let inv_determinant = 1.0 / determinant;
+    let s = ray.origin - p0;
+    let b1 = inv_determinant * V3::dot(s, h);
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+    let q = V3::cross(s, edge_1);
+    let b2 = inv_determinant * V3::dot(ray.direction, q);
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+    let t = inv_determinant * V3::dot(edge_2, q);
+    if t < t_min || t > t_max {
+        return None;
+    }
+    return Some((t, b1, b2));
+}
+
+/// How a point on a triangle moves with u and v, given the position and uv
+/// of each corner. Zero if the uvs don't span the triangle.
+pub fn triangle_uv_derivatives(positions: [V3; 3], uvs: [(f64, f64); 3]) -> (V3, V3) {
+    let (du_1, dv_1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+    let (du_2, dv_2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+    let determinant = du_1 * dv_2 - dv_1 * du_2;
+    if f64::abs(determinant) < 1e-12 {
+        return (V3::new(0.0, 0.0, 0.0), V3::new(0.0, 0.0, 0.0));
+    }
+    let edge_1 = positions[1] - positions[0];
+    let edge_2 = positions[2] - positions[0];
+    let dpdu = (dv_2 * edge_1 - dv_1 * edge_2) / determinant;
+    let dpdv = (du_1 * edge_2 - du_2 * edge_1) / determinant;
+    return (dpdu, dpdv);
+}
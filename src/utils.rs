@@ -1,3 +1,72 @@
 pub fn scale(proportion: f64, min: f64, max: f64) -> f64 {
     return min + proportion * (max - min);
 }
+
+/// The real roots of the polynomial with the given coefficients (lowest
+/// power first), in ascending order. Roots are bracketed between the turning
+/// points of the polynomial, found recursively from its derivative, and then
+/// refined by bisection.
+pub fn solve_polynomial(coefficients: &[f64]) -> Vec<f64> {
+    // drop leading coefficients which are (close to) zero
+    let mut degree = coefficients.len() - 1;
+    let largest = coefficients.iter().fold(0.0, |a: f64, c| a.max(c.abs()));
+    while degree > 0 && coefficients[degree].abs() <= largest * 1e-12 {
+        degree -= 1;
+    }
+    let coefficients = &coefficients[..=degree];
+    match degree {
+        0 => return vec![],
+        1 => return vec![-coefficients[0] / coefficients[1]],
+        _ => {}
+    }
+
+    let evaluate = |x: f64| coefficients.iter().rev().fold(0.0, |acc, c| acc * x + c);
+    let derivative: Vec<f64> = coefficients[1..]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (i + 1) as f64 * c)
+        .collect();
+
+    // Cauchy's bound on the size of any root
+    let leading = coefficients[degree];
+    let bound = 1.0
+        + coefficients[..degree]
+            .iter()
+            .fold(0.0, |a: f64, c| a.max((c / leading).abs()));
+
+    let mut edges = vec![-bound];
+    edges.extend(
+        solve_polynomial(&derivative)
+            .into_iter()
+            .filter(|x| x.abs() < bound),
+    );
+    edges.push(bound);
+
+    let mut roots = vec![];
+    for pair in edges.windows(2) {
+        let (mut low, mut high) = (pair[0], pair[1]);
+        let (f_low, f_high) = (evaluate(low), evaluate(high));
+        if f_low == 0.0 {
+            roots.push(low);
+            continue;
+        }
+        if f_low.signum() == f_high.signum() {
+            continue;
+        }
+        // the polynomial is monotonic between turning points, so there is
+        // exactly one root in here
+        for _ in 0..100 {
+            let mid = 0.5 * (low + high);
+            if mid <= low || mid >= high {
+                break;
+            }
+            if evaluate(mid).signum() == f_low.signum() {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        roots.push(0.5 * (low + high));
+    }
+    return roots;
+}
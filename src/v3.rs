@@ -38,7 +38,9 @@ impl V3 {
 
     pub fn near_zero(self) -> bool {
         let threshold = 1e-8;
-        return self.x < threshold && self.y < threshold && self.x < threshold;
+        return f64::abs(self.x) < threshold
+            && f64::abs(self.y) < threshold
+            && f64::abs(self.z) < threshold;
     }
 
     pub fn get_by_index(&self, i: usize) -> f64 {
@@ -48,6 +50,14 @@ impl V3 {
             _ => self.z,
         }
     }
+
+    pub fn set_by_index(&mut self, i: usize, value: f64) {
+        match i {
+            0 => self.x = value,
+            1 => self.y = value,
+            _ => self.z = value,
+        }
+    }
 }
 
 impl Neg for V3 {
@@ -152,3 +162,189 @@ pub fn random_unit_vector() -> V3 {
 pub fn reflect(v: V3, n: V3) -> V3 {
     return v - 2.0 * V3::dot(v, n) * n;
 }
+
+/// A 4x4 matrix, used for affine transforms. Points are treated as having an
+/// implicit w of 1 and vectors as having an implicit w of 0.
+#[derive(Clone, Copy)]
+pub struct M4 {
+    pub m: [[f64; 4]; 4],
+}
+
+impl M4 {
+    pub fn new(m: [[f64; 4]; 4]) -> Self {
+        return M4 { m };
+    }
+
+    pub fn identity() -> Self {
+        return M4::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    pub fn translation(offset: V3) -> Self {
+        return M4::new([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    pub fn scaling(factors: V3) -> Self {
+        return M4::new([
+            [factors.x, 0.0, 0.0, 0.0],
+            [0.0, factors.y, 0.0, 0.0],
+            [0.0, 0.0, factors.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    /// Rotation about the x axis, in degrees.
+    pub fn rotation_x(angle: f64) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        return M4::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, cos, -sin, 0.0],
+            [0.0, sin, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    /// Rotation about the y axis, in degrees.
+    pub fn rotation_y(angle: f64) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        return M4::new([
+            [cos, 0.0, sin, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [-sin, 0.0, cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    /// Rotation about the z axis, in degrees.
+    pub fn rotation_z(angle: f64) -> Self {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        return M4::new([
+            [cos, -sin, 0.0, 0.0],
+            [sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+    }
+
+    /// Elementwise linear interpolation between two matrices.
+    pub fn lerp(a: M4, b: M4, proportion: f64) -> M4 {
+        return M4::new(std::array::from_fn(|i| {
+            return std::array::from_fn(|j| a.m[i][j] + proportion * (b.m[i][j] - a.m[i][j]));
+        }));
+    }
+
+    pub fn transpose(&self) -> M4 {
+        return M4::new(std::array::from_fn(|i| {
+            return std::array::from_fn(|j| self.m[j][i]);
+        }));
+    }
+
+    /// The inverse of the matrix, found by Gauss-Jordan elimination. Returns
+    /// None if the matrix is singular.
+    pub fn inverse(&self) -> Option<M4> {
+        let mut a = self.m;
+        let mut inv = M4::identity().m;
+        for col in 0..4 {
+            // partial pivoting: use the row with the largest value in this column
+            let mut pivot = col;
+            for row in (col + 1)..4 {
+                if f64::abs(a[row][col]) > f64::abs(a[pivot][col]) {
+                    pivot = row;
+                }
+            }
+            if f64::abs(a[pivot][col]) < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1.0 / a[col][col];
+            for j in 0..4 {
+                a[col][j] *= scale;
+                inv[col][j] *= scale;
+            }
+            for row in 0..4 {
+                if row != col {
+                    let factor = a[row][col];
+                    for j in 0..4 {
+                        a[row][j] -= factor * a[col][j];
+                        inv[row][j] -= factor * inv[col][j];
+                    }
+                }
+            }
+        }
+        return Some(M4::new(inv));
+    }
+
+    pub fn transform_point(&self, p: V3) -> V3 {
+        let m = &self.m;
+        let x = m[0][0] * p.x + m[0][1] * p.y + m[0][2] * p.z + m[0][3];
+        let y = m[1][0] * p.x + m[1][1] * p.y + m[1][2] * p.z + m[1][3];
+        let z = m[2][0] * p.x + m[2][1] * p.y + m[2][2] * p.z + m[2][3];
+        let w = m[3][0] * p.x + m[3][1] * p.y + m[3][2] * p.z + m[3][3];
+        if w == 1.0 {
+            return V3::new(x, y, z);
+        }
+        return V3::new(x, y, z) / w;
+    }
+
+    pub fn transform_vector(&self, v: V3) -> V3 {
+        let m = &self.m;
+        return V3::new(
+            m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+            m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+            m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+        );
+    }
+
+    /// Transform a normal. This should be called on the *inverse* of the
+    /// matrix that transforms points, as normals transform by the inverse
+    /// transpose.
+    pub fn transform_normal(&self, n: V3) -> V3 {
+        return self.transpose().transform_vector(n);
+    }
+}
+
+impl Mul<M4> for M4 {
+    type Output = M4;
+
+    fn mul(self, other: M4) -> Self::Output {
+        return M4::new(std::array::from_fn(|i| {
+            return std::array::from_fn(|j| (0..4).map(|k| self.m[i][k] * other.m[k][j]).sum());
+        }));
+    }
+}
+
+/// Two unit vectors which, together with the unit vector `n`, form an
+/// orthonormal basis.
+pub fn orthonormal_basis(n: V3) -> (V3, V3) {
+    // Duff et al., "Building an Orthonormal Basis, Revisited"
+    let sign = f64::copysign(1.0, n.z);
+    let a = -1.0 / (sign + n.z);
+    let b = n.x * n.y * a;
+    let s = V3::new(1.0 + sign * n.x * n.x * a, sign * b, -sign * n.x);
+    let t = V3::new(b, sign + n.y * n.y * a, -n.y);
+    return (s, t);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn near_zero_checks_every_axis_both_ways() {
+        assert!(V3::new(1e-9, -1e-9, 0.0).near_zero());
+        assert!(!V3::new(0.0, -1.0, 0.0).near_zero());
+        assert!(!V3::new(-1.0, -1.0, 0.0).near_zero());
+        assert!(!V3::new(0.0, 0.0, 1.0).near_zero());
+    }
+}